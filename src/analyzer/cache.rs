@@ -0,0 +1,397 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{Result, Context};
+use serde::{Deserialize, Serialize};
+
+use crate::analyzer::{DependencyUsage, DependencyUsageData};
+use crate::analyzer::cfg::Cfg;
+use crate::analyzer::module_graph::ModuleInfo;
+use crate::manifest::dependency::{Dependency, Ecosystem};
+
+/// Bumped whenever the analyzer records usages differently, invalidating older caches
+const CACHE_FORMAT: u32 = 12;
+
+/// Directory below the project holding the cache files
+const CACHE_DIR: &str = "target/why";
+
+/// Per-file usage results of a package from a previous run
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UsageCache {
+    /// Analyzer version and cache format the results were produced with
+    version: String,
+    /// Hash of the dependency set and trait method map the results were produced for
+    dependencies: u64,
+    /// Results keyed by file path relative to the package directory
+    files: HashMap<PathBuf, CachedFile>,
+}
+
+/// Usage results of a single file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedFile {
+    /// Hash of the file content and its place in the module tree
+    hash: u64,
+    /// Usages per dependency name
+    usages: HashMap<String, Vec<DependencyUsage>>,
+    /// Usages of crate names matching no dependency, per crate name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    unknown_crates: HashMap<String, Vec<DependencyUsage>>,
+    /// Features named by cfg predicates in the file
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    cfg_features: BTreeSet<String>,
+}
+
+impl UsageCache {
+    /// Create an empty cache for the given dependency set and trait method map
+    pub fn new(dependencies: &[Dependency], trait_methods: &BTreeMap<String, Vec<String>>) -> Self {
+        Self {
+            version: cache_version(),
+            dependencies: dependency_hash(dependencies, trait_methods),
+            files: HashMap::new(),
+        }
+    }
+    
+    /// Load the cache at `path`, falling back to an empty cache when it is missing, unreadable
+    /// or was produced by another analyzer version or for another dependency set or trait method map
+    pub fn load(path: &Path, dependencies: &[Dependency], trait_methods: &BTreeMap<String, Vec<String>>) -> Self {
+        let fresh = Self::new(dependencies, trait_methods);
+        
+        let cache: Option<Self> = fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok());
+        
+        match cache {
+            Some(cache) if cache.version == fresh.version && cache.dependencies == fresh.dependencies => cache,
+            _ => fresh,
+        }
+    }
+    
+    /// Get the cached usages of a file if its content is unchanged.
+    /// Usages point at `file`, whatever path the file was cached under.
+    pub fn get(&self, relative: &Path, file: &Path, hash: u64) -> Option<DependencyUsageData> {
+        let cached = self.files.get(relative).filter(|cached| cached.hash == hash)?;
+        
+        let mut usage_data = DependencyUsageData {
+            usage_locations: cached.usages.clone(),
+            unknown_crates: cached.unknown_crates.clone(),
+            cfg_features: cached.cfg_features.clone(),
+            ..DependencyUsageData::default()
+        };
+        for usage in usage_data.usage_locations.values_mut().chain(usage_data.unknown_crates.values_mut()).flatten() {
+            usage.file = file.to_path_buf();
+        }
+        
+        Some(usage_data)
+    }
+    
+    /// Record the usages found in a file
+    pub fn insert(&mut self, relative: PathBuf, hash: u64, usage_data: &DependencyUsageData) {
+        // Dependencies without usages in this file need no entry
+        let usages = usage_data.usage_locations.iter()
+            .filter(|(_, usages)| !usages.is_empty())
+            .map(|(dep_name, usages)| (dep_name.clone(), usages.clone()))
+            .collect();
+        
+        self.files.insert(relative, CachedFile {
+            hash,
+            usages,
+            unknown_crates: usage_data.unknown_crates.clone(),
+            cfg_features: usage_data.cfg_features.clone(),
+        });
+    }
+    
+    /// Write the cache to `path`, creating its directory if needed
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create cache directory {:?}", dir))?;
+        }
+        
+        let content = serde_json::to_string(self)?;
+        fs::write(path, content)
+            .with_context(|| format!("Failed to write cache file {:?}", path))
+    }
+}
+
+/// Directory holding the cache files of a project
+pub fn cache_dir(project_path: &Path) -> PathBuf {
+    project_path.join(CACHE_DIR)
+}
+
+/// Path of the cache file for a package of a project; packages of different ecosystems
+/// may share a name, so each ecosystem has its own directory, and packages of the same
+/// ecosystem may too, so the file name includes a hash of the manifest's relative path
+pub fn cache_file(project_path: &Path, ecosystem: Ecosystem, package: &str, manifest_path: &Path) -> PathBuf {
+    let relative = manifest_path.strip_prefix(project_path).unwrap_or(manifest_path);
+    // Hash with `/` separators so the key does not depend on the platform
+    let relative = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    
+    // Scoped npm package names contain a slash
+    cache_dir(project_path)
+        .join(ecosystem.id())
+        .join(format!("{}-{:016x}.json", package.replace('/', "__"), content_hash(relative.as_bytes())))
+}
+
+/// Remove the cache of a project. Returns whether there was one.
+pub fn clean(project_path: &Path) -> Result<bool> {
+    let dir = cache_dir(project_path);
+    if !dir.exists() {
+        return Ok(false);
+    }
+    
+    fs::remove_dir_all(&dir)
+        .with_context(|| format!("Failed to remove cache directory {:?}", dir))?;
+    Ok(true)
+}
+
+/// Hash file content; stable across runs and platforms, unlike `std`'s hashers
+pub fn content_hash(content: &[u8]) -> u64 {
+    fnv1a(0xcbf29ce484222325, content)
+}
+
+/// Hash a source file together with its place in the package: its target, module path, cfg
+/// and API visibility, which are recorded in its usages
+pub fn file_hash(content: &str, module: &ModuleInfo) -> u64 {
+    let cfg = module.cfg.as_ref().map(Cfg::to_string).unwrap_or_default();
+    let context = format!("{}\n{}\n{}\n{}\n", module.target.id(), module.module, cfg, module.public);
+    fnv1a(content_hash(context.as_bytes()), content.as_bytes())
+}
+
+/// Continue a 64-bit FNV-1a hash over more bytes
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Hash the parts of a dependency set that influence which usages are recorded, along with
+/// the trait methods calls are attributed through
+fn dependency_hash(dependencies: &[Dependency], trait_methods: &BTreeMap<String, Vec<String>>) -> u64 {
+    let mut names: Vec<String> = dependencies.iter()
+        .map(|dep| format!("{}={}", dep.name, dep.import_name))
+        .collect();
+    names.sort();
+    names.dedup();
+    
+    let methods: Vec<String> = trait_methods.iter()
+        .map(|(name, methods)| format!("{}={}", name, methods.join(",")))
+        .collect();
+    
+    let hash = content_hash(names.join("\n").as_bytes());
+    fnv1a(hash, format!("\n\n{}", methods.join("\n")).as_bytes())
+}
+
+fn cache_version() -> String {
+    format!("{}+{}", env!("CARGO_PKG_VERSION"), CACHE_FORMAT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use crate::analyzer::UsageType;
+    use crate::analyzer::source::{analyze_files_cached, FileAnalysis};
+    use crate::manifest::cargo::TargetKind;
+    use crate::manifest::dependency::{CargoExtras, DependencyExtras, DependencySource, DependencyType};
+    
+    fn dependency(name: &str) -> Dependency {
+        Dependency {
+            name: name.to_string(),
+            import_name: name.replace('-', "_"),
+            package: None,
+            version: None,
+            features: Vec::new(),
+            optional: false,
+            dependency_type: DependencyType::Normal,
+            source: DependencySource::default(),
+            extras: DependencyExtras::Rust(CargoExtras {
+                default_features: true,
+                platforms: Vec::new(),
+                all_platforms: true,
+            }),
+        }
+    }
+    
+    fn library_module() -> ModuleInfo {
+        ModuleInfo {
+            target: TargetKind::Lib,
+            module: "crate".to_string(),
+            cfg: None,
+            public: true,
+        }
+    }
+    
+    /// Usage of `item` recorded in `file`
+    fn usage(file: &str, item: &str) -> DependencyUsage {
+        DependencyUsage {
+            file: PathBuf::from(file),
+            line: 1,
+            column: 5,
+            end_line: 1,
+            end_column: 5 + item.len(),
+            imported_item: item.to_string(),
+            item: Some(item.to_string()),
+            usage_type: UsageType::Import,
+            target: TargetKind::Lib,
+            module: "crate".to_string(),
+            cfg: None,
+            inactive: false,
+            in_macro: false,
+            exposure: None,
+        }
+    }
+    
+    /// Save a cache of `src/lib.rs`, hashed 42, made for `dependencies` and `trait_methods` to
+    /// `path`, then load it for `reload_dependencies` and `reload_methods`
+    fn reload(
+        path: &Path,
+        dependencies: &[Dependency],
+        trait_methods: &BTreeMap<String, Vec<String>>,
+        reload_dependencies: &[Dependency],
+        reload_methods: &BTreeMap<String, Vec<String>>,
+    ) -> UsageCache {
+        let mut usage_data = DependencyUsageData::default();
+        usage_data.usage_locations.insert("serde".to_string(), vec![usage("src/lib.rs", "serde::Serialize")]);
+        
+        let mut cache = UsageCache::new(dependencies, trait_methods);
+        cache.insert(PathBuf::from("src/lib.rs"), 42, &usage_data);
+        cache.save(path).unwrap();
+        
+        UsageCache::load(path, reload_dependencies, reload_methods)
+    }
+    
+    #[test]
+    fn returns_unchanged_files_with_usages_in_the_given_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.json");
+        let dependencies = vec![dependency("serde")];
+        
+        let mut usage_data = DependencyUsageData::default();
+        usage_data.usage_locations.insert("serde".to_string(), vec![usage("old/src/lib.rs", "serde::Serialize")]);
+        usage_data.unknown_crates.insert("serde_derive".to_string(), vec![usage("old/src/lib.rs", "serde_derive::Serialize")]);
+        usage_data.cfg_features.insert("std".to_string());
+        
+        let mut cache = UsageCache::new(&dependencies, &BTreeMap::new());
+        cache.insert(PathBuf::from("src/lib.rs"), 42, &usage_data);
+        cache.save(&path).unwrap();
+        
+        let cache = UsageCache::load(&path, &dependencies, &BTreeMap::new());
+        let cached = cache.get(Path::new("src/lib.rs"), Path::new("new/src/lib.rs"), 42).unwrap();
+        
+        assert_eq!(cached.usage_locations["serde"][0].file, PathBuf::from("new/src/lib.rs"));
+        assert_eq!(cached.usage_locations["serde"][0].imported_item, "serde::Serialize");
+        assert_eq!(cached.unknown_crates["serde_derive"][0].file, PathBuf::from("new/src/lib.rs"));
+        assert_eq!(cached.cfg_features, BTreeSet::from(["std".to_string()]));
+        
+        assert!(cache.get(Path::new("src/lib.rs"), Path::new("src/lib.rs"), 43).is_none());
+        assert!(cache.get(Path::new("src/main.rs"), Path::new("src/main.rs"), 42).is_none());
+    }
+    
+    #[test]
+    fn hashes_content_and_module_info() {
+        let module = library_module();
+        let hash = file_hash("use serde::Serialize;\n", &module);
+        
+        assert_eq!(file_hash("use serde::Serialize;\n", &library_module()), hash);
+        assert_ne!(file_hash("use serde::Deserialize;\n", &module), hash);
+        
+        let changed = [
+            ModuleInfo { target: TargetKind::Bin, ..library_module() },
+            ModuleInfo { module: "crate::ser".to_string(), ..library_module() },
+            ModuleInfo { cfg: Some(Cfg::Name("unix".to_string())), ..library_module() },
+            ModuleInfo { public: false, ..library_module() },
+        ];
+        for module in &changed {
+            assert_ne!(file_hash("use serde::Serialize;\n", module), hash, "{:?}", module);
+        }
+    }
+    
+    #[test]
+    fn invalidates_caches_of_other_dependency_sets() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.json");
+        let dependencies = vec![dependency("serde")];
+        let no_methods = BTreeMap::new();
+        let get = |cache: &UsageCache| cache.get(Path::new("src/lib.rs"), Path::new("src/lib.rs"), 42);
+        
+        assert!(get(&reload(&path, &dependencies, &no_methods, &dependencies, &no_methods)).is_some());
+        
+        let added = vec![dependency("serde"), dependency("rand")];
+        assert!(get(&reload(&path, &dependencies, &no_methods, &added, &no_methods)).is_none());
+        
+        let renamed = vec![Dependency { import_name: "serde1".to_string(), ..dependency("serde") }];
+        assert!(get(&reload(&path, &dependencies, &no_methods, &renamed, &no_methods)).is_none());
+    }
+    
+    #[test]
+    fn invalidates_caches_of_other_trait_methods() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.json");
+        let dependencies = vec![dependency("serde")];
+        let methods = BTreeMap::from([("serde::Serialize".to_string(), vec!["serialize".to_string()])]);
+        let more_methods = BTreeMap::from([
+            ("serde::Serialize".to_string(), vec!["serialize".to_string(), "to_json".to_string()]),
+        ]);
+        let get = |cache: &UsageCache| cache.get(Path::new("src/lib.rs"), Path::new("src/lib.rs"), 42);
+        
+        assert!(get(&reload(&path, &dependencies, &methods, &dependencies, &methods)).is_some());
+        assert!(get(&reload(&path, &dependencies, &methods, &dependencies, &more_methods)).is_none());
+        assert!(get(&reload(&path, &dependencies, &methods, &dependencies, &BTreeMap::new())).is_none());
+    }
+    
+    #[test]
+    fn invalidates_caches_of_other_formats() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.json");
+        let dependencies = vec![dependency("serde")];
+        
+        let mut cache = UsageCache::new(&dependencies, &BTreeMap::new());
+        cache.insert(PathBuf::from("src/lib.rs"), 42, &DependencyUsageData::default());
+        cache.version = format!("{}+{}", env!("CARGO_PKG_VERSION"), CACHE_FORMAT - 1);
+        cache.save(&path).unwrap();
+        
+        let cache = UsageCache::load(&path, &dependencies, &BTreeMap::new());
+        assert!(cache.get(Path::new("src/lib.rs"), Path::new("src/lib.rs"), 42).is_none());
+    }
+    
+    #[test]
+    fn reanalyzes_changed_files_and_drops_deleted_ones() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_path = dir.path().join("target/why/rust/demo.json");
+        let (lib, util) = (dir.path().join("src/lib.rs"), dir.path().join("src/util.rs"));
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(&lib, "use serde::Serialize;\n").unwrap();
+        fs::write(&util, "pub fn helper() {}\n").unwrap();
+        
+        let dependencies = vec![dependency("serde")];
+        let options = FileAnalysis { project_path: dir.path(), threads: Some(1), cache_file: Some(&cache_path) };
+        let analyzed = AtomicUsize::new(0);
+        let run = |files: Vec<PathBuf>| {
+            analyzed.store(0, Ordering::SeqCst);
+            analyze_files_cached(&files, &dependencies, &BTreeMap::new(), &options, |_| library_module(), |_, file_path, _, file_data| {
+                analyzed.fetch_add(1, Ordering::SeqCst);
+                file_data.usage_locations.get_mut("serde").unwrap()
+                    .push(usage(&file_path.display().to_string(), "serde::Serialize"));
+            }).unwrap();
+            analyzed.load(Ordering::SeqCst)
+        };
+        
+        assert_eq!(run(vec![lib.clone(), util.clone()]), 2);
+        assert_eq!(run(vec![lib.clone(), util.clone()]), 0);
+        
+        fs::write(&util, "pub fn helper() -> u32 { 1 }\n").unwrap();
+        assert_eq!(run(vec![lib.clone(), util.clone()]), 1);
+        
+        fs::remove_file(&util).unwrap();
+        assert_eq!(run(vec![lib.clone()]), 0);
+        
+        let cache = UsageCache::load(&cache_path, &dependencies, &BTreeMap::new());
+        let files: Vec<&PathBuf> = cache.files.keys().collect();
+        assert_eq!(files, vec![&PathBuf::from("src/lib.rs")]);
+    }
+}
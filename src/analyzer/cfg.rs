@@ -0,0 +1,380 @@
+use std::collections::HashSet;
+use std::fmt;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use syn::punctuated::Punctuated;
+use syn::Token;
+
+use crate::manifest::features::FeatureGraph;
+use crate::utils::config::AnalysisConfig;
+
+/// Names set by every target of a family, as in `#[cfg(unix)]`
+const TARGET_NAMES: [&str; 2] = ["unix", "windows"];
+
+/// A `#[cfg(...)]` predicate
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum Cfg {
+    /// A name such as `unix` or `test`
+    Name(String),
+    /// A key-value pair such as `feature = "serde"` or `target_os = "linux"`
+    KeyValue(String, String),
+    All(Vec<Cfg>),
+    Any(Vec<Cfg>),
+    Not(Box<Cfg>),
+}
+
+impl Cfg {
+    /// Read the predicate of a `#[cfg(...)]` attribute
+    pub fn from_attribute(meta: &syn::Meta) -> Option<Self> {
+        match meta {
+            syn::Meta::List(list) if list.path.is_ident("cfg") => list.parse_args::<syn::Meta>()
+                .ok()
+                .and_then(|predicate| Self::from_meta(&predicate)),
+            _ => None,
+        }
+    }
+    
+    /// Parse a cfg predicate
+    pub fn from_meta(meta: &syn::Meta) -> Option<Self> {
+        let name = meta.path().get_ident()?.to_string();
+        
+        match meta {
+            syn::Meta::Path(_) => Some(Cfg::Name(name)),
+            syn::Meta::NameValue(syn::MetaNameValue {
+                value: syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(value), .. }),
+                ..
+            }) => Some(Cfg::KeyValue(name, value.value())),
+            syn::Meta::NameValue(_) => None,
+            syn::Meta::List(list) => {
+                let nested: Vec<Cfg> = list.parse_args_with(Punctuated::<syn::Meta, Token![,]>::parse_terminated)
+                    .ok()?
+                    .iter()
+                    .map(Self::from_meta)
+                    .collect::<Option<_>>()?;
+                
+                match name.as_str() {
+                    "all" => Some(Cfg::All(nested)),
+                    "any" => Some(Cfg::Any(nested)),
+                    "not" if nested.len() == 1 => nested.into_iter().next().map(|cfg| Cfg::Not(Box::new(cfg))),
+                    _ => None,
+                }
+            }
+        }
+    }
+    
+    /// Combine the predicates of nested items into the one governing the innermost item
+    pub fn all_of(cfgs: &[Cfg]) -> Option<Self> {
+        match cfgs {
+            [] => None,
+            [cfg] => Some(cfg.clone()),
+            cfgs => Some(Cfg::All(cfgs.to_vec())),
+        }
+    }
+    
+    /// Features the predicate refers to
+    pub fn features(&self) -> Vec<&str> {
+        match self {
+            Cfg::KeyValue(key, value) if key == "feature" => vec![value.as_str()],
+            Cfg::Name(_) | Cfg::KeyValue(..) => Vec::new(),
+            Cfg::All(cfgs) | Cfg::Any(cfgs) => cfgs.iter().flat_map(Cfg::features).collect(),
+            Cfg::Not(cfg) => cfg.features(),
+        }
+    }
+    
+    /// Evaluate the predicate with `leaf` giving the value of names and key-value pairs;
+    /// `None` when the value is unknown
+    pub fn evaluate(&self, leaf: &impl Fn(&Cfg) -> Option<bool>) -> Option<bool> {
+        match self {
+            Cfg::All(cfgs) => {
+                let values: Vec<Option<bool>> = cfgs.iter().map(|cfg| cfg.evaluate(leaf)).collect();
+                if values.contains(&Some(false)) {
+                    Some(false)
+                } else if values.iter().all(|value| *value == Some(true)) {
+                    Some(true)
+                } else {
+                    None
+                }
+            }
+            Cfg::Any(cfgs) => {
+                let values: Vec<Option<bool>> = cfgs.iter().map(|cfg| cfg.evaluate(leaf)).collect();
+                if values.contains(&Some(true)) {
+                    Some(true)
+                } else if values.iter().all(|value| *value == Some(false)) {
+                    Some(false)
+                } else {
+                    None
+                }
+            }
+            Cfg::Not(cfg) => cfg.evaluate(leaf).map(|value| !value),
+            Cfg::Name(_) | Cfg::KeyValue(..) => leaf(self),
+        }
+    }
+    
+    /// Whether the predicate is false whenever none of `features` is enabled
+    pub fn requires_any_feature(&self, features: &[String]) -> bool {
+        let leaf = |cfg: &Cfg| match cfg {
+            Cfg::KeyValue(key, value) if key == "feature" && features.contains(value) => Some(false),
+            _ => None,
+        };
+        self.evaluate(&leaf) == Some(false)
+    }
+}
+
+impl fmt::Display for Cfg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |f: &mut fmt::Formatter<'_>, name: &str, cfgs: &[Cfg]| {
+            let cfgs: Vec<String> = cfgs.iter().map(Cfg::to_string).collect();
+            write!(f, "{}({})", name, cfgs.join(", "))
+        };
+        
+        match self {
+            Cfg::Name(name) => write!(f, "{}", name),
+            Cfg::KeyValue(key, value) => write!(f, "{} = {:?}", key, value),
+            Cfg::All(cfgs) => list(f, "all", cfgs),
+            Cfg::Any(cfgs) => list(f, "any", cfgs),
+            Cfg::Not(cfg) => write!(f, "not({})", cfg),
+        }
+    }
+}
+
+impl From<Cfg> for String {
+    fn from(cfg: Cfg) -> Self {
+        cfg.to_string()
+    }
+}
+
+impl TryFrom<String> for Cfg {
+    type Error = String;
+    
+    fn try_from(predicate: String) -> std::result::Result<Self, Self::Error> {
+        syn::parse_str::<syn::Meta>(&predicate)
+            .ok()
+            .and_then(|meta| Self::from_meta(&meta))
+            .ok_or_else(|| format!("invalid cfg predicate {:?}", predicate))
+    }
+}
+
+/// Which features are enabled
+#[derive(Debug, Clone, Default)]
+enum FeatureSelection {
+    /// No features were selected, any of them may be enabled
+    #[default]
+    Any,
+    /// `--all-features`
+    All,
+    /// The selected features, the default features and everything they enable
+    Only(HashSet<String>),
+}
+
+/// Features and target cfgs selected for the analysis. Usages whose cfg predicate is
+/// false for the selection are inactive; parts of a predicate nothing was selected for
+/// (no `--features`, or no `--target-cfg` for a target key) may be either true or false.
+#[derive(Debug, Clone, Default)]
+pub struct CfgSelection {
+    features: FeatureSelection,
+    /// Target cfgs from `--target-cfg`, such as `unix` or `target_os = "linux"`
+    target: Vec<Cfg>,
+}
+
+impl CfgSelection {
+    /// Build the selection for a package from the analysis configuration, resolving the
+    /// selected features through the package's feature graph
+    pub fn for_package(config: &AnalysisConfig, features: &FeatureGraph) -> Result<Self> {
+        let features = if config.all_features {
+            FeatureSelection::All
+        } else if config.features.is_empty() {
+            FeatureSelection::Any
+        } else {
+            let mut requested: Vec<&str> = config.features.iter().map(String::as_str).collect();
+            requested.push("default");
+            FeatureSelection::Only(features.enabled_features(&requested))
+        };
+        
+        let target = config.target_cfg.iter()
+            .map(|cfg| parse_target_cfg(cfg))
+            .collect::<Result<_>>()?;
+        
+        Ok(Self { features, target })
+    }
+    
+    /// Whether code governed by `cfg` can be compiled with this selection
+    pub fn is_active(&self, cfg: &Cfg) -> bool {
+        self.evaluate(cfg) != Some(false)
+    }
+    
+    /// Evaluate a predicate; `None` when it depends on something that was not selected
+    fn evaluate(&self, cfg: &Cfg) -> Option<bool> {
+        cfg.evaluate(&|leaf: &Cfg| self.evaluate_leaf(leaf))
+    }
+    
+    /// Value of a name or key-value pair for this selection
+    fn evaluate_leaf(&self, cfg: &Cfg) -> Option<bool> {
+        match cfg {
+            Cfg::KeyValue(key, value) if key == "feature" => match &self.features {
+                FeatureSelection::Any => None,
+                FeatureSelection::All => Some(true),
+                FeatureSelection::Only(enabled) => Some(enabled.contains(value)),
+            },
+            Cfg::KeyValue(key, _) => {
+                if self.target.contains(cfg) {
+                    Some(true)
+                } else if self.target.is_empty() {
+                    None
+                } else if key.starts_with("target_") || self.target.iter().any(|selected| matches!(selected, Cfg::KeyValue(k, _) if k == key)) {
+                    // Each target has one value per key, so selecting a target makes others false
+                    Some(false)
+                } else {
+                    None
+                }
+            }
+            Cfg::Name(name) => {
+                if self.target.contains(cfg) {
+                    Some(true)
+                } else if !self.target.is_empty() && TARGET_NAMES.contains(&name.as_str()) {
+                    Some(false)
+                } else {
+                    // `test`, `debug_assertions` and custom cfgs are only known when selected
+                    None
+                }
+            }
+            Cfg::All(_) | Cfg::Any(_) | Cfg::Not(_) => self.evaluate(cfg),
+        }
+    }
+}
+
+/// Parse a `--target-cfg` value: `name`, `key=value` or `key="value"`
+fn parse_target_cfg(cfg: &str) -> Result<Cfg> {
+    let parsed = match cfg.split_once('=') {
+        Some((key, value)) => Cfg::KeyValue(key.trim().to_string(), value.trim().trim_matches('"').to_string()),
+        None => Cfg::Name(cfg.trim().to_string()),
+    };
+    
+    match &parsed {
+        Cfg::Name(name) | Cfg::KeyValue(name, _) if name.is_empty() => {
+            Err(anyhow::anyhow!("Invalid target cfg {:?}, expected `name` or `key=value`", cfg))
+        }
+        _ => Ok(parsed),
+    }
+}
+
+/// Features named by `feature = "..."` anywhere in Rust source, as in `#[cfg(...)]`,
+/// `#[cfg_attr(...)]`, `cfg!(...)` and `cfg_if!` branches
+pub fn referenced_features(source: &str) -> Vec<String> {
+    let mut features = Vec::new();
+    
+    for (start, _) in source.match_indices("feature") {
+        // Skip `target_feature` and identifiers merely ending in `feature`
+        let preceded_by_ident = source[..start].chars().next_back()
+            .is_some_and(|c| c.is_alphanumeric() || c == '_');
+        let rest = source[start + "feature".len()..].trim_start();
+        let value = match rest.strip_prefix('=') {
+            Some(value) if !preceded_by_ident && !value.starts_with('=') => value.trim_start(),
+            _ => continue,
+        };
+        
+        if let Some(name) = value.strip_prefix('"').and_then(|value| value.split('"').next()) {
+            features.push(name.to_string());
+        }
+    }
+    
+    features
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    
+    fn cfg(predicate: &str) -> Cfg {
+        Cfg::try_from(predicate.to_string()).unwrap()
+    }
+    
+    fn selection(features: Option<&[&str]>, target: &[&str]) -> CfgSelection {
+        CfgSelection {
+            features: match features {
+                Some(features) => FeatureSelection::Only(features.iter().map(|feature| feature.to_string()).collect()),
+                None => FeatureSelection::Any,
+            },
+            target: target.iter().map(|cfg| parse_target_cfg(cfg).unwrap()).collect(),
+        }
+    }
+    
+    #[test]
+    fn parses_and_displays_predicates() {
+        let predicate = cfg("all(unix, any(feature = \"a\", not(target_os = \"macos\")))");
+        
+        assert_eq!(predicate, Cfg::All(vec![
+            Cfg::Name("unix".to_string()),
+            Cfg::Any(vec![
+                Cfg::KeyValue("feature".to_string(), "a".to_string()),
+                Cfg::Not(Box::new(Cfg::KeyValue("target_os".to_string(), "macos".to_string()))),
+            ]),
+        ]));
+        assert_eq!(predicate.to_string(), "all(unix, any(feature = \"a\", not(target_os = \"macos\")))");
+        assert_eq!(predicate.features(), vec!["a"]);
+        assert!(Cfg::try_from("not(a, b)".to_string()).is_err());
+        assert!(Cfg::try_from("version(\"1.0\")".to_string()).is_err());
+    }
+    
+    #[test]
+    fn evaluates_with_unknowns() {
+        // With a target selected, `unix` is true, `windows` false and `test` unknown
+        let unix = selection(None, &["unix"]);
+        let evaluate = |predicate: &str| unix.evaluate(&cfg(predicate));
+        
+        assert_eq!(evaluate("all(unix, test)"), None);
+        assert_eq!(evaluate("all(windows, test)"), Some(false));
+        assert_eq!(evaluate("all(unix, unix)"), Some(true));
+        assert_eq!(evaluate("all()"), Some(true));
+        assert_eq!(evaluate("any(windows, test)"), None);
+        assert_eq!(evaluate("any(unix, test)"), Some(true));
+        assert_eq!(evaluate("any(windows, windows)"), Some(false));
+        assert_eq!(evaluate("any()"), Some(false));
+        assert_eq!(evaluate("not(test)"), None);
+        assert_eq!(evaluate("not(any(windows, all(unix, windows)))"), Some(true));
+    }
+    
+    #[test]
+    fn finds_predicates_requiring_features() {
+        let features = vec!["a".to_string(), "b".to_string()];
+        
+        assert!(cfg("feature = \"a\"").requires_any_feature(&features));
+        assert!(cfg("all(unix, any(feature = \"a\", feature = \"b\"))").requires_any_feature(&features));
+        assert!(!cfg("any(feature = \"a\", unix)").requires_any_feature(&features));
+        assert!(!cfg("not(feature = \"a\")").requires_any_feature(&features));
+        assert!(!cfg("feature = \"c\"").requires_any_feature(&features));
+    }
+    
+    #[test]
+    fn evaluates_against_selected_features_and_targets() {
+        let any = selection(None, &[]);
+        assert!(any.is_active(&cfg("feature = \"a\"")));
+        assert!(any.is_active(&cfg("windows")));
+        
+        let linux = selection(Some(&["default", "a"]), &["unix", "target_os=\"linux\""]);
+        assert!(linux.is_active(&cfg("all(unix, feature = \"a\")")));
+        assert!(!linux.is_active(&cfg("feature = \"b\"")));
+        assert!(!linux.is_active(&cfg("windows")));
+        assert!(!linux.is_active(&cfg("target_os = \"macos\"")));
+        assert!(!linux.is_active(&cfg("target_arch = \"x86_64\"")));
+        // Names and keys outside the target are left unknown
+        assert!(linux.is_active(&cfg("test")));
+        assert!(linux.is_active(&cfg("not(test)")));
+        assert!(linux.is_active(&cfg("panic = \"abort\"")));
+    }
+    
+    #[test]
+    fn rejects_empty_target_cfgs() {
+        assert_eq!(parse_target_cfg(" target_env = \"gnu\" ").unwrap(), Cfg::KeyValue("target_env".to_string(), "gnu".to_string()));
+        assert!(parse_target_cfg("=linux").is_err());
+        assert!(parse_target_cfg("").is_err());
+    }
+    
+    #[test]
+    fn finds_referenced_features() {
+        let source = "#[cfg(feature = \"a\")]\nif cfg!(feature=\"b\") {}\n#[cfg(target_feature = \"sse2\")]\nlet my_feature = \"c\"; if feature == \"d\" {}";
+        
+        assert_eq!(referenced_features(source), vec!["a", "b"]);
+    }
+}
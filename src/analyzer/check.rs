@@ -0,0 +1,332 @@
+use std::fmt;
+use serde::Serialize;
+
+use crate::analyzer::{metrics, AnalysisResult, Misplacement, OptionalIssue};
+use crate::manifest::dependency::{Dependency, DependencyType};
+use crate::utils::config::CheckConfig;
+
+/// How serious a finding is; errors make `why check` fail
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// What is wrong with a dependency
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FindingKind {
+    /// The dependency is never used
+    Unused,
+    /// The dependency is used, but so little that it might be removable
+    LowImportance,
+    /// The dependency is declared in the wrong table for where it is used
+    Misplaced,
+    /// The dependency is optional, but no feature enables it
+    OptionalNotEnabled,
+    /// The optional dependency is used by code not guarded by a cfg for a feature enabling it
+    UngatedOptional,
+    /// A feature of the package enables nothing the code uses
+    UnusedFeature,
+}
+
+/// A single problem found by the check
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    /// Package declaring the dependency or feature
+    pub package: String,
+    /// Dependency the finding is about; empty for findings about a feature
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub dependency: String,
+    /// Feature of the package the finding is about
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feature: Option<String>,
+    pub kind: FindingKind,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Outcome of checking a project against the configured policies
+#[derive(Debug, Default, Serialize)]
+pub struct CheckReport {
+    pub findings: Vec<Finding>,
+}
+
+impl CheckReport {
+    /// Number of findings that fail the check
+    pub fn error_count(&self) -> usize {
+        self.findings.iter().filter(|f| f.severity == Severity::Error).count()
+    }
+    
+    /// Number of findings that are only reported
+    pub fn warning_count(&self) -> usize {
+        self.findings.iter().filter(|f| f.severity == Severity::Warning).count()
+    }
+    
+    /// Whether the check passes; with `deny_warnings` any finding fails it
+    pub fn passed(&self, deny_warnings: bool) -> bool {
+        if deny_warnings {
+            self.findings.is_empty()
+        } else {
+            self.error_count() == 0
+        }
+    }
+}
+
+/// Checks analysis results against the policies of the `[check]` config section
+pub struct Checker<'a> {
+    policy: &'a CheckConfig,
+    removal_threshold: f64,
+}
+
+impl<'a> Checker<'a> {
+    /// Create a checker; used dependencies scoring below `removal_threshold` are reported
+    pub fn new(policy: &'a CheckConfig, removal_threshold: f64) -> Self {
+        Self {
+            policy,
+            removal_threshold,
+        }
+    }
+    
+    /// Check a project; workspaces and the projects of a mixed repository are checked package by package
+    pub fn check(&self, result: &AnalysisResult) -> CheckReport {
+        let mut report = CheckReport::default();
+        
+        for package in result.packages() {
+            self.check_package(package, &mut report);
+        }
+        
+        // Errors first, then by package and dependency so the output is stable
+        report.findings.sort_by(|a, b| {
+            a.severity.cmp(&b.severity)
+                .then_with(|| a.package.cmp(&b.package))
+                .then_with(|| a.dependency.cmp(&b.dependency))
+                .then_with(|| a.feature.cmp(&b.feature))
+                .then_with(|| a.kind.cmp(&b.kind))
+        });
+        
+        report
+    }
+    
+    /// Check the dependencies declared by a single package
+    fn check_package(&self, result: &AnalysisResult, report: &mut CheckReport) {
+        let removable = metrics::find_removable_dependencies(&result.metrics, self.removal_threshold);
+        
+        for dep in &result.dependencies {
+            if self.policy.ignore.contains(&dep.name) {
+                continue;
+            }
+            
+            let is_used = *result.metrics.is_used.get(&dep.name).unwrap_or(&false);
+            
+            // A misplaced dependency is reported instead of its low score, as moving it is the actual fix
+            let finding = if !is_used {
+                // Usages compiled out for the selected features and target cfgs are not counted
+                let has_usages = result.usage_data.usage_locations.get(&dep.name).is_some_and(|usages| !usages.is_empty());
+                Some(self.unused_finding(dep, has_usages))
+            } else if let Some(misplacement) = result.metrics.misplaced.get(&dep.name) {
+                Some(self.misplaced_finding(misplacement))
+            } else if removable.contains(&dep.name) {
+                let score = *result.metrics.importance_scores.get(&dep.name).unwrap_or(&0.0);
+                Some(self.low_importance_finding(score))
+            } else {
+                None
+            };
+            
+            let optional_finding = result.metrics.optional_issues.get(&dep.name)
+                .map(|issue| self.optional_finding(issue));
+            
+            for (kind, severity, message) in finding.into_iter().chain(optional_finding) {
+                report.findings.push(Finding {
+                    package: result.name.clone(),
+                    dependency: dep.name.clone(),
+                    feature: None,
+                    kind,
+                    severity,
+                    message,
+                });
+            }
+        }
+        
+        for feature in &result.metrics.unused_package_features {
+            report.findings.push(Finding {
+                package: result.name.clone(),
+                dependency: String::new(),
+                feature: Some(feature.clone()),
+                kind: FindingKind::UnusedFeature,
+                severity: severity(self.policy.fail_on_unused_features),
+                message: "enables nothing the code uses, remove it from [features]".to_string(),
+            });
+        }
+    }
+    
+    fn unused_finding(&self, dep: &Dependency, inactive_usages: bool) -> (FindingKind, Severity, String) {
+        let fails = match dep.dependency_type {
+            DependencyType::Normal | DependencyType::Peer => self.policy.fail_on_unused,
+            DependencyType::Development | DependencyType::Build => self.policy.fail_on_unused_dev,
+        };
+        
+        let message = if inactive_usages {
+            "only used by code disabled for the selected features and target cfgs".to_string()
+        } else {
+            format!("unused, remove it from [{}]", dep.table_headers().join("] and ["))
+        };
+        
+        (FindingKind::Unused, severity(fails), message)
+    }
+    
+    fn low_importance_finding(&self, score: f64) -> (FindingKind, Severity, String) {
+        // Partially used dependencies are also removable with a somewhat higher score
+        let message = if score < self.removal_threshold {
+            format!("importance score {:.2} is below the removal threshold {:.2}", score, self.removal_threshold)
+        } else {
+            format!("importance score {:.2} is low and only some of its enabled features are used", score)
+        };
+        
+        (FindingKind::LowImportance, severity(self.policy.fail_on_low_importance), message)
+    }
+    
+    fn misplaced_finding(&self, misplacement: &Misplacement) -> (FindingKind, Severity, String) {
+        (FindingKind::Misplaced, severity(self.policy.fail_on_misplaced), misplacement.message())
+    }
+    
+    fn optional_finding(&self, issue: &OptionalIssue) -> (FindingKind, Severity, String) {
+        let kind = match issue {
+            OptionalIssue::NotEnabled => FindingKind::OptionalNotEnabled,
+            OptionalIssue::Ungated { .. } => FindingKind::UngatedOptional,
+        };
+        
+        (kind, severity(self.policy.fail_on_optional), issue.message())
+    }
+}
+
+fn severity(fails: bool) -> Severity {
+    if fails { Severity::Error } else { Severity::Warning }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+impl fmt::Display for CheckReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for finding in &self.findings {
+            let subject = match &finding.feature {
+                Some(feature) => format!("feature `{}`", feature),
+                None => finding.dependency.clone(),
+            };
+            writeln!(f, "{}: {} ({}): {}", finding.severity, subject, finding.package, finding.message)?;
+        }
+        
+        if !self.findings.is_empty() {
+            writeln!(f)?;
+        }
+        
+        write!(f, "{} error(s), {} warning(s)", self.error_count(), self.warning_count())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::fs;
+    use crate::analyzer::DependencyAnalyzer;
+    use crate::utils::config::Config;
+    
+    /// Manifest of a package using `serde`, with an unused `rand` and an unused dev-dependency
+    const MANIFEST: &str = "[package]\nname = \"demo\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\nserde = \"1\"\nrand = \"0.8\"\n\n[dev-dependencies]\ntempfile = \"3\"\n";
+    
+    /// Analyze a package with the given manifest whose library uses `serde`
+    fn analyze(manifest: &str) -> AnalysisResult {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("Cargo.toml"), manifest).unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/lib.rs"), "use serde::Serialize;\n\npub fn name<T: Serialize>(_: T) {}\n").unwrap();
+        
+        let mut config = Config::default();
+        config.analysis.use_cache = false;
+        DependencyAnalyzer::new(dir.path()).with_config(config).analyze().unwrap()
+    }
+    
+    /// Findings of a report as `(dependency, kind, severity)`
+    fn findings(report: &CheckReport) -> Vec<(&str, FindingKind, Severity)> {
+        report.findings.iter()
+            .map(|finding| (finding.dependency.as_str(), finding.kind, finding.severity))
+            .collect()
+    }
+    
+    #[test]
+    fn fails_only_on_unused_normal_dependencies_by_default() {
+        let policy = CheckConfig::default();
+        assert!(policy.fail_on_unused);
+        assert!(!policy.fail_on_unused_dev && !policy.fail_on_low_importance && !policy.fail_on_misplaced);
+        assert!(!policy.fail_on_optional && !policy.fail_on_unused_features);
+        assert!(policy.ignore.is_empty());
+        
+        let report = Checker::new(&policy, 0.0).check(&analyze(MANIFEST));
+        
+        assert_eq!(findings(&report), vec![
+            ("rand", FindingKind::Unused, Severity::Error),
+            ("tempfile", FindingKind::Unused, Severity::Warning),
+        ]);
+        assert!(!report.passed(false));
+        assert_eq!(
+            report.to_string(),
+            "error: rand (demo): unused, remove it from [dependencies]\nwarning: tempfile (demo): unused, remove it from [dev-dependencies]\n\n1 error(s), 1 warning(s)",
+        );
+    }
+    
+    #[test]
+    fn passes_with_warnings_unless_they_are_denied() {
+        let policy = CheckConfig::default();
+        let manifest = MANIFEST.replace("rand = \"0.8\"\n", "");
+        
+        let report = Checker::new(&policy, 0.0).check(&analyze(&manifest));
+        
+        assert_eq!(findings(&report), vec![("tempfile", FindingKind::Unused, Severity::Warning)]);
+        assert!(report.passed(false));
+        assert!(!report.passed(true));
+    }
+    
+    #[test]
+    fn skips_ignored_dependencies() {
+        let policy = CheckConfig {
+            ignore: vec!["rand".to_string(), "tempfile".to_string()],
+            ..CheckConfig::default()
+        };
+        
+        let report = Checker::new(&policy, 0.0).check(&analyze(MANIFEST));
+        
+        assert!(report.findings.is_empty());
+        assert!(report.passed(true));
+        assert_eq!(report.to_string(), "0 error(s), 0 warning(s)");
+    }
+    
+    #[test]
+    fn reports_used_dependencies_scoring_below_the_threshold() {
+        let result = analyze(MANIFEST);
+        let score = result.metrics.importance_scores["serde"];
+        let policy = CheckConfig {
+            fail_on_low_importance: true,
+            ignore: vec!["rand".to_string(), "tempfile".to_string()],
+            ..CheckConfig::default()
+        };
+        
+        let below = Checker::new(&policy, score + 0.01).check(&result);
+        assert_eq!(findings(&below), vec![("serde", FindingKind::LowImportance, Severity::Error)]);
+        assert_eq!(
+            below.findings[0].message,
+            format!("importance score {:.2} is below the removal threshold {:.2}", score, score + 0.01),
+        );
+        
+        let above = Checker::new(&policy, score - 0.01).check(&result);
+        assert!(above.findings.is_empty());
+    }
+}
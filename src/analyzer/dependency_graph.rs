@@ -1,66 +1,144 @@
 use std::collections::HashMap;
+use std::fmt;
+use std::fs;
 use std::path::Path;
-use anyhow::Result;
+use anyhow::{Result, Context};
 use petgraph::graph::{DiGraph, NodeIndex};
 use petgraph::algo;
 use petgraph::dot::{Dot, Config};
+use petgraph::visit::Dfs;
+use serde::Deserialize;
 
-use crate::manifest::cargo::CargoDependency;
+use crate::manifest::dependency::Dependency;
+
+/// A package in the dependency graph, identified by name, version and source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockedPackage {
+    pub name: String,
+    /// Exact version from Cargo.lock, or the version requirement when no lock file is available
+    pub version: String,
+    /// Where the package comes from (e.g. `registry+https://...`); `None` for local packages
+    pub source: Option<String>,
+}
+
+impl fmt::Display for LockedPackage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.version.is_empty() {
+            write!(f, "{}", self.name)
+        } else {
+            write!(f, "{} v{}", self.name, self.version)
+        }
+    }
+}
 
 /// Represents the dependency graph of a project
 #[derive(Debug)]
 pub struct DependencyGraph {
-    /// The graph structure with dependencies as nodes and relationships as edges
-    pub graph: DiGraph<String, ()>,
-    /// Maps dependency names to their node indices
+    /// The graph structure with packages as nodes and edges from dependents to their dependencies
+    pub graph: DiGraph<LockedPackage, ()>,
+    /// Maps direct dependency names (as declared in the manifest) to their node indices
     pub node_indices: HashMap<String, NodeIndex>,
+    /// Nodes of the analyzed packages themselves (workspace members), if known
+    pub roots: Vec<NodeIndex>,
+}
+
+/// The subset of a Cargo.lock file needed to build the graph
+#[derive(Debug, Deserialize)]
+struct CargoLock {
+    #[serde(default)]
+    package: Vec<LockEntry>,
+}
+
+/// A `[[package]]` entry of Cargo.lock
+#[derive(Debug, Deserialize)]
+struct LockEntry {
+    name: String,
+    version: String,
+    source: Option<String>,
+    /// Entries are `name`, `name version` or `name version (source)`
+    #[serde(default)]
+    dependencies: Vec<String>,
 }
 
 impl DependencyGraph {
     /// Create a new dependency graph from the list of dependencies
-    pub fn new(dependencies: &[CargoDependency]) -> Self {
+    pub fn new(dependencies: &[Dependency]) -> Self {
         let mut graph = DiGraph::new();
         let mut node_indices = HashMap::new();
         
         // Add all dependencies as nodes in the graph
         for dep in dependencies {
-            let node_idx = graph.add_node(dep.name.clone());
+            if node_indices.contains_key(&dep.name) {
+                continue;
+            }
+            
+            let node_idx = graph.add_node(LockedPackage {
+                name: dep.name.clone(),
+                version: dep.version.clone().unwrap_or_default(),
+                source: None,
+            });
             node_indices.insert(dep.name.clone(), node_idx);
         }
         
-        // For now, we have a simple graph with just nodes (no edges)
-        // In a more complete implementation, we would determine dependency relationships
-        // This would involve analyzing Cargo.lock or using cargo-metadata
+        // Without a lock file there is no relationship information, so the graph has no edges
         
         Self {
             graph,
             node_indices,
+            roots: Vec::new(),
         }
     }
     
-    /// Calculate the transitive dependencies for each dependency
-    pub fn calculate_transitive_dependencies(&self) -> HashMap<String, Vec<String>> {
-        let mut result = HashMap::new();
+    /// Get the package a direct dependency resolved to
+    pub fn package(&self, dep_name: &str) -> Option<&LockedPackage> {
+        self.node_indices.get(dep_name).map(|&idx| &self.graph[idx])
+    }
+    
+    /// Get the packages a direct dependency itself depends on
+    pub fn direct_dependencies_of(&self, dep_name: &str) -> Vec<&LockedPackage> {
+        let mut deps: Vec<&LockedPackage> = match self.node_indices.get(dep_name) {
+            Some(&idx) => self.graph.neighbors(idx).map(|n| &self.graph[n]).collect(),
+            None => Vec::new(),
+        };
         
-        for (dep_name, &node_idx) in &self.node_indices {
-            // For each dependency, find all nodes reachable from it
-            let reachable = algo::has_path_connecting(&self.graph, node_idx, node_idx, None);
-            
-            // Collect the names of all reachable dependencies
-            let deps: Vec<String> = self.node_indices.iter()
-                .filter_map(|(name, &idx)| {
-                    if idx != node_idx && reachable {
-                        Some(name.clone())
-                    } else {
-                        None
-                    }
-                })
-                .collect();
-            
-            result.insert(dep_name.clone(), deps);
+        deps.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.version.cmp(&b.version)));
+        deps.dedup();
+        deps
+    }
+    
+    /// Get every package reachable from a direct dependency, excluding the dependency itself
+    pub fn transitive_dependencies_of(&self, dep_name: &str) -> Vec<&LockedPackage> {
+        let start = match self.node_indices.get(dep_name) {
+            Some(&idx) => idx,
+            None => return Vec::new(),
+        };
+        
+        let mut reachable = Vec::new();
+        let mut dfs = Dfs::new(&self.graph, start);
+        
+        while let Some(node) = dfs.next(&self.graph) {
+            if node != start {
+                reachable.push(&self.graph[node]);
+            }
         }
         
-        result
+        reachable.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.version.cmp(&b.version)));
+        reachable
+    }
+    
+    /// Calculate the transitive dependencies for each direct dependency
+    #[allow(dead_code)]
+    pub fn calculate_transitive_dependencies(&self) -> HashMap<String, Vec<String>> {
+        self.node_indices.keys()
+            .map(|dep_name| {
+                let deps = self.transitive_dependencies_of(dep_name)
+                    .into_iter()
+                    .map(|package| package.to_string())
+                    .collect();
+                
+                (dep_name.clone(), deps)
+            })
+            .collect()
     }
     
     /// Find circular dependencies in the graph
@@ -73,21 +151,22 @@ impl DependencyGraph {
             .map(|scc| {
                 // Convert node indices back to dependency names
                 scc.into_iter()
-                    .filter_map(|idx| {
-                        let name = self.graph[idx].clone();
-                        Some(name)
-                    })
+                    .map(|idx| self.graph[idx].name.clone())
                     .collect()
             })
             .collect()
     }
     
     /// Generate a DOT representation of the dependency graph for visualization
+    #[allow(dead_code)]
     pub fn to_dot(&self) -> String {
-        format!("{:?}", Dot::with_config(&self.graph, &[Config::EdgeNoLabel]))
+        // Label nodes with `name vX.Y.Z` rather than their debug representation
+        let labeled = self.graph.map(|_, package| package.to_string(), |_, _| "");
+        format!("{}", Dot::with_config(&labeled, &[Config::EdgeNoLabel]))
     }
     
     /// Save the graph to a DOT file for visualization
+    #[allow(dead_code)]
     pub fn save_dot<P: AsRef<Path>>(&self, path: P) -> Result<()> {
         let dot = self.to_dot();
         std::fs::write(path, dot)?;
@@ -95,6 +174,7 @@ impl DependencyGraph {
     }
     
     /// Add an edge representing a dependency relationship
+    #[allow(dead_code)]
     pub fn add_dependency(&mut self, dependent: &str, dependency: &str) -> Result<()> {
         let dependent_idx = self.node_indices.get(dependent)
             .ok_or_else(|| anyhow::anyhow!("Dependent {} not found in graph", dependent))?;
@@ -108,18 +188,238 @@ impl DependencyGraph {
         Ok(())
     }
     
-    /// Build a dependency graph from Cargo.lock
-    pub fn from_cargo_lock<P: AsRef<Path>>(path: P, dependencies: &[CargoDependency]) -> Result<Self> {
-        let mut graph = Self::new(dependencies);
+    /// Build a dependency graph from Cargo.lock.
+    ///
+    /// `roots` are the names of the analyzed packages; they are used to pick the right
+    /// version of a direct dependency when the lock file contains several.
+    pub fn from_cargo_lock<P: AsRef<Path>>(path: P, roots: &[String], dependencies: &[Dependency]) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read Cargo.lock at {:?}", path))?;
+        let lock: CargoLock = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse Cargo.lock at {:?}", path))?;
+        
+        let mut graph = DiGraph::new();
+        let mut by_name: HashMap<&str, Vec<NodeIndex>> = HashMap::new();
+        
+        // Add every locked package as a node
+        for entry in &lock.package {
+            let idx = graph.add_node(LockedPackage {
+                name: entry.name.clone(),
+                version: entry.version.clone(),
+                source: entry.source.clone(),
+            });
+            by_name.entry(entry.name.as_str()).or_default().push(idx);
+        }
+        
+        // Add an edge for every dependency listed by a package
+        for (entry_idx, entry) in lock.package.iter().enumerate() {
+            let from = NodeIndex::new(entry_idx);
+            
+            for spec in &entry.dependencies {
+                if let Some(to) = resolve_lock_dependency(&graph, &by_name, spec) {
+                    graph.add_edge(from, to, ());
+                }
+            }
+        }
+        
+        // Local packages (no source) with a root's name are the analyzed packages
+        let root_nodes: Vec<NodeIndex> = roots.iter()
+            .filter_map(|root| by_name.get(root.as_str()))
+            .flat_map(|candidates| candidates.iter().copied())
+            .filter(|&idx| graph[idx].source.is_none())
+            .collect();
+        
+        // Map each direct dependency to the locked package it resolved to
+        let mut node_indices = HashMap::new();
+        for dep in dependencies {
+            let package_name = dep.package.as_deref().unwrap_or(&dep.name);
+            let candidates = match by_name.get(package_name) {
+                Some(candidates) => candidates,
+                None => continue,
+            };
+            
+            // Prefer the version a root actually depends on when several are locked
+            let chosen = candidates.iter()
+                .copied()
+                .find(|&idx| root_nodes.iter().any(|&root| graph.contains_edge(root, idx)))
+                .or_else(|| candidates.last().copied());
+            
+            if let Some(idx) = chosen {
+                node_indices.entry(dep.name.clone()).or_insert(idx);
+            }
+        }
+        
+        Ok(Self {
+            graph,
+            node_indices,
+            roots: root_nodes,
+        })
+    }
+}
+
+/// Resolve a Cargo.lock dependency entry (`name`, `name version` or `name version (source)`) to its node
+fn resolve_lock_dependency(
+    graph: &DiGraph<LockedPackage, ()>,
+    by_name: &HashMap<&str, Vec<NodeIndex>>,
+    spec: &str,
+) -> Option<NodeIndex> {
+    let mut parts = spec.splitn(3, ' ');
+    let name = parts.next()?;
+    let version = parts.next();
+    let source = parts.next().map(|s| s.trim_start_matches('(').trim_end_matches(')'));
+    
+    by_name.get(name)?
+        .iter()
+        .copied()
+        .find(|&idx| {
+            let package = &graph[idx];
+            version.is_none_or(|v| package.version == v)
+                && source.is_none_or(|s| package.source.as_deref() == Some(s))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use crate::manifest::dependency::{CargoExtras, DependencyExtras, DependencySource, DependencyType};
+    
+    const REGISTRY: &str = "registry+https://github.com/rust-lang/crates.io-index";
+    
+    fn dependency(name: &str, package: Option<&str>) -> Dependency {
+        Dependency {
+            name: name.to_string(),
+            import_name: name.replace('-', "_"),
+            package: package.map(str::to_string),
+            version: None,
+            features: Vec::new(),
+            optional: false,
+            dependency_type: DependencyType::Normal,
+            source: DependencySource::default(),
+            extras: DependencyExtras::Rust(CargoExtras {
+                default_features: true,
+                platforms: Vec::new(),
+                all_platforms: true,
+            }),
+        }
+    }
+    
+    fn names(packages: Vec<&LockedPackage>) -> Vec<String> {
+        packages.into_iter().map(LockedPackage::to_string).collect()
+    }
+    
+    /// A lock file where `app` depends on `rand` 0.8 and a renamed `rand` 0.7, while
+    /// `helper` pulls in another version of `syn`
+    fn lock_file() -> String {
+        format!(r#"version = 3
+
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = [
+ "helper",
+ "rand 0.8.5",
+ "rand 0.7.3",
+ "syn 2.0.0",
+]
+
+[[package]]
+name = "helper"
+version = "0.1.0"
+dependencies = [
+ "syn 1.0.0 ({registry})",
+]
+
+[[package]]
+name = "rand"
+version = "0.7.3"
+source = "{registry}"
+
+[[package]]
+name = "rand"
+version = "0.8.5"
+source = "{registry}"
+dependencies = [
+ "rand_core",
+]
+
+[[package]]
+name = "rand_core"
+version = "0.6.4"
+source = "{registry}"
+
+[[package]]
+name = "syn"
+version = "1.0.0"
+source = "{registry}"
+
+[[package]]
+name = "syn"
+version = "2.0.0"
+source = "{registry}"
+dependencies = [
+ "rand_core",
+]
+"#, registry = REGISTRY)
+    }
+    
+    fn load(dependencies: &[Dependency]) -> DependencyGraph {
+        let dir = tempfile::tempdir().unwrap();
+        let lock_path = dir.path().join("Cargo.lock");
+        fs::write(&lock_path, lock_file()).unwrap();
+        DependencyGraph::from_cargo_lock(&lock_path, &["app".to_string()], dependencies).unwrap()
+    }
+    
+    #[test]
+    fn resolves_direct_dependencies_to_locked_versions() {
+        let graph = load(&[
+            dependency("rand", None),
+            dependency("old-rand", Some("rand")),
+            dependency("syn", None),
+            dependency("helper", None),
+            dependency("missing", None),
+        ]);
         
-        // Parse Cargo.lock to extract dependency relationships
-        // This is a simplified implementation. In a complete version,
-        // we would parse the Cargo.lock file and build the graph
-        // based on the dependency relationships specified there.
+        assert_eq!(graph.roots.len(), 1);
+        assert_eq!(graph.package("syn").map(LockedPackage::to_string).as_deref(), Some("syn v2.0.0"));
+        assert_eq!(graph.package("helper").unwrap().source, None);
+        assert_eq!(graph.package("missing"), None);
+        // Both versions of `rand` are dependencies of the root; the first one locked is kept
+        assert_eq!(graph.package("rand").map(LockedPackage::to_string).as_deref(), Some("rand v0.7.3"));
+        assert_eq!(graph.package("old-rand").map(LockedPackage::to_string).as_deref(), Some("rand v0.7.3"));
+    }
+    
+    #[test]
+    fn follows_versioned_and_sourced_lock_entries() {
+        let graph = load(&[dependency("helper", None), dependency("syn", None)]);
         
-        // For now, we'll simulate by adding some placeholder relationships
-        // In a real implementation, this would be determined from Cargo.lock
+        assert_eq!(names(graph.direct_dependencies_of("helper")), vec!["syn v1.0.0"]);
+        assert_eq!(names(graph.direct_dependencies_of("syn")), vec!["rand_core v0.6.4"]);
+        assert_eq!(names(graph.transitive_dependencies_of("helper")), vec!["syn v1.0.0"]);
+        assert!(graph.find_circular_dependencies().is_empty());
+    }
+    
+    #[test]
+    fn links_the_root_to_every_locked_version_it_depends_on() {
+        let graph = load(&[]);
+        let mut dependencies: Vec<String> = graph.graph.neighbors(graph.roots[0])
+            .map(|node| graph.graph[node].to_string())
+            .collect();
+        dependencies.sort();
         
-        Ok(graph)
+        assert_eq!(dependencies, vec!["helper v0.1.0", "rand v0.7.3", "rand v0.8.5", "syn v2.0.0"]);
     }
-} 
\ No newline at end of file
+    
+    #[test]
+    fn builds_an_unconnected_graph_without_a_lock_file() {
+        let mut rand = dependency("rand", None);
+        rand.version = Some("0.8".to_string());
+        let graph = DependencyGraph::new(&[rand.clone(), rand, dependency("syn", None)]);
+        
+        assert_eq!(graph.graph.node_count(), 2);
+        assert_eq!(graph.graph.edge_count(), 0);
+        assert_eq!(graph.package("rand").map(LockedPackage::to_string).as_deref(), Some("rand v0.8"));
+        assert_eq!(graph.package("syn").map(LockedPackage::to_string).as_deref(), Some("syn"));
+    }
+}
@@ -0,0 +1,572 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use anyhow::Result;
+use petgraph::graph::NodeIndex;
+use petgraph::Direction;
+use serde::Serialize;
+
+use crate::analyzer::{ProjectGraph, ProjectPackage};
+use crate::analyzer::dependency_graph::LockedPackage;
+use crate::manifest::cargo::{self, CargoParser};
+use crate::manifest::dependency::{Dependency, DependencyType};
+use crate::manifest::features::FeatureGraph;
+
+/// Why a crate is part of the resolved dependency tree
+#[derive(Debug, Serialize)]
+pub struct Explanation {
+    /// The explained crate
+    pub name: String,
+    pub version: String,
+    pub source: Option<String>,
+    /// Paths from a project package down to the explained crate
+    pub paths: Vec<DependencyPath>,
+    /// Number of paths left out because of the path limit
+    pub omitted_paths: usize,
+}
+
+/// A chain of packages from a project package (first) down to the explained crate (last)
+#[derive(Debug, Serialize)]
+pub struct DependencyPath {
+    pub steps: Vec<PathStep>,
+}
+
+/// A package on a dependency path, together with how its parent depends on it
+#[derive(Debug, Clone, Serialize)]
+pub struct PathStep {
+    pub name: String,
+    pub version: String,
+    /// Dependency kinds the parent declares this package with (empty for the first step or when unknown)
+    pub kinds: Vec<DependencyType>,
+    /// Features the parent requests on this package
+    pub features: Vec<String>,
+    /// Whether the parent declares this package as optional
+    pub optional: bool,
+    /// Features of the parent enabled along the path that turn this package on when it is optional
+    pub enabled_by: Vec<String>,
+}
+
+/// How one package declares its dependency on another
+#[derive(Debug, Clone, Default)]
+struct EdgeInfo {
+    kinds: Vec<DependencyType>,
+    features: Vec<String>,
+    optional: bool,
+    enabled_by: Vec<String>,
+    /// Features requested on the dependency along this edge: the declared ones, those the parent's
+    /// enabled features turn on (`dep/feature`), and `default` unless it is turned off
+    child_features: Vec<String>,
+}
+
+/// Explains why crates are in a project's dependency tree
+pub struct Explainer<'a> {
+    project: &'a ProjectGraph,
+    /// Features requested on the project packages, including `default`; `None` selects every feature
+    selected_features: Option<Vec<String>>,
+    /// Cache of declared dependencies and features of packages, read from the project's manifests
+    /// or from the local Cargo registry
+    manifests: RefCell<HashMap<NodeIndex, Option<PackageManifest>>>,
+}
+
+/// What the manifest of a package in the graph declares
+#[derive(Debug, Clone)]
+struct PackageManifest {
+    dependencies: Vec<Dependency>,
+    features: FeatureGraph,
+}
+
+impl<'a> Explainer<'a> {
+    /// Create an explainer for a resolved project graph, with the default features of the project packages
+    pub fn new(project: &'a ProjectGraph) -> Self {
+        Self {
+            project,
+            selected_features: Some(vec!["default".to_string()]),
+            manifests: RefCell::new(HashMap::new()),
+        }
+    }
+    
+    /// Request the given features on the project packages besides the default ones, or every feature
+    pub fn with_features(mut self, features: &[String], all_features: bool) -> Self {
+        self.selected_features = if all_features {
+            None
+        } else {
+            let mut requested = features.to_vec();
+            requested.push("default".to_string());
+            Some(requested)
+        };
+        self
+    }
+    
+    /// Explain every locked package matching `spec` (`name` or `name@version`), listing
+    /// at most `max_paths` paths per package
+    pub fn explain(&self, spec: &str, max_paths: usize) -> Result<Vec<Explanation>> {
+        let graph = &self.project.dependency_graph;
+        if graph.roots.is_empty() {
+            return Err(anyhow::anyhow!(
+                "The project's packages were not found in Cargo.lock; run `cargo generate-lockfile` first"
+            ));
+        }
+        
+        let (name, version) = match spec.split_once('@') {
+            Some((name, version)) => (name, Some(version)),
+            None => (spec, None),
+        };
+        
+        let targets: Vec<NodeIndex> = graph.graph.node_indices()
+            .filter(|&idx| {
+                let package = &graph.graph[idx];
+                package.name == name && version.is_none_or(|v| package.version == v)
+            })
+            .collect();
+        
+        if targets.is_empty() {
+            return Err(anyhow::anyhow!("{} is not part of the resolved dependency tree", spec));
+        }
+        
+        Ok(targets.into_iter()
+            .map(|target| self.explain_package(target, max_paths))
+            .collect())
+    }
+    
+    /// Collect the inverse paths from a package back to the project packages
+    fn explain_package(&self, target: NodeIndex, max_paths: usize) -> Explanation {
+        let graph = &self.project.dependency_graph;
+        let mut node_paths = Vec::new();
+        let mut current = vec![target];
+        
+        self.walk_dependents(target, &mut current, &mut node_paths, max_paths);
+        // Enumerating every path is exponential in diamond-heavy graphs, so the rest are only counted
+        let total = self.count_paths(target, &mut HashMap::new(), &mut HashSet::new());
+        let omitted_paths = total.saturating_sub(node_paths.len());
+        
+        let paths = node_paths.into_iter()
+            .map(|nodes| self.describe_path(&nodes))
+            .collect();
+        
+        let package = &graph.graph[target];
+        Explanation {
+            name: package.name.clone(),
+            version: package.version.clone(),
+            source: package.source.clone(),
+            paths,
+            omitted_paths,
+        }
+    }
+    
+    /// Depth-first search over dependents, recording each path that reaches a project package
+    /// until `max_paths` are found. `current` holds the path from the explained crate upwards.
+    fn walk_dependents(
+        &self,
+        node: NodeIndex,
+        current: &mut Vec<NodeIndex>,
+        paths: &mut Vec<Vec<NodeIndex>>,
+        max_paths: usize,
+    ) {
+        if paths.len() == max_paths {
+            return;
+        }
+        
+        if self.project.dependency_graph.roots.contains(&node) {
+            paths.push(current.iter().rev().copied().collect());
+            return;
+        }
+        
+        for dependent in self.dependents(node) {
+            // Skip cycles through packages already on the path
+            if current.contains(&dependent) {
+                continue;
+            }
+            
+            current.push(dependent);
+            self.walk_dependents(dependent, current, paths, max_paths);
+            current.pop();
+        }
+    }
+    
+    /// Count the paths from `node` up to the project packages, memoizing the count of every
+    /// node. Dependents already on the path being counted close a cycle and are skipped.
+    fn count_paths(&self, node: NodeIndex, counts: &mut HashMap<NodeIndex, usize>, on_path: &mut HashSet<NodeIndex>) -> usize {
+        if self.project.dependency_graph.roots.contains(&node) {
+            return 1;
+        }
+        if let Some(&count) = counts.get(&node) {
+            return count;
+        }
+        
+        on_path.insert(node);
+        let mut count = 0usize;
+        for dependent in self.dependents(node) {
+            if !on_path.contains(&dependent) {
+                count = count.saturating_add(self.count_paths(dependent, counts, on_path));
+            }
+        }
+        on_path.remove(&node);
+        
+        counts.insert(node, count);
+        count
+    }
+    
+    /// Packages depending on `node`, by name
+    fn dependents(&self, node: NodeIndex) -> Vec<NodeIndex> {
+        let graph = &self.project.dependency_graph.graph;
+        
+        let mut dependents: Vec<NodeIndex> = graph.neighbors_directed(node, Direction::Incoming).collect();
+        dependents.sort_by_key(|&idx| graph[idx].name.clone());
+        dependents.dedup();
+        dependents
+    }
+    
+    /// Describe a path of nodes (project package first) with the edge information between them
+    fn describe_path(&self, nodes: &[NodeIndex]) -> DependencyPath {
+        let graph = &self.project.dependency_graph.graph;
+        // Features requested on the package at the current step, passed down the path
+        let mut requested = self.selected_features.clone();
+        
+        let steps = nodes.iter()
+            .enumerate()
+            .map(|(i, &node)| {
+                let edge = match i {
+                    0 => EdgeInfo::default(),
+                    _ => self.edge_info(nodes[i - 1], &graph[node], requested.as_deref()),
+                };
+                if i > 0 {
+                    requested = Some(edge.child_features.clone());
+                }
+                
+                PathStep {
+                    name: graph[node].name.clone(),
+                    version: graph[node].version.clone(),
+                    kinds: edge.kinds,
+                    features: edge.features,
+                    optional: edge.optional,
+                    enabled_by: edge.enabled_by,
+                }
+            })
+            .collect();
+        
+        DependencyPath { steps }
+    }
+    
+    /// Find how `parent` declares its dependency on `child`, given the features requested on
+    /// `parent` along the path (`None` for every feature)
+    fn edge_info(&self, parent: NodeIndex, child: &LockedPackage, parent_features: Option<&[String]>) -> EdgeInfo {
+        let graph = &self.project.dependency_graph.graph;
+        let parent_package = &graph[parent];
+        let is_project_package = self.project_package(parent_package).is_some();
+        
+        let mut info = EdgeInfo::default();
+        let manifest = match self.manifest(parent) {
+            Some(manifest) => manifest,
+            None => return info,
+        };
+        
+        // Only the features actually enabled on the parent pull optional dependencies in
+        let enabled = match parent_features {
+            Some(requested) => {
+                let requested: Vec<&str> = requested.iter().map(String::as_str).collect();
+                manifest.features.enabled_features(&requested)
+            }
+            None => {
+                let all: Vec<&str> = manifest.features.features().chain(manifest.features.optional_dependencies()).collect();
+                manifest.features.enabled_features(&all)
+            }
+        };
+        
+        let matching = manifest.dependencies.iter()
+            .filter(|dep| dep.package.as_deref().unwrap_or(&dep.name) == child.name)
+            // Dev-dependencies of other packages never end up in this project's build
+            .filter(|dep| is_project_package || dep.dependency_type != DependencyType::Development);
+        
+        for dep in matching {
+            if !info.kinds.contains(&dep.dependency_type) {
+                info.kinds.push(dep.dependency_type.clone());
+            }
+            for feature in &dep.features {
+                if !info.features.contains(feature) {
+                    info.features.push(feature.clone());
+                }
+            }
+            
+            let default = dep.cargo().is_none_or(|cargo| cargo.default_features).then(|| "default".to_string());
+            let child_features = dep.features.iter()
+                .cloned()
+                .chain(manifest.features.dependency_features(&enabled, &dep.name))
+                .chain(default);
+            for feature in child_features {
+                if !info.child_features.contains(&feature) {
+                    info.child_features.push(feature);
+                }
+            }
+            
+            if dep.optional {
+                info.optional = true;
+                info.enabled_by = manifest.features.features_enabling(&dep.name)
+                    .into_iter()
+                    .filter(|feature| enabled.contains(feature))
+                    .collect();
+            }
+        }
+        
+        info
+    }
+    
+    /// The project package a locked package is, if any
+    fn project_package(&self, package: &LockedPackage) -> Option<&'a ProjectPackage> {
+        if package.source.is_some() {
+            return None;
+        }
+        self.project.packages.iter().find(|project_package| project_package.name == package.name)
+    }
+    
+    /// Declared dependencies and features of a package: project packages use their already-parsed
+    /// manifests, other packages are read from the local Cargo registry if cached there
+    fn manifest(&self, node: NodeIndex) -> Option<PackageManifest> {
+        let package = &self.project.dependency_graph.graph[node];
+        
+        self.manifests.borrow_mut()
+            .entry(node)
+            .or_insert_with(|| match self.project_package(package) {
+                Some(project_package) => {
+                    let features = FeatureGraph::load(&project_package.manifest_path, &project_package.dependencies, self.project.workspace.as_ref()).ok()?;
+                    Some(PackageManifest {
+                        dependencies: project_package.dependencies.clone(),
+                        features,
+                    })
+                }
+                None => {
+                    // Published manifests have their workspace inheritance resolved already
+                    let manifest_path = registry_manifest(package)?;
+                    let dependencies = CargoParser::parse_in_workspace(&manifest_path, None).ok()?;
+                    let features = FeatureGraph::load(&manifest_path, &dependencies, None).ok()?;
+                    Some(PackageManifest { dependencies, features })
+                }
+            })
+            .clone()
+    }
+}
+
+/// Locate the manifest of a registry package in the local Cargo cache
+fn registry_manifest(package: &LockedPackage) -> Option<std::path::PathBuf> {
+    package.source.as_ref()?;
+    cargo::find_registry_manifest(&package.name, Some(&package.version))
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} v{} is required by {} path(s):",
+            self.name, self.version, self.paths.len() + self.omitted_paths)?;
+        
+        for path in &self.paths {
+            writeln!(f)?;
+            
+            for (depth, step) in path.steps.iter().enumerate() {
+                if depth == 0 {
+                    writeln!(f, "{} v{}", step.name, step.version)?;
+                    continue;
+                }
+                
+                let mut notes: Vec<String> = step.kinds.iter()
+                    .map(|kind| match kind {
+                        DependencyType::Normal => "normal".to_string(),
+                        DependencyType::Development => "dev".to_string(),
+                        DependencyType::Build => "build".to_string(),
+                        DependencyType::Peer => "peer".to_string(),
+                    })
+                    .collect();
+                
+                if step.optional {
+                    notes.push(match step.enabled_by.as_slice() {
+                        [] => "optional".to_string(),
+                        features => format!("optional, enabled by feature {}", features.join(" or ")),
+                    });
+                }
+                
+                if !step.features.is_empty() {
+                    notes.push(format!("features: {}", step.features.join(", ")));
+                }
+                
+                let notes = if notes.is_empty() {
+                    String::new()
+                } else {
+                    format!(" ({})", notes.join("; "))
+                };
+                
+                writeln!(f, "{}└─ {} v{}{}", "   ".repeat(depth - 1), step.name, step.version, notes)?;
+            }
+        }
+        
+        if self.omitted_paths > 0 {
+            writeln!(f)?;
+            writeln!(f, "... and {} more path(s); raise --max-paths to see them", self.omitted_paths)?;
+        }
+        
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::fs;
+    use crate::analyzer::DependencyAnalyzer;
+    
+    const MANIFEST: &str = r#"[package]
+name = "app"
+version = "0.1.0"
+
+[dependencies]
+alpha = { package = "why-alpha", version = "1" }
+why-beta = "1"
+why-log = { version = "0.4", optional = true }
+
+[features]
+logging = ["dep:why-log"]
+"#;
+
+    /// Lock file of `MANIFEST`: `why-core` is locked in two versions, 1.0.0 being reached
+    /// directly from `why-alpha` and through `why-shared`
+    const LOCK_FILE: &str = r#"version = 3
+
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = [
+ "why-alpha",
+ "why-beta",
+ "why-log",
+]
+
+[[package]]
+name = "why-alpha"
+version = "1.0.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+dependencies = [
+ "why-core 1.0.0",
+ "why-shared",
+]
+
+[[package]]
+name = "why-beta"
+version = "1.0.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+dependencies = [
+ "why-core 2.0.0",
+ "why-shared",
+]
+
+[[package]]
+name = "why-core"
+version = "1.0.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "why-core"
+version = "2.0.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "why-log"
+version = "0.4.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "why-shared"
+version = "1.0.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+dependencies = [
+ "why-core 1.0.0",
+]
+"#;
+
+    /// Resolved graph of the `MANIFEST` package, locked by `LOCK_FILE`, and the directory
+    /// holding the package
+    fn project() -> (ProjectGraph, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("Cargo.toml"), MANIFEST).unwrap();
+        fs::write(dir.path().join("Cargo.lock"), LOCK_FILE).unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/lib.rs"), "").unwrap();
+        
+        (DependencyAnalyzer::new(dir.path()).resolve_graphs().unwrap().remove(0), dir)
+    }
+    
+    /// Paths of an explanation as `name vversion` steps joined by ` -> `
+    fn paths(explanation: &Explanation) -> Vec<String> {
+        explanation.paths.iter()
+            .map(|path| path.steps.iter()
+                .map(|step| format!("{} v{}", step.name, step.version))
+                .collect::<Vec<_>>()
+                .join(" -> "))
+            .collect()
+    }
+    
+    #[test]
+    fn lists_every_path_to_a_crate() {
+        let (project, _dir) = project();
+        let explanations = Explainer::new(&project).explain("why-core@1.0.0", 10).unwrap();
+        
+        assert_eq!(explanations.len(), 1);
+        assert_eq!(paths(&explanations[0]), vec![
+            "app v0.1.0 -> why-alpha v1.0.0 -> why-core v1.0.0",
+            "app v0.1.0 -> why-alpha v1.0.0 -> why-shared v1.0.0 -> why-core v1.0.0",
+            "app v0.1.0 -> why-beta v1.0.0 -> why-shared v1.0.0 -> why-core v1.0.0",
+        ]);
+        assert_eq!(explanations[0].omitted_paths, 0);
+    }
+    
+    #[test]
+    fn counts_the_paths_beyond_the_limit() {
+        let (project, _dir) = project();
+        let explanations = Explainer::new(&project).explain("why-core@1.0.0", 1).unwrap();
+        
+        assert_eq!(paths(&explanations[0]), vec!["app v0.1.0 -> why-alpha v1.0.0 -> why-core v1.0.0"]);
+        assert_eq!(explanations[0].omitted_paths, 2);
+        assert_eq!(
+            explanations[0].to_string(),
+            "why-core v1.0.0 is required by 3 path(s):\n\napp v0.1.0\n└─ why-alpha v1.0.0 (normal)\n   └─ why-core v1.0.0\n\n... and 2 more path(s); raise --max-paths to see them\n",
+        );
+    }
+    
+    #[test]
+    fn explains_every_locked_version() {
+        let (project, _dir) = project();
+        let explanations = Explainer::new(&project).explain("why-core", 10).unwrap();
+        
+        let versions: Vec<&str> = explanations.iter().map(|explanation| explanation.version.as_str()).collect();
+        assert_eq!(versions, vec!["1.0.0", "2.0.0"]);
+        assert_eq!(paths(&explanations[1]), vec!["app v0.1.0 -> why-beta v1.0.0 -> why-core v2.0.0"]);
+    }
+    
+    #[test]
+    fn describes_renamed_and_optional_dependencies() {
+        let (project, _dir) = project();
+        
+        let renamed = Explainer::new(&project).explain("why-alpha", 10).unwrap();
+        let step = &renamed[0].paths[0].steps[1];
+        assert_eq!((step.name.as_str(), step.kinds.clone(), step.optional), ("why-alpha", vec![DependencyType::Normal], false));
+        
+        let optional = Explainer::new(&project).explain("why-log", 10).unwrap();
+        let step = &optional[0].paths[0].steps[1];
+        assert!(step.optional);
+        assert!(step.enabled_by.is_empty());
+        
+        let optional = Explainer::new(&project)
+            .with_features(&["logging".to_string()], false)
+            .explain("why-log", 10)
+            .unwrap();
+        assert_eq!(optional[0].paths[0].steps[1].enabled_by, vec!["logging"]);
+        assert!(optional[0].to_string().contains("└─ why-log v0.4.0 (normal; optional, enabled by feature logging)"));
+    }
+    
+    #[test]
+    fn rejects_crates_outside_the_graph() {
+        let (project, _dir) = project();
+        
+        let err = Explainer::new(&project).explain("why-missing", 10).unwrap_err();
+        assert_eq!(err.to_string(), "why-missing is not part of the resolved dependency tree");
+        
+        let err = Explainer::new(&project).explain("why-core@3.0.0", 10).unwrap_err();
+        assert_eq!(err.to_string(), "why-core@3.0.0 is not part of the resolved dependency tree");
+    }
+}
@@ -0,0 +1,199 @@
+use std::fmt;
+use anyhow::Result;
+
+use crate::analyzer::AnalysisResult;
+use crate::manifest::dependency::{DependencyType, Ecosystem};
+use crate::manifest::editor::{dependency_table_header, CargoManifestEditor};
+use crate::utils::diff::unified_diff;
+
+/// A dependency removed from a manifest
+#[derive(Debug)]
+pub struct Removal {
+    pub name: String,
+    pub dependency_type: DependencyType,
+    /// Platform of the `[target.<platform>.*]` table the dependency was removed from
+    pub platform: Option<String>,
+    /// Features that referenced the dependency and were cleaned up
+    pub features: Vec<String>,
+}
+
+/// The edits planned for a single manifest
+pub struct ManifestFix {
+    /// Package the manifest belongs to
+    pub package: String,
+    pub removals: Vec<Removal>,
+    editor: CargoManifestEditor,
+}
+
+impl ManifestFix {
+    /// Unified diff of the planned edits
+    pub fn diff(&self) -> String {
+        let label = self.editor.path().display().to_string();
+        unified_diff(self.editor.original(), &self.editor.contents(), &label, &label)
+    }
+    
+    /// Write the edited manifest to disk
+    pub fn apply(&self) -> Result<()> {
+        self.editor.save()
+    }
+}
+
+impl fmt::Display for ManifestFix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} ({}):", self.package, self.editor.path().display())?;
+        
+        for removal in &self.removals {
+            let table = dependency_table_header(&removal.dependency_type, removal.platform.as_deref());
+            write!(f, "  removed {} from [{}]", removal.name, table)?;
+            if !removal.features.is_empty() {
+                write!(f, ", cleaned features: {}", removal.features.join(", "))?;
+            }
+            writeln!(f)?;
+        }
+        
+        Ok(())
+    }
+}
+
+/// Plan the removal of every unused dependency, one fix per manifest that changes.
+/// Dependencies listed in `ignore` are left alone.
+pub fn plan_fixes(result: &AnalysisResult, ignore: &[String]) -> Result<Vec<ManifestFix>> {
+    let mut fixes = Vec::new();
+    
+    // Only Cargo.toml can be edited
+    let packages = result.packages()
+        .into_iter()
+        .filter(|package| package.ecosystem == Some(Ecosystem::Rust));
+    
+    for package in packages {
+        let mut editor = CargoManifestEditor::open(&package.manifest_path)?;
+        let mut removals = Vec::new();
+        
+        // Only dependencies without any usage are removed; low-importance ones need a human decision.
+        // Usages compiled out for the selected features or target cfgs still keep a dependency.
+        let unused = package.dependencies.iter().filter(|dep| {
+            package.metrics.removable_dependencies.contains(&dep.name)
+                && !package.metrics.is_used.get(&dep.name).copied().unwrap_or(false)
+                && package.usage_data.usage_locations.get(&dep.name).is_none_or(|usages| usages.is_empty())
+                && !ignore.contains(&dep.name)
+        });
+        
+        for dep in unused {
+            // A dependency can be declared for every platform and in `[target.*]` tables
+            let removed: Vec<Option<String>> = dep.table_platforms()
+                .filter(|platform| editor.remove_dependency(&dep.name, &dep.dependency_type, *platform))
+                .map(|platform| platform.map(str::to_string))
+                .collect();
+            if removed.is_empty() {
+                continue;
+            }
+            
+            // Feature entries such as `dep:name` or `name/feature` would be dangling otherwise
+            let mut features = editor.remove_feature_references(&dep.name);
+            
+            for platform in removed {
+                removals.push(Removal {
+                    name: dep.name.clone(),
+                    dependency_type: dep.dependency_type.clone(),
+                    platform,
+                    features: std::mem::take(&mut features),
+                });
+            }
+        }
+        
+        if !removals.is_empty() && editor.is_modified() {
+            fixes.push(ManifestFix {
+                package: package.name.clone(),
+                removals,
+                editor,
+            });
+        }
+    }
+    
+    Ok(fixes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::fs;
+    use crate::analyzer::DependencyAnalyzer;
+    use crate::utils::config::Config;
+    
+    /// `[package]` table of the manifests below
+    const PACKAGE: &str = "[package]\nname = \"demo\"\nversion = \"0.1.0\"\nedition = \"2021\"\n";
+    
+    /// Fixes planned for a package with the given manifest and library source, and the
+    /// directory holding the package
+    fn plan(manifest: &str, lib: &str, ignore: &[&str]) -> (Vec<ManifestFix>, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("Cargo.toml"), manifest).unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/lib.rs"), lib).unwrap();
+        
+        let mut config = Config::default();
+        config.analysis.use_cache = false;
+        let result = DependencyAnalyzer::new(dir.path()).with_config(config).analyze().unwrap();
+        let ignore: Vec<String> = ignore.iter().map(|name| name.to_string()).collect();
+        
+        (plan_fixes(&result, &ignore).unwrap(), dir)
+    }
+    
+    /// Diff of `fix` with the manifest path of its header replaced by `Cargo.toml`
+    fn diff(fix: &ManifestFix) -> String {
+        fix.diff().replace(&fix.editor.path().display().to_string(), "Cargo.toml")
+    }
+    
+    #[test]
+    fn removes_an_optional_dependency_and_its_feature_references() {
+        let manifest = format!(
+            "{}\n[dependencies]\nserde = \"1\"\nrand = {{ version = \"0.8\", optional = true }}\n\n[features]\nrandom = [\"dep:rand\"]\nsmall = [\"rand/small_rng\", \"serde/std\"]\n",
+            PACKAGE,
+        );
+        let (fixes, _dir) = plan(&manifest, "use serde::Serialize;\n", &[]);
+        
+        assert_eq!(fixes.len(), 1);
+        assert_eq!(
+            fixes[0].editor.contents(),
+            format!("{}\n[dependencies]\nserde = \"1\"\n\n[features]\nrandom = []\nsmall = [\"serde/std\"]\n", PACKAGE),
+        );
+        assert_eq!(
+            diff(&fixes[0]),
+            "--- Cargo.toml\n+++ Cargo.toml\n@@ -5,8 +5,7 @@\n \n [dependencies]\n serde = \"1\"\n-rand = { version = \"0.8\", optional = true }\n \n [features]\n-random = [\"dep:rand\"]\n-small = [\"rand/small_rng\", \"serde/std\"]\n+random = []\n+small = [\"serde/std\"]\n",
+        );
+        assert_eq!(fixes[0].removals[0].features, vec!["random", "small"]);
+    }
+    
+    #[test]
+    fn removes_dependencies_from_platform_tables() {
+        let manifest = format!(
+            "{}\n[dependencies]\nlibc = \"0.2\"\n\n[target.'cfg(unix)'.dependencies]\nlibc = {{ version = \"0.2\", features = [\"extra_traits\"] }}\n\n[target.'cfg(windows)'.dependencies]\nwinapi = \"0.3\"\n",
+            PACKAGE,
+        );
+        let (fixes, _dir) = plan(&manifest, "use winapi::um::winbase;\n", &[]);
+        
+        assert_eq!(fixes.len(), 1);
+        assert_eq!(
+            fixes[0].editor.contents(),
+            format!("{}\n[dependencies]\n\n[target.'cfg(unix)'.dependencies]\n\n[target.'cfg(windows)'.dependencies]\nwinapi = \"0.3\"\n", PACKAGE),
+        );
+        let platforms: Vec<Option<&str>> = fixes[0].removals.iter().map(|removal| removal.platform.as_deref()).collect();
+        assert_eq!(platforms, vec![None, Some("cfg(unix)")]);
+        assert_eq!(
+            fixes[0].to_string().lines().skip(1).collect::<Vec<_>>(),
+            vec!["  removed libc from [dependencies]", "  removed libc from [target.'cfg(unix)'.dependencies]"],
+        );
+    }
+    
+    #[test]
+    fn leaves_ignored_dependencies_alone() {
+        let manifest = format!("{}\n[dependencies]\nrand = \"0.8\"\n", PACKAGE);
+        
+        let (fixes, _dir) = plan(&manifest, "", &[]);
+        assert_eq!(fixes.len(), 1);
+        
+        let (fixes, _dir) = plan(&manifest, "", &["rand"]);
+        assert!(fixes.is_empty());
+    }
+}
@@ -0,0 +1,564 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use syn::visit::{self, Visit};
+use syn::{Item, UseTree};
+
+/// Kind of scope names are resolved in
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ScopeKind {
+    /// A file or inline module; names of enclosing modules are not visible inside it
+    Module,
+    /// A function, block, closure or match arm, which sees the names of enclosing scopes
+    Local,
+}
+
+/// Names declared, imported and bound in a single scope
+#[derive(Debug, Clone)]
+struct Scope {
+    kind: ScopeKind,
+    /// Path of the module for module scopes; relative to the file when its module path is unknown
+    module: Vec<String>,
+    /// Imported names and the fully qualified path outside the crate they refer to
+    imports: HashMap<String, String>,
+    /// Modules, types and traits declared in the scope or imported from the crate itself,
+    /// which shadow imports of enclosing scopes and crates of the same name
+    items: HashSet<String>,
+    /// Functions, constants, statics and macros declared in the scope, which only shadow
+    /// single-segment paths as they cannot start a longer one
+    values: HashSet<String>,
+    /// Local variables, which shadow single-segment paths
+    bindings: HashSet<String>,
+    /// Known traits and preludes imported in the scope
+    traits: Vec<String>,
+}
+
+/// What the first segments of a path refer to
+#[derive(Debug, Clone, PartialEq)]
+pub enum Resolution {
+    /// The external crate named by the first segment, e.g. `regex::Regex` or `::regex::Regex`
+    Crate,
+    /// An imported item outside the crate: its fully qualified path, and how many segments
+    /// of the path it stands for
+    Import(String, usize),
+    /// An item of the crate itself, a local variable, or a path that cannot be resolved
+    Local,
+}
+
+/// Resolves paths through the nested module, function and block scopes of a file
+pub struct ImportResolver<'a> {
+    /// Methods of known traits and preludes, keyed by path
+    trait_methods: &'a BTreeMap<String, Vec<String>>,
+    /// Module path of the file, empty when it is not known
+    root: Vec<String>,
+    /// Scopes enclosing the item being visited, innermost last
+    scopes: Vec<Scope>,
+    /// Module scopes of the file that were already left, for `super::` and `crate::` paths into them
+    modules: HashMap<Vec<String>, Scope>,
+}
+
+impl<'a> ImportResolver<'a> {
+    /// Create a resolver for a file with the given module path
+    pub fn new(trait_methods: &'a BTreeMap<String, Vec<String>>, root: Vec<String>) -> Self {
+        Self {
+            trait_methods,
+            root,
+            scopes: Vec::new(),
+            modules: HashMap::new(),
+        }
+    }
+    
+    /// Whether no scope has been entered yet
+    pub fn is_empty(&self) -> bool {
+        self.scopes.is_empty()
+    }
+    
+    /// Enter the file's root module (`None`) or an inline module declared in the current one
+    pub fn enter_module(&mut self, name: Option<&str>) {
+        let module = match name {
+            Some(name) => {
+                let mut module = self.current_module().to_vec();
+                module.push(name.to_string());
+                module
+            }
+            None => self.root.clone(),
+        };
+        self.push(ScopeKind::Module, module);
+    }
+    
+    /// Enter a function, block, closure or match arm
+    pub fn enter_scope(&mut self) {
+        self.push(ScopeKind::Local, Vec::new());
+    }
+    
+    /// Leave the innermost scope
+    pub fn exit(&mut self) {
+        if let Some(scope) = self.scopes.pop() {
+            if scope.kind == ScopeKind::Module {
+                self.modules.insert(scope.module.clone(), scope);
+            }
+        }
+    }
+    
+    fn push(&mut self, kind: ScopeKind, module: Vec<String>) {
+        self.scopes.push(Scope {
+            kind,
+            module,
+            imports: HashMap::new(),
+            items: HashSet::new(),
+            values: HashSet::new(),
+            bindings: HashSet::new(),
+            traits: Vec::new(),
+        });
+    }
+    
+    /// Declare the items of the innermost scope. Items and imports are visible in the whole
+    /// scope, so they are declared on entering it, before anything in it is resolved.
+    pub fn declare_items<'i>(&mut self, items: impl IntoIterator<Item = &'i Item>) {
+        for item in items {
+            let value = match item {
+                Item::Const(item) => Some(&item.ident),
+                Item::Fn(item) => Some(&item.sig.ident),
+                Item::Macro(item) => item.ident.as_ref(),
+                Item::Static(item) => Some(&item.ident),
+                _ => None,
+            };
+            if let (Some(name), Some(scope)) = (value, self.scopes.last_mut()) {
+                scope.values.insert(ident_name(name));
+            }
+            
+            let name = match item {
+                Item::Enum(item) => Some(&item.ident),
+                Item::Mod(item) => Some(&item.ident),
+                Item::Struct(item) => Some(&item.ident),
+                Item::Trait(item) => Some(&item.ident),
+                Item::TraitAlias(item) => Some(&item.ident),
+                Item::Type(item) => Some(&item.ident),
+                Item::Union(item) => Some(&item.ident),
+                Item::ExternCrate(item) => {
+                    // `extern crate foo as bar;` imports the crate under another name
+                    if let Some((_, rename)) = &item.rename {
+                        if item.ident != "self" {
+                            self.insert_import(rename.to_string(), item.ident.to_string());
+                        }
+                    }
+                    None
+                }
+                Item::Use(item) => {
+                    self.declare_use(&item.tree, &mut Vec::new(), item.leading_colon.is_some());
+                    None
+                }
+                _ => None,
+            };
+            
+            if let Some(name) = name {
+                self.insert_item(ident_name(name));
+            }
+        }
+    }
+    
+    /// Declare the local variables bound by a pattern in the innermost scope
+    pub fn bind(&mut self, pat: &syn::Pat) {
+        let mut bindings = Bindings(HashSet::new());
+        bindings.visit_pat(pat);
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.bindings.extend(bindings.0);
+        }
+    }
+    
+    /// Resolve what the first segments of a path refer to. `value` tells whether the path
+    /// may name a local variable, which macro and attribute paths cannot.
+    pub fn resolve(&self, segments: &[String], leading_colon: bool, value: bool) -> Resolution {
+        let first = match segments.first() {
+            Some(first) => first,
+            None => return Resolution::Local,
+        };
+        
+        // `::name` always names an external crate
+        if leading_colon {
+            return Resolution::Crate;
+        }
+        
+        // `crate::`, `self::` and `super::` paths, and paths through inline modules of the file
+        if let Some((scope, consumed)) = self.relative_module(segments) {
+            return match segments.get(consumed).and_then(|name| scope.imports.get(name)) {
+                Some(full_path) => Resolution::Import(full_path.clone(), consumed + 1),
+                None => Resolution::Local,
+            };
+        }
+        if matches!(first.as_str(), "crate" | "self" | "super" | "Self") {
+            return Resolution::Local;
+        }
+        
+        let single = segments.len() == 1;
+        for scope in self.visible_scopes() {
+            let shadowed = single && (value && scope.bindings.contains(first) || scope.values.contains(first));
+            if shadowed || scope.items.contains(first) {
+                return Resolution::Local;
+            }
+            if let Some(full_path) = scope.imports.get(first) {
+                // `use anyhow::anyhow;` imports a macro, while `anyhow::Result` still names the crate
+                if !single && (full_path == first || full_path.starts_with(&format!("{}::", first))) {
+                    return Resolution::Crate;
+                }
+                return Resolution::Import(full_path.clone(), 1);
+            }
+        }
+        
+        Resolution::Crate
+    }
+    
+    /// Known traits and preludes in scope that provide a method called `method`
+    pub fn traits_providing(&self, method: &str) -> Vec<String> {
+        let mut traits: Vec<String> = self.visible_scopes()
+            .flat_map(|scope| &scope.traits)
+            .filter(|path| self.trait_methods.get(*path).is_some_and(|methods| methods.iter().any(|known| known == method)))
+            .cloned()
+            .collect();
+        traits.dedup();
+        traits
+    }
+    
+    /// Scopes whose names are visible from the innermost one: up to the enclosing module
+    fn visible_scopes(&self) -> impl Iterator<Item = &Scope> {
+        let module = self.scopes.iter().rposition(|scope| scope.kind == ScopeKind::Module).unwrap_or(0);
+        self.scopes[module..].iter().rev()
+    }
+    
+    /// Path of the innermost module
+    fn current_module(&self) -> &[String] {
+        self.scopes.iter()
+            .rev()
+            .find(|scope| scope.kind == ScopeKind::Module)
+            .map_or(&self.root[..], |scope| &scope.module[..])
+    }
+    
+    /// Scope of the module at `path`, if it is part of this file
+    fn module_scope(&self, path: &[String]) -> Option<&Scope> {
+        self.scopes.iter()
+            .rev()
+            .find(|scope| scope.kind == ScopeKind::Module && scope.module == path)
+            .or_else(|| self.modules.get(path))
+    }
+    
+    /// Resolve the leading `crate`, `self` and `super` segments of a path, and the inline
+    /// modules of the file after them, to a module of this file. Returns the module's scope
+    /// and the number of segments naming it.
+    fn relative_module(&self, segments: &[String]) -> Option<(&Scope, usize)> {
+        let current = self.current_module();
+        let (mut module, mut consumed) = match segments.first()?.as_str() {
+            "crate" => (vec!["crate".to_string()], 1),
+            "self" => (current.to_vec(), 1),
+            "super" => (current.to_vec(), 0),
+            // `inner::Item` for a module `inner` declared in the current one
+            child => {
+                let mut module = current.to_vec();
+                module.push(child.to_string());
+                self.module_scope(&module)?;
+                (current.to_vec(), 0)
+            }
+        };
+        
+        while let Some(segment) = segments.get(consumed) {
+            if segment == "super" {
+                // The crate root has no parent; with an unknown file module the file is the top
+                if module.is_empty() || module == ["crate"] {
+                    return None;
+                }
+                module.pop();
+            } else {
+                module.push(segment.clone());
+                if self.module_scope(&module).is_none() {
+                    module.pop();
+                    break;
+                }
+            }
+            consumed += 1;
+        }
+        
+        Some((self.module_scope(&module)?, consumed))
+    }
+    
+    /// Declare the names a use tree imports into the innermost scope
+    fn declare_use(&mut self, tree: &UseTree, prefix: &mut Vec<String>, leading_colon: bool) {
+        match tree {
+            UseTree::Path(path) => {
+                prefix.push(ident_name(&path.ident));
+                self.declare_use(&path.tree, prefix, leading_colon);
+                prefix.pop();
+            }
+            UseTree::Name(name) => {
+                // `use foo::{self};` imports `foo` itself
+                if name.ident == "self" {
+                    if let Some(last) = prefix.last().cloned() {
+                        self.declare_import(prefix, last, leading_colon);
+                    }
+                } else {
+                    let name = ident_name(&name.ident);
+                    prefix.push(name.clone());
+                    self.declare_import(prefix, name, leading_colon);
+                    prefix.pop();
+                }
+            }
+            UseTree::Rename(rename) => {
+                let path = if rename.ident == "self" {
+                    prefix.clone()
+                } else {
+                    let mut path = prefix.clone();
+                    path.push(ident_name(&rename.ident));
+                    path
+                };
+                self.declare_import(&path, ident_name(&rename.rename), leading_colon);
+            }
+            UseTree::Glob(_) => self.declare_glob(prefix, leading_colon),
+            UseTree::Group(group) => {
+                for item in &group.items {
+                    self.declare_use(item, prefix, leading_colon);
+                }
+            }
+        }
+    }
+    
+    /// Declare `name` as an import of `path`; `Trait as _` only brings the trait's methods into scope
+    fn declare_import(&mut self, path: &[String], name: String, leading_colon: bool) {
+        let full_path = match self.resolve(path, leading_colon, false) {
+            Resolution::Crate => path.join("::"),
+            Resolution::Import(full_path, consumed) => qualify(&full_path, &path[consumed..]),
+            Resolution::Local => {
+                if name != "_" {
+                    self.insert_item(name);
+                }
+                return;
+            }
+        };
+        
+        self.import_traits(&full_path, false);
+        if name != "_" {
+            self.insert_import(name, full_path);
+        }
+    }
+    
+    /// Declare the names of a glob import. Globs of modules in this file (`use super::*;`)
+    /// import what those modules declare and import; globs of other crates only bring their
+    /// known traits and preludes into scope.
+    fn declare_glob(&mut self, prefix: &[String], leading_colon: bool) {
+        if !leading_colon {
+            if let Some((scope, consumed)) = self.relative_module(prefix) {
+                if consumed == prefix.len() {
+                    let scope = scope.clone();
+                    if let Some(innermost) = self.scopes.last_mut() {
+                        // Glob imports never shadow explicit ones
+                        for (name, full_path) in scope.imports {
+                            innermost.imports.entry(name).or_insert(full_path);
+                        }
+                        innermost.items.extend(scope.items);
+                        innermost.values.extend(scope.values);
+                        innermost.traits.extend(scope.traits);
+                    }
+                    return;
+                }
+            }
+        }
+        
+        let full_path = match self.resolve(prefix, leading_colon, false) {
+            Resolution::Crate => prefix.join("::"),
+            Resolution::Import(full_path, consumed) => qualify(&full_path, &prefix[consumed..]),
+            Resolution::Local => return,
+        };
+        self.import_traits(&full_path, true);
+    }
+    
+    /// Bring the known traits imported as `path` into scope; a glob import of `path`
+    /// brings in the prelude at `path` and the known traits declared in it
+    fn import_traits(&mut self, path: &str, glob: bool) {
+        let imported: Vec<String> = self.trait_methods.keys()
+            .filter(|known| {
+                known.as_str() == path
+                    || glob && known.rsplit_once("::").is_some_and(|(parent, name)| parent == path && name.starts_with(char::is_uppercase))
+            })
+            .cloned()
+            .collect();
+        
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.traits.extend(imported);
+        }
+    }
+    
+    fn insert_import(&mut self, name: String, full_path: String) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.items.remove(&name);
+            scope.imports.insert(name, full_path);
+        }
+    }
+    
+    fn insert_item(&mut self, name: String) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.imports.remove(&name);
+            scope.items.insert(name);
+        }
+    }
+}
+
+/// Fully qualified path of a path whose leading segments resolved to `full_path`, given
+/// the segments after them, e.g. `regex::Regex::new` for `Regex::new` with `use regex::Regex`
+pub fn qualify(full_path: &str, rest: &[String]) -> String {
+    if rest.is_empty() {
+        full_path.to_string()
+    } else {
+        format!("{}::{}", full_path, rest.join("::"))
+    }
+}
+
+/// Name of an identifier without the `r#` of raw identifiers
+fn ident_name(ident: &syn::Ident) -> String {
+    ident.to_string().trim_start_matches("r#").to_string()
+}
+
+/// Collects the local variables a pattern binds
+struct Bindings(HashSet<String>);
+
+impl<'ast> Visit<'ast> for Bindings {
+    fn visit_pat_ident(&mut self, node: &'ast syn::PatIdent) {
+        // Uppercase identifiers in patterns are unit structs, variants and constants, not bindings
+        let name = ident_name(&node.ident);
+        if name.starts_with(|c: char| c.is_lowercase() || c == '_') {
+            self.0.insert(name);
+        }
+        visit::visit_pat_ident(self, node);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    
+    /// Declare the items of `source` in the innermost scope
+    fn declare(resolver: &mut ImportResolver, source: &str) {
+        let file = syn::parse_file(source).unwrap();
+        resolver.declare_items(&file.items);
+    }
+    
+    /// Resolve a path written as in source, e.g. `::regex::Regex`
+    fn resolve(resolver: &ImportResolver, path: &str) -> Resolution {
+        let leading_colon = path.starts_with("::");
+        let segments: Vec<String> = path.trim_start_matches("::").split("::").map(str::to_string).collect();
+        resolver.resolve(&segments, leading_colon, true)
+    }
+    
+    fn import(full_path: &str, consumed: usize) -> Resolution {
+        Resolution::Import(full_path.to_string(), consumed)
+    }
+    
+    fn crate_root() -> Vec<String> {
+        vec!["crate".to_string()]
+    }
+    
+    #[test]
+    fn resolves_imports_and_crate_paths() {
+        let trait_methods = BTreeMap::new();
+        let mut resolver = ImportResolver::new(&trait_methods, crate_root());
+        resolver.enter_module(None);
+        declare(&mut resolver, "use regex::Regex; use serde_json as json; use anyhow::{anyhow, Context as _}; extern crate log as logging;");
+        
+        assert_eq!(resolve(&resolver, "Regex::new"), import("regex::Regex", 1));
+        assert_eq!(resolve(&resolver, "json::Value"), import("serde_json", 1));
+        assert_eq!(resolve(&resolver, "logging::info"), import("log", 1));
+        // The `anyhow!` macro is imported, but `anyhow::Result` still names the crate
+        assert_eq!(resolve(&resolver, "anyhow"), import("anyhow::anyhow", 1));
+        assert_eq!(resolve(&resolver, "anyhow::Result"), Resolution::Crate);
+        assert_eq!(resolve(&resolver, "Context"), Resolution::Crate);
+        assert_eq!(resolve(&resolver, "tokio::spawn"), Resolution::Crate);
+        assert_eq!(resolve(&resolver, "crate::Regex"), import("regex::Regex", 2));
+        assert_eq!(resolve(&resolver, "self::json::Value"), import("serde_json", 2));
+        assert_eq!(resolve(&resolver, "Self::new"), Resolution::Local);
+    }
+    
+    #[test]
+    fn items_and_bindings_shadow_crates() {
+        let trait_methods = BTreeMap::new();
+        let mut resolver = ImportResolver::new(&trait_methods, crate_root());
+        resolver.enter_module(None);
+        declare(&mut resolver, "mod regex {} fn log() {} use crate::config::Config;");
+        
+        assert_eq!(resolve(&resolver, "regex::Regex"), Resolution::Local);
+        assert_eq!(resolve(&resolver, "::regex::Regex"), Resolution::Crate);
+        // Functions only shadow single-segment paths
+        assert_eq!(resolve(&resolver, "log"), Resolution::Local);
+        assert_eq!(resolve(&resolver, "log::info"), Resolution::Crate);
+        assert_eq!(resolve(&resolver, "Config::load"), Resolution::Local);
+        
+        resolver.enter_scope();
+        let stmt: syn::Stmt = syn::parse_str("let (serde, Foo) = pair;").unwrap();
+        match stmt {
+            syn::Stmt::Local(local) => resolver.bind(&local.pat),
+            _ => unreachable!(),
+        }
+        declare(&mut resolver, "use tokio::sync::Mutex;");
+        
+        assert_eq!(resolve(&resolver, "serde"), Resolution::Local);
+        assert_eq!(resolver.resolve(&["serde".to_string()], false, false), Resolution::Crate);
+        assert_eq!(resolve(&resolver, "serde::Serialize"), Resolution::Crate);
+        assert_eq!(resolve(&resolver, "Foo"), Resolution::Crate);
+        assert_eq!(resolve(&resolver, "Mutex::new"), import("tokio::sync::Mutex", 1));
+        
+        // Names of a block are gone once it is left
+        resolver.exit();
+        assert_eq!(resolve(&resolver, "Mutex::new"), Resolution::Crate);
+    }
+    
+    #[test]
+    fn resolves_paths_through_inline_modules() {
+        let trait_methods = BTreeMap::new();
+        let mut resolver = ImportResolver::new(&trait_methods, crate_root());
+        resolver.enter_module(None);
+        declare(&mut resolver, "use regex::Regex; mod inner {}");
+        
+        resolver.enter_module(Some("inner"));
+        // Imports of the enclosing module are not visible in an inline module
+        assert_eq!(resolve(&resolver, "Regex::new"), Resolution::Crate);
+        assert_eq!(resolve(&resolver, "super::Regex::new"), import("regex::Regex", 2));
+        assert_eq!(resolve(&resolver, "crate::Regex::new"), import("regex::Regex", 2));
+        declare(&mut resolver, "use super::*; use tokio::task::spawn;");
+        assert_eq!(resolve(&resolver, "Regex::new"), import("regex::Regex", 1));
+        resolver.exit();
+        
+        assert_eq!(resolve(&resolver, "inner::spawn"), import("tokio::task::spawn", 2));
+        assert_eq!(resolve(&resolver, "self::inner::spawn"), import("tokio::task::spawn", 3));
+        assert_eq!(resolve(&resolver, "inner::Other"), Resolution::Local);
+    }
+    
+    #[test]
+    fn resolves_files_with_unknown_module_paths() {
+        let trait_methods = BTreeMap::new();
+        let mut resolver = ImportResolver::new(&trait_methods, Vec::new());
+        resolver.enter_module(None);
+        declare(&mut resolver, "use regex::Regex;");
+        
+        assert_eq!(resolve(&resolver, "self::Regex"), import("regex::Regex", 2));
+        // The parent of the file is not known, nor is the crate root
+        assert_eq!(resolve(&resolver, "super::Regex"), Resolution::Local);
+        assert_eq!(resolve(&resolver, "crate::Regex"), Resolution::Local);
+    }
+    
+    #[test]
+    fn finds_traits_in_scope() {
+        let mut trait_methods = BTreeMap::new();
+        trait_methods.insert("itertools::Itertools".to_string(), vec!["join".to_string(), "sorted".to_string()]);
+        trait_methods.insert("rayon::prelude".to_string(), vec!["par_iter".to_string()]);
+        trait_methods.insert("rayon::iter::ParallelIterator".to_string(), vec!["for_each".to_string()]);
+        let mut resolver = ImportResolver::new(&trait_methods, crate_root());
+        resolver.enter_module(None);
+        declare(&mut resolver, "use itertools::Itertools as _;");
+        
+        assert_eq!(resolver.traits_providing("join"), vec!["itertools::Itertools"]);
+        assert!(resolver.traits_providing("par_iter").is_empty());
+        
+        resolver.enter_scope();
+        declare(&mut resolver, "use rayon::prelude::*; use rayon::iter::*;");
+        assert_eq!(resolver.traits_providing("par_iter"), vec!["rayon::prelude"]);
+        assert_eq!(resolver.traits_providing("for_each"), vec!["rayon::iter::ParallelIterator"]);
+        assert_eq!(resolver.traits_providing("sorted"), vec!["itertools::Itertools"]);
+        
+        resolver.exit();
+        assert!(resolver.traits_providing("par_iter").is_empty());
+    }
+}
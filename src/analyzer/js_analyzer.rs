@@ -0,0 +1,865 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use anyhow::Result;
+
+use crate::manifest::cargo::TargetKind;
+use crate::manifest::dependency::Dependency;
+use crate::analyzer::{DependencyUsageData, DependencyUsage, UsageType};
+use crate::analyzer::lexer::{SourceReader, Token, TokenKind, TokenLookup};
+use crate::analyzer::module_graph::ModuleInfo;
+use crate::analyzer::source::{analyze_files_cached, classify_by_dirs, relative_path, FileAnalysis};
+use crate::utils::config::Config;
+use crate::utils::fs::FileWalker;
+
+/// Extensions of the JavaScript and TypeScript files that are analyzed
+pub const JS_EXTENSIONS: &[&str] = &["js", "mjs", "cjs", "jsx", "ts", "mts", "cts", "tsx"];
+
+/// Built-in Node.js modules, whose type declarations come from `@types/node`
+const NODE_BUILTINS: &[&str] = &[
+    "assert", "async_hooks", "buffer", "child_process", "cluster", "console", "constants", "crypto",
+    "dgram", "diagnostics_channel", "dns", "domain", "events", "fs", "http", "http2", "https",
+    "inspector", "module", "net", "os", "path", "perf_hooks", "process", "punycode", "querystring",
+    "readline", "repl", "stream", "string_decoder", "timers", "tls", "trace_events", "tty", "url",
+    "util", "v8", "vm", "wasi", "worker_threads", "zlib",
+];
+
+/// Keywords after which a `/` starts a regular expression rather than a division
+const KEYWORDS_BEFORE_EXPRESSION: &[&str] = &[
+    "return", "typeof", "instanceof", "in", "of", "new", "delete", "void", "throw", "case", "do",
+    "else", "yield", "await",
+];
+
+/// Analyzer for JavaScript and TypeScript files of Node.js packages
+pub struct JsAnalyzer {
+    project_path: PathBuf,
+    /// Decides which files below the project path are analyzed
+    walker: FileWalker,
+    /// Number of threads scanning files in parallel (`None` uses one per CPU)
+    threads: Option<usize>,
+    /// File caching per-file results between runs, if caching is enabled
+    cache_file: Option<PathBuf>,
+}
+
+/// Splits source into tokens, skipping whitespace, comments and the text of template literals
+struct Lexer {
+    reader: SourceReader,
+}
+
+/// A local name bound by an import, e.g. `_` in `import _ from 'lodash'`
+#[derive(Debug, Clone)]
+struct Binding {
+    /// Module specifier the binding is imported from
+    specifier: String,
+    /// Path of what the binding refers to: the specifier for the module itself, or
+    /// `specifier.name` for a named export
+    path: String,
+    /// Dependencies the module belongs to
+    dependencies: Vec<String>,
+}
+
+/// A name in an import or export clause: `name`, `name as local` or `type name`
+struct ClauseEntry {
+    /// Exported name, `None` for default and namespace imports
+    name: Option<String>,
+    /// Local name, or the name re-exported under
+    local: String,
+    /// Token of the entry, where its usage is recorded
+    token: usize,
+    is_type: bool,
+    namespace: bool,
+}
+
+/// Finds the imports of a single file and the usages of the names they bind
+struct ImportCollector<'a> {
+    tokens: &'a [Token],
+    /// Dependency names keyed by the package name code imports them by
+    packages: HashMap<&'a str, &'a str>,
+    file_path: PathBuf,
+    target: TargetKind,
+    /// Whether `@types/*` packages are attributed the imports of the packages they describe
+    typescript: bool,
+    bindings: HashMap<String, Binding>,
+    /// Tokens declaring bindings, which are not usages of them
+    declarations: HashSet<usize>,
+    usage_data: &'a mut DependencyUsageData,
+}
+
+impl JsAnalyzer {
+    /// Create a new JavaScript and TypeScript analyzer for the given project path, using the default configuration
+    pub fn new<P: AsRef<Path>>(project_path: P) -> Result<Self> {
+        let project_path = project_path.as_ref().to_path_buf();
+        let walker = FileWalker::new(&project_path, &Config::default())?;
+        
+        Ok(Self {
+            project_path,
+            walker,
+            threads: None,
+            cache_file: None,
+        })
+    }
+    
+    /// Discover files with the given walker instead of the default one
+    pub fn with_walker(mut self, walker: FileWalker) -> Self {
+        self.walker = walker;
+        self
+    }
+    
+    /// Scan files with the given number of threads instead of one per CPU
+    pub fn with_threads(mut self, threads: Option<usize>) -> Self {
+        self.threads = threads;
+        self
+    }
+    
+    /// Reuse the results of unchanged files from the cache at `cache_file`, and update it
+    pub fn with_cache(mut self, cache_file: PathBuf) -> Self {
+        self.cache_file = Some(cache_file);
+        self
+    }
+    
+    /// Analyze JavaScript and TypeScript code to detect dependency usage
+    pub fn analyze(&self, dependencies: &[Dependency]) -> Result<DependencyUsageData> {
+        // Find all source files in the project, in a stable order
+        let files = self.walker.files_with_extensions(&self.project_path, JS_EXTENSIONS);
+        
+        let module_of = |file_path: &Path| ModuleInfo {
+            target: classify(relative_path(&self.project_path, file_path)),
+            module: String::new(),
+            cfg: None,
+            public: false,
+        };
+        let options = FileAnalysis {
+            project_path: &self.project_path,
+            threads: self.threads,
+            cache_file: self.cache_file.as_deref(),
+        };
+        // There are no trait methods to attribute calls through
+        let no_trait_methods = BTreeMap::new();
+        
+        analyze_files_cached(&files, dependencies, &no_trait_methods, &options, module_of, |file_content, file_path, module, file_data| {
+            analyze_file(file_content, file_path, module.target, dependencies, file_data)
+        })
+    }
+}
+
+/// Analyze the content of a single JavaScript or TypeScript file for dependency usage
+fn analyze_file(
+    file_content: &str,
+    file_path: &Path,
+    target: TargetKind,
+    dependencies: &[Dependency],
+    usage_data: &mut DependencyUsageData,
+) {
+    let tokens = Lexer::lex(file_content);
+    let typescript = file_path.extension()
+        .is_some_and(|ext| ext == "ts" || ext == "tsx" || ext == "mts" || ext == "cts");
+    
+    let mut collector = ImportCollector {
+        tokens: &tokens,
+        packages: dependencies.iter().map(|dep| (dep.import_name.as_str(), dep.name.as_str())).collect(),
+        file_path: file_path.to_path_buf(),
+        target,
+        typescript,
+        bindings: HashMap::new(),
+        declarations: HashSet::new(),
+        usage_data,
+    };
+    
+    // Imports are hoisted, so names can be used before the statement binding them
+    for i in 0..tokens.len() {
+        collector.collect_import(i);
+    }
+    for i in 0..tokens.len() {
+        collector.collect_binding_usage(i);
+    }
+}
+
+/// Determine the kind of target a file belongs to from its path: test and spec files, examples,
+/// benchmarks, and everything else as the package itself
+fn classify(relative: &Path) -> TargetKind {
+    let file_name = relative.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    let is_test = |file_name: &str| file_name.contains(".test.") || file_name.contains(".spec.");
+    
+    match classify_by_dirs(relative, &["test", "tests", "__tests__", "__mocks__", "spec", "e2e"], is_test) {
+        Some(kind) => kind,
+        None if file_name.contains(".bench.") => TargetKind::Bench,
+        None => TargetKind::Lib,
+    }
+}
+
+/// Name of the package a module specifier imports from: `lodash` for `lodash/fp` and
+/// `@scope/pkg` for `@scope/pkg/sub`. Relative paths, URLs, `node:` built-ins and
+/// `#internal` subpath imports have none.
+fn package_name(specifier: &str) -> Option<&str> {
+    if specifier.is_empty()
+        || specifier.starts_with('.')
+        || specifier.starts_with('/')
+        || specifier.starts_with('#')
+        || specifier.contains(':')
+    {
+        return None;
+    }
+    
+    let mut segments = specifier.splitn(3, '/');
+    let first = segments.next()?;
+    if first.starts_with('@') {
+        let second = segments.next().filter(|name| !name.is_empty())?;
+        Some(&specifier[..first.len() + 1 + second.len()])
+    } else {
+        Some(first)
+    }
+}
+
+/// Name of the `@types` package declaring the types of a package: `@types/lodash` for
+/// `lodash`, `@types/scope__pkg` for `@scope/pkg` and `@types/node` for built-in modules
+fn types_package(specifier: &str) -> Option<String> {
+    let builtin = specifier.strip_prefix("node:").unwrap_or(specifier);
+    let builtin = builtin.split('/').next().unwrap_or(builtin);
+    if specifier.starts_with("node:") || NODE_BUILTINS.contains(&builtin) {
+        return Some("@types/node".to_string());
+    }
+    
+    let package = package_name(specifier)?;
+    match package.strip_prefix('@') {
+        Some(scoped) if scoped.starts_with("types/") => None,
+        Some(scoped) => Some(format!("@types/{}", scoped.replacen('/', "__", 1))),
+        None => Some(format!("@types/{}", package)),
+    }
+}
+
+impl<'a> TokenLookup<'a> for ImportCollector<'a> {
+    fn tokens(&self) -> &'a [Token] {
+        self.tokens
+    }
+}
+
+impl<'a> ImportCollector<'a> {
+    /// Dependencies a module specifier belongs to: the package itself and, in TypeScript,
+    /// the `@types` package describing it
+    fn dependencies_of(&self, specifier: &str) -> Vec<String> {
+        let mut dependencies: Vec<String> = package_name(specifier)
+            .and_then(|package| self.packages.get(package))
+            .map(|dep| vec![dep.to_string()])
+            .unwrap_or_default();
+        
+        if self.typescript {
+            if let Some(dep) = types_package(specifier).and_then(|types| self.packages.get(types.as_str())) {
+                dependencies.push(dep.to_string());
+            }
+        }
+        
+        dependencies
+    }
+    
+    /// Record a usage of the module `specifier` at token `i` for every dependency it belongs to
+    fn record_usage(&mut self, dependencies: &[String], i: usize, imported_item: String, item: String, usage_type: UsageType) {
+        let token = &self.tokens[i];
+        
+        for dep_name in dependencies {
+            if let Some(usages) = self.usage_data.usage_locations.get_mut(dep_name) {
+                usages.push(DependencyUsage {
+                    file: self.file_path.clone(),
+                    line: token.line,
+                    column: token.column,
+                    end_line: token.end_line,
+                    end_column: token.end_column,
+                    imported_item: imported_item.clone(),
+                    item: Some(item.clone()),
+                    usage_type: usage_type.clone(),
+                    target: self.target,
+                    module: String::new(),
+                    cfg: None,
+                    inactive: false,
+                    in_macro: false,
+                    exposure: None,
+                });
+            }
+        }
+    }
+    
+    /// Record the import starting at token `i`, if any: an `import` or re-exporting `export`
+    /// statement, a dynamic `import()` or a `require()` call
+    fn collect_import(&mut self, i: usize) {
+        if self.is_property(i) {
+            return;
+        }
+        
+        match self.ident(i) {
+            Some("import") => self.collect_import_statement(i),
+            Some("export") => self.collect_reexport(i),
+            Some("require") => self.collect_require(i),
+            _ => {}
+        }
+    }
+    
+    /// `import 'x'`, `import('x')`, `import x, { y as z } from 'x'`, `import * as x from 'x'`,
+    /// `import type { X } from 'x'` and TypeScript's `import x = require('x')`
+    fn collect_import_statement(&mut self, i: usize) {
+        let mut j = i + 1;
+        
+        // Side-effect imports and dynamic imports with a literal specifier
+        if let Some(specifier) = self.string(j) {
+            let dependencies = self.dependencies_of(specifier);
+            self.record_usage(&dependencies, j, specifier.to_string(), specifier.to_string(), UsageType::Import);
+            return;
+        }
+        if self.is_punct(j, '(') {
+            if let (Some(specifier), true) = (self.string(j + 1), self.is_punct(j + 2, ')')) {
+                let dependencies = self.dependencies_of(specifier);
+                self.record_usage(&dependencies, j + 1, format!("import({})", specifier), specifier.to_string(), UsageType::Import);
+            }
+            return;
+        }
+        
+        // `import type X from 'x'`, unless `type` is the name of a default import
+        let default_named_type = (self.is_ident(j + 1, "from") && self.string(j + 2).is_some())
+            || self.is_punct(j + 1, ',')
+            || self.is_punct(j + 1, '=');
+        let type_only = self.is_ident(j, "type") && !default_named_type;
+        if type_only {
+            j += 1;
+        }
+        
+        let mut entries = Vec::new();
+        loop {
+            if self.is_ident(j, "from") && self.string(j + 1).is_some() {
+                j += 1;
+                break;
+            }
+            
+            match self.kind(j) {
+                Some(TokenKind::Ident(local)) if self.is_punct(j + 1, '=') => {
+                    // `import x = require('x')`
+                    if self.is_ident(j + 2, "require") && self.is_punct(j + 3, '(') && self.is_punct(j + 5, ')') {
+                        if let Some(specifier) = self.string(j + 4) {
+                            let entry = ClauseEntry { name: None, local: local.clone(), token: j, is_type: type_only, namespace: true };
+                            self.record_clause(specifier, &[entry], false);
+                        }
+                    }
+                    return;
+                }
+                Some(TokenKind::Ident(local)) => {
+                    entries.push(ClauseEntry { name: None, local: local.clone(), token: j, is_type: type_only, namespace: false });
+                    j += 1;
+                }
+                Some(TokenKind::Punct('*')) if self.is_ident(j + 1, "as") => {
+                    let local = match self.ident(j + 2) {
+                        Some(local) => local.to_string(),
+                        None => return,
+                    };
+                    entries.push(ClauseEntry { name: None, local, token: j + 2, is_type: type_only, namespace: true });
+                    j += 3;
+                }
+                Some(TokenKind::Punct('{')) => match self.named_entries(j, type_only) {
+                    Some((named, end)) => {
+                        entries.extend(named);
+                        j = end + 1;
+                    }
+                    None => return,
+                },
+                Some(TokenKind::Punct(',')) => j += 1,
+                _ => return,
+            }
+        }
+        
+        if let Some(specifier) = self.string(j) {
+            if entries.is_empty() {
+                // `import {} from 'x'` only loads the module
+                let dependencies = self.dependencies_of(specifier);
+                self.record_usage(&dependencies, j, specifier.to_string(), specifier.to_string(), UsageType::Import);
+            } else {
+                self.record_clause(specifier, &entries, false);
+            }
+        }
+    }
+    
+    /// `export * from 'x'`, `export * as ns from 'x'` and `export { a, b as c } from 'x'`.
+    /// Exports of local names are not imports.
+    fn collect_reexport(&mut self, i: usize) {
+        let mut j = i + 1;
+        let type_only = self.is_ident(j, "type");
+        if type_only {
+            j += 1;
+        }
+        
+        let entries = if self.is_punct(j, '*') {
+            let (local, token) = match self.ident(j + 2) {
+                Some(local) if self.is_ident(j + 1, "as") => (local.to_string(), j + 2),
+                _ => ("*".to_string(), j),
+            };
+            j = token + 1;
+            vec![ClauseEntry { name: None, local, token, is_type: type_only, namespace: true }]
+        } else if self.is_punct(j, '{') {
+            match self.named_entries(j, type_only) {
+                Some((entries, end)) => {
+                    j = end + 1;
+                    entries
+                }
+                None => return,
+            }
+        } else {
+            return;
+        };
+        
+        if self.is_ident(j, "from") {
+            if let Some(specifier) = self.string(j + 1) {
+                self.record_clause(specifier, &entries, true);
+            }
+        }
+    }
+    
+    /// `require('x')`, binding `const x = require('x')`, `const { a, b: c } = require('x')`
+    /// and `const y = require('x').y`
+    fn collect_require(&mut self, i: usize) {
+        let specifier = match self.string(i + 2) {
+            Some(specifier) if self.is_punct(i + 1, '(') && self.is_punct(i + 3, ')') => specifier,
+            _ => return,
+        };
+        
+        let dependencies = self.dependencies_of(specifier);
+        self.record_usage(&dependencies, i + 2, format!("require({})", specifier), specifier.to_string(), UsageType::Import);
+        
+        // A property of the module selected right away
+        let path = match self.ident(i + 5) {
+            Some(property) if self.is_punct(i + 4, '.') => format!("{}.{}", specifier, property),
+            _ => specifier.to_string(),
+        };
+        
+        if i < 2 || !self.is_punct(i - 1, '=') {
+            return;
+        }
+        
+        match self.kind(i - 2) {
+            Some(TokenKind::Ident(local)) => {
+                self.declarations.insert(i - 2);
+                self.bind(local, specifier, path, &dependencies);
+            }
+            Some(TokenKind::Punct('}')) => {
+                // Destructuring: walk back to the opening brace; nested patterns are not followed
+                let mut k = i - 3;
+                let mut names = Vec::new();
+                while k > 0 && !self.is_punct(k, '{') {
+                    if let Some(name) = self.ident(k) {
+                        // `{ a: b }` binds `b` to the export `a`
+                        let export = if k >= 2 && self.is_punct(k - 1, ':') { self.ident(k - 2) } else { None };
+                        let is_key = self.is_punct(k + 1, ':');
+                        if !is_key {
+                            names.push((k, name, export.unwrap_or(name)));
+                        }
+                    }
+                    k -= 1;
+                }
+                
+                for (token, local, export) in names.into_iter().rev() {
+                    self.declarations.insert(token);
+                    self.bind(local, specifier, format!("{}.{}", path, export), &dependencies);
+                }
+            }
+            _ => {}
+        }
+    }
+    
+    /// Parse the braces of a named import or export clause starting at token `open`, returning
+    /// the entries and the index of the closing brace
+    fn named_entries(&self, open: usize, type_only: bool) -> Option<(Vec<ClauseEntry>, usize)> {
+        let mut entries = Vec::new();
+        let mut j = open + 1;
+        
+        loop {
+            if self.is_punct(j, '}') {
+                return Some((entries, j));
+            }
+            if self.is_punct(j, ',') {
+                j += 1;
+                continue;
+            }
+            
+            // `type X` inside the braces marks a single type-only entry
+            let is_type = type_only || (self.is_ident(j, "type") && (self.ident(j + 1).is_some() || self.string(j + 1).is_some()) && !self.is_ident(j + 1, "as"));
+            if is_type && !type_only {
+                j += 1;
+            }
+            
+            // Names of exports can also be string literals: `{ "a-b" as ab }`
+            let name = self.ident(j).or_else(|| self.string(j))?.to_string();
+            let (local, token) = match self.ident(j + 2) {
+                Some(local) if self.is_ident(j + 1, "as") => (local.to_string(), j + 2),
+                _ => (name.clone(), j),
+            };
+            entries.push(ClauseEntry { name: Some(name), local, token, is_type, namespace: false });
+            j = token + 1;
+        }
+    }
+    
+    /// Record the entries of an import or re-export clause of `specifier`, binding the local
+    /// names of imports
+    fn record_clause(&mut self, specifier: &str, entries: &[ClauseEntry], reexport: bool) {
+        let dependencies = self.dependencies_of(specifier);
+        
+        for entry in entries {
+            let (imported_item, path) = match (&entry.name, reexport) {
+                (Some(name), false) => (format!("{} (from {})", name, specifier), format!("{}.{}", specifier, name)),
+                (Some(name), true) => (format!("{} (re-exported from {})", name, specifier), format!("{}.{}", specifier, name)),
+                (None, false) if entry.namespace => (format!("* as {} (from {})", entry.local, specifier), specifier.to_string()),
+                (None, false) => (format!("{} (default from {})", entry.local, specifier), specifier.to_string()),
+                (None, true) => (format!("{} (re-exported from {})", entry.local, specifier), specifier.to_string()),
+            };
+            let usage_type = if entry.is_type { UsageType::Type } else { UsageType::Import };
+            
+            self.record_usage(&dependencies, entry.token, imported_item, path.clone(), usage_type);
+            
+            if !reexport {
+                self.declarations.insert(entry.token);
+                self.bind(&entry.local, specifier, path, &dependencies);
+            }
+        }
+    }
+    
+    fn bind(&mut self, local: &str, specifier: &str, path: String, dependencies: &[String]) {
+        if dependencies.is_empty() {
+            return;
+        }
+        
+        self.bindings.insert(local.to_string(), Binding {
+            specifier: specifier.to_string(),
+            path,
+            dependencies: dependencies.to_vec(),
+        });
+    }
+    
+    /// Record a usage of an imported name at token `i`: calls, member accesses, constructors,
+    /// JSX elements and type references
+    fn collect_binding_usage(&mut self, i: usize) {
+        let name = match self.ident(i) {
+            Some(name) => name,
+            None => return,
+        };
+        let binding = match self.bindings.get(name) {
+            Some(binding) if !self.declarations.contains(&i) => binding.clone(),
+            _ => return,
+        };
+        
+        // Property accesses, object keys (`{ name: ... }`) and closing JSX tags (`</Name>`)
+        // are not references to the binding
+        let previous = i.checked_sub(1);
+        let is_key = self.is_punct(i + 1, ':') && previous.is_some_and(|p| self.is_punct(p, '{') || self.is_punct(p, ','));
+        let is_closing_tag = i >= 2 && self.is_punct(i - 1, '/') && self.is_punct(i - 2, '<');
+        if self.is_property(i) || is_key || is_closing_tag {
+            return;
+        }
+        
+        let after_type_keyword = previous.and_then(|p| self.ident(p))
+            .is_some_and(|word| matches!(word, "new" | "extends" | "implements" | "instanceof" | "keyof" | "typeof"));
+        let is_element = previous.is_some_and(|p| self.is_punct(p, '<'));
+        
+        let (imported_item, path, usage_type) = match self.ident(i + 2) {
+            Some(member) if self.is_punct(i + 1, '.') => {
+                let usage_type = if after_type_keyword || is_element {
+                    UsageType::Type
+                } else if self.is_punct(i + 3, '(') {
+                    UsageType::Function
+                } else {
+                    UsageType::Other
+                };
+                (format!("{}.{} (from {})", name, member, binding.specifier), format!("{}.{}", binding.path, member), usage_type)
+            }
+            _ if after_type_keyword || is_element => {
+                (format!("{} (from {})", name, binding.specifier), binding.path.clone(), UsageType::Type)
+            }
+            _ if self.is_punct(i + 1, '(') => {
+                (format!("{}() (from {})", name, binding.specifier), binding.path.clone(), UsageType::Function)
+            }
+            _ => (format!("{} (from {})", name, binding.specifier), binding.path.clone(), UsageType::Other),
+        };
+        
+        self.record_usage(&binding.dependencies, i, imported_item, path, usage_type);
+    }
+}
+
+impl Lexer {
+    /// Split source into tokens
+    fn lex(content: &str) -> Vec<Token> {
+        let mut lexer = Lexer {
+            reader: SourceReader::new(content),
+        };
+        
+        // A hashbang line is not JavaScript
+        if content.starts_with("#!") {
+            lexer.reader.skip_line();
+        }
+        lexer.lex_tokens(false);
+        
+        lexer.reader.tokens
+    }
+    
+    /// Tokenize until the end of input, or until the `}` closing a `${` substitution of a template literal
+    fn lex_tokens(&mut self, in_substitution: bool) {
+        let mut depth = 0usize;
+        
+        while let Some(c) = self.reader.peek(0) {
+            let (line, column) = (self.reader.line, self.reader.column);
+            
+            match c {
+                c if c.is_whitespace() => {
+                    self.reader.bump();
+                }
+                '/' if self.reader.peek(1) == Some('/') => self.reader.skip_line(),
+                '/' if self.reader.peek(1) == Some('*') => self.skip_block_comment(),
+                '/' if self.regex_allowed() => {
+                    self.skip_regex();
+                    self.reader.push(TokenKind::Value, line, column);
+                }
+                '\'' | '"' => {
+                    let value = self.string(c);
+                    self.reader.push(TokenKind::Str(value), line, column);
+                }
+                '`' => {
+                    self.reader.bump();
+                    self.template();
+                    self.reader.push(TokenKind::Value, line, column);
+                }
+                c if c.is_alphabetic() || c == '_' || c == '$' => {
+                    let ident = self.reader.read_while(|c| c.is_alphanumeric() || c == '_' || c == '$');
+                    self.reader.push(TokenKind::Ident(ident), line, column);
+                }
+                c if c.is_ascii_digit() => {
+                    self.reader.skip_number();
+                    self.reader.push(TokenKind::Value, line, column);
+                }
+                '}' if in_substitution && depth == 0 => {
+                    self.reader.bump();
+                    return;
+                }
+                c => {
+                    match c {
+                        '{' => depth += 1,
+                        '}' => depth = depth.saturating_sub(1),
+                        _ => {}
+                    }
+                    self.reader.bump();
+                    self.reader.push(TokenKind::Punct(c), line, column);
+                }
+            }
+        }
+    }
+    
+    /// Whether a `/` at this point starts a regular expression: after an operator, an opening
+    /// bracket or a keyword, but not after a value, a closing bracket or `<` of a closing JSX tag
+    fn regex_allowed(&self) -> bool {
+        match self.reader.tokens.last().map(|token| &token.kind) {
+            None | Some(TokenKind::Newline) => true,
+            Some(TokenKind::Punct(c)) => !matches!(c, ')' | ']' | '}' | '<'),
+            Some(TokenKind::Ident(word)) => KEYWORDS_BEFORE_EXPRESSION.contains(&word.as_str()),
+            Some(TokenKind::Str(_)) | Some(TokenKind::Value) => false,
+        }
+    }
+    
+    fn skip_block_comment(&mut self) {
+        self.reader.bump();
+        self.reader.bump();
+        while let Some(c) = self.reader.bump() {
+            if c == '*' && self.reader.peek(0) == Some('/') {
+                self.reader.bump();
+                return;
+            }
+        }
+    }
+    
+    /// Read a string literal; unterminated strings end at the end of the line
+    fn string(&mut self, quote: char) -> String {
+        let mut value = String::new();
+        self.reader.bump();
+        
+        while let Some(c) = self.reader.peek(0) {
+            match c {
+                '\n' => break,
+                '\\' => {
+                    self.reader.bump();
+                    value.extend(self.reader.bump());
+                }
+                c if c == quote => {
+                    self.reader.bump();
+                    break;
+                }
+                c => {
+                    value.push(c);
+                    self.reader.bump();
+                }
+            }
+        }
+        
+        value
+    }
+    
+    /// Skip the text of a template literal, tokenizing its `${...}` substitutions
+    fn template(&mut self) {
+        while let Some(c) = self.reader.bump() {
+            match c {
+                '\\' => {
+                    self.reader.bump();
+                }
+                '`' => return,
+                '$' if self.reader.peek(0) == Some('{') => {
+                    self.reader.bump();
+                    self.lex_tokens(true);
+                }
+                _ => {}
+            }
+        }
+    }
+    
+    /// Skip a regular expression literal and its flags
+    fn skip_regex(&mut self) {
+        let mut in_class = false;
+        self.reader.bump();
+        
+        while let Some(c) = self.reader.peek(0) {
+            if c == '\n' {
+                break;
+            }
+            self.reader.bump();
+            match c {
+                '\\' => {
+                    self.reader.bump();
+                }
+                '[' => in_class = true,
+                ']' => in_class = false,
+                '/' if !in_class => break,
+                _ => {}
+            }
+        }
+        
+        self.reader.read_while(|c| c.is_alphanumeric());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use crate::manifest::dependency::{DependencyExtras, DependencySource, DependencyType, NodeExtras};
+    
+    /// Kinds of the tokens of `source`, with identifiers and strings as text
+    fn token_texts(source: &str) -> Vec<String> {
+        Lexer::lex(source).into_iter()
+            .map(|token| match token.kind {
+                TokenKind::Ident(ident) => ident,
+                TokenKind::Str(value) => format!("{:?}", value),
+                TokenKind::Punct(c) => c.to_string(),
+                TokenKind::Newline => "\\n".to_string(),
+                TokenKind::Value => "<value>".to_string(),
+            })
+            .collect()
+    }
+    
+    /// Usages found in `file_name` as `(dependency, line, imported item, usage type)`, for
+    /// dependencies declared under `names`
+    fn usages(source: &str, file_name: &str, names: &[&str]) -> Vec<(String, usize, String, UsageType)> {
+        let dependencies: Vec<Dependency> = names.iter()
+            .map(|name| Dependency {
+                name: name.to_string(),
+                import_name: name.to_string(),
+                package: None,
+                version: None,
+                features: Vec::new(),
+                optional: false,
+                dependency_type: DependencyType::Normal,
+                source: DependencySource::default(),
+                extras: DependencyExtras::Node(NodeExtras::default()),
+            })
+            .collect();
+        let mut usage_data = DependencyUsageData::default();
+        for name in names {
+            usage_data.usage_locations.insert(name.to_string(), Vec::new());
+        }
+        
+        analyze_file(source, Path::new(file_name), TargetKind::Lib, &dependencies, &mut usage_data);
+        
+        let mut usages: Vec<_> = usage_data.usage_locations.into_iter()
+            .flat_map(|(dep_name, usages)| usages.into_iter()
+                .map(move |usage| (dep_name.clone(), usage.line, usage.imported_item, usage.usage_type)))
+            .collect();
+        usages.sort_by_key(|(dep_name, line, imported_item, _)| (*line, dep_name.clone(), imported_item.clone()));
+        usages
+    }
+    
+    fn usage(dep_name: &str, line: usize, imported_item: &str, usage_type: UsageType) -> (String, usize, String, UsageType) {
+        (dep_name.to_string(), line, imported_item.to_string(), usage_type)
+    }
+    
+    #[test]
+    fn tells_regular_expressions_from_division() {
+        assert_eq!(token_texts("a = b / c / d"), vec!["a", "=", "b", "/", "c", "/", "d"]);
+        assert_eq!(token_texts("x = /'[/]'/g.test(y)"), vec!["x", "=", "<value>", ".", "test", "(", "y", ")"]);
+        assert_eq!(token_texts("f(a) / 2 / g"), vec!["f", "(", "a", ")", "/", "<value>", "/", "g"]);
+        assert_eq!(token_texts("return /\"/.source"), vec!["return", "<value>", ".", "source"]);
+        assert_eq!(token_texts("</a>"), vec!["<", "/", "a", ">"]);
+    }
+    
+    #[test]
+    fn skips_comments_and_tokenizes_template_substitutions() {
+        let source = "#!/usr/bin/env node\n// require('a')\n/* import 'b' */\n`${require('c')} ${ {x: 1}.x }`";
+        
+        // The substitutions come before the template, which is pushed once it ends
+        assert_eq!(token_texts(source), vec!["require", "(", "\"c\"", ")", "{", "x", ":", "<value>", "}", ".", "x", "<value>"]);
+    }
+    
+    #[test]
+    fn names_packages_of_specifiers() {
+        assert_eq!(package_name("lodash"), Some("lodash"));
+        assert_eq!(package_name("lodash/fp"), Some("lodash"));
+        assert_eq!(package_name("@scope/pkg"), Some("@scope/pkg"));
+        assert_eq!(package_name("@scope/pkg/sub/path"), Some("@scope/pkg"));
+        assert_eq!(package_name("@scope"), None);
+        assert_eq!(package_name("@scope/"), None);
+        assert_eq!(package_name("./local"), None);
+        assert_eq!(package_name("/abs/path"), None);
+        assert_eq!(package_name("#internal"), None);
+        assert_eq!(package_name("node:fs"), None);
+        assert_eq!(package_name("https://esm.sh/react"), None);
+    }
+    
+    #[test]
+    fn names_types_packages() {
+        assert_eq!(types_package("lodash/fp").as_deref(), Some("@types/lodash"));
+        assert_eq!(types_package("@scope/pkg").as_deref(), Some("@types/scope__pkg"));
+        assert_eq!(types_package("fs/promises").as_deref(), Some("@types/node"));
+        assert_eq!(types_package("node:path").as_deref(), Some("@types/node"));
+        assert_eq!(types_package("@types/react"), None);
+    }
+    
+    #[test]
+    fn follows_imports_requires_and_bindings() {
+        let source = "import React, { useState as useS } from 'react';\nconst { join } = require('lodash/fp');\nconst el = <React.Fragment />;\nuseS(0);\njoin(a, b);\nconst obj = { useS: 1 };\n";
+        
+        assert_eq!(usages(source, "app.jsx", &["react", "lodash"]), vec![
+            usage("react", 1, "React (default from react)", UsageType::Import),
+            usage("react", 1, "useState (from react)", UsageType::Import),
+            usage("lodash", 2, "require(lodash/fp)", UsageType::Import),
+            usage("react", 3, "React.Fragment (from react)", UsageType::Type),
+            usage("react", 4, "useS() (from react)", UsageType::Function),
+            usage("lodash", 5, "join() (from lodash/fp)", UsageType::Function),
+        ]);
+    }
+    
+    #[test]
+    fn attributes_typescript_imports_to_types_packages() {
+        let source = "import type { Request } from 'express';\nexport { Router } from 'express';\n";
+        
+        assert_eq!(usages(source, "app.ts", &["express", "@types/express"]), vec![
+            usage("@types/express", 1, "Request (from express)", UsageType::Type),
+            usage("express", 1, "Request (from express)", UsageType::Type),
+            usage("@types/express", 2, "Router (re-exported from express)", UsageType::Import),
+            usage("express", 2, "Router (re-exported from express)", UsageType::Import),
+        ]);
+    }
+    
+    #[test]
+    fn classifies_files_by_path() {
+        assert_eq!(classify(Path::new("src/__tests__/app.js")), TargetKind::Test);
+        assert_eq!(classify(Path::new("src/app.spec.ts")), TargetKind::Test);
+        assert_eq!(classify(Path::new("src/app.bench.js")), TargetKind::Bench);
+        assert_eq!(classify(Path::new("benchmarks/run.js")), TargetKind::Bench);
+        assert_eq!(classify(Path::new("examples/demo.js")), TargetKind::Example);
+        assert_eq!(classify(Path::new("src/testing.js")), TargetKind::Lib);
+    }
+}
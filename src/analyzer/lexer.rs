@@ -0,0 +1,130 @@
+/// A lexical token of JavaScript, TypeScript or Python source
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKind {
+    Ident(String),
+    /// A string literal, with its prefix and quotes removed
+    Str(String),
+    Punct(char),
+    /// The end of a logical line, in languages where it ends a statement
+    Newline,
+    /// Numbers, and the literals whose text is skipped: template literals and regular expressions
+    Value,
+}
+
+#[derive(Debug, Clone)]
+pub struct Token {
+    pub kind: TokenKind,
+    /// Line and column where the token starts (1-based)
+    pub line: usize,
+    pub column: usize,
+    /// Line and column just past the end of the token (1-based)
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+/// Reads source character by character, keeping track of the position, and collects the
+/// tokens a lexer finds in it
+pub struct SourceReader {
+    chars: Vec<char>,
+    pos: usize,
+    /// Line and column of the next character (1-based)
+    pub line: usize,
+    pub column: usize,
+    pub tokens: Vec<Token>,
+}
+
+impl SourceReader {
+    pub fn new(content: &str) -> Self {
+        Self {
+            chars: content.chars().collect(),
+            pos: 0,
+            line: 1,
+            column: 1,
+            tokens: Vec::new(),
+        }
+    }
+    
+    pub fn peek(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+    
+    pub fn bump(&mut self) -> Option<char> {
+        let c = self.chars.get(self.pos).copied()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+    
+    /// Add a token starting at `line` and `column` and ending at the current position
+    pub fn push(&mut self, kind: TokenKind, line: usize, column: usize) {
+        self.tokens.push(Token {
+            kind,
+            line,
+            column,
+            end_line: self.line,
+            end_column: self.column,
+        });
+    }
+    
+    /// Read the characters matching `accept`, up to the first one that does not
+    pub fn read_while(&mut self, accept: impl Fn(char) -> bool) -> String {
+        let mut text = String::new();
+        while let Some(c) = self.peek(0).filter(|c| accept(*c)) {
+            text.push(c);
+            self.bump();
+        }
+        text
+    }
+    
+    /// Skip a number: digits, letters of hexadecimal digits and suffixes, dots and separators
+    pub fn skip_number(&mut self) {
+        self.read_while(|c| c.is_alphanumeric() || c == '.' || c == '_');
+    }
+    
+    /// Skip to the end of the line, leaving the line break
+    pub fn skip_line(&mut self) {
+        self.read_while(|c| c != '\n');
+    }
+}
+
+/// Lookups of the tokens of a file by index, shared by the import collectors
+pub trait TokenLookup<'a> {
+    fn tokens(&self) -> &'a [Token];
+    
+    fn kind(&self, i: usize) -> Option<&'a TokenKind> {
+        self.tokens().get(i).map(|token| &token.kind)
+    }
+    
+    fn is_ident(&self, i: usize, word: &str) -> bool {
+        matches!(self.kind(i), Some(TokenKind::Ident(ident)) if ident == word)
+    }
+    
+    fn is_punct(&self, i: usize, c: char) -> bool {
+        self.kind(i) == Some(&TokenKind::Punct(c))
+    }
+    
+    fn ident(&self, i: usize) -> Option<&'a str> {
+        match self.kind(i) {
+            Some(TokenKind::Ident(ident)) => Some(ident),
+            _ => None,
+        }
+    }
+    
+    fn string(&self, i: usize) -> Option<&'a str> {
+        match self.kind(i) {
+            Some(TokenKind::Str(value)) => Some(value),
+            _ => None,
+        }
+    }
+    
+    /// Whether the token is accessed as a property or attribute (`obj.import`), so it is not
+    /// a keyword or binding
+    fn is_property(&self, i: usize) -> bool {
+        i > 0 && self.is_punct(i - 1, '.')
+    }
+}
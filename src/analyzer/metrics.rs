@@ -1,13 +1,17 @@
 use std::collections::{HashMap, HashSet};
 use anyhow::Result;
 
-use crate::manifest::cargo::CargoDependency;
-use crate::analyzer::{DependencyUsageData, DependencyMetrics, UsageType};
+use crate::manifest::cargo::TargetKind;
+use crate::manifest::dependency::{Dependency, DependencyType, Ecosystem};
+use crate::manifest::features::FeatureGraph;
+use crate::analyzer::{DependencyUsage, DependencyUsageData, DependencyMetrics, ItemUsage, Misplacement, OptionalIssue, UsageType};
 
-/// Calculate metrics for dependencies based on usage data
+/// Calculate metrics for dependencies based on usage data; used dependencies scoring
+/// below `removal_threshold` are reported as removable
 pub fn calculate_metrics(
-    dependencies: &[CargoDependency],
+    dependencies: &[Dependency],
     usage_data: &DependencyUsageData,
+    removal_threshold: f64,
 ) -> Result<DependencyMetrics> {
     let mut metrics = DependencyMetrics::default();
     
@@ -16,6 +20,7 @@ pub fn calculate_metrics(
         let empty_vec = Vec::new();
         let usages = usage_data.usage_locations.get(&dep.name).unwrap_or(&empty_vec);
         let is_used = !usages.is_empty();
+        let is_public = usages.iter().any(|usage| usage.exposure.is_some());
         
         // Count unique files where the dependency is used
         let unique_files: HashSet<_> = usages.iter().map(|usage| &usage.file).collect();
@@ -35,14 +40,23 @@ pub fn calculate_metrics(
         
         // Store metrics
         metrics.is_used.insert(dep.name.clone(), is_used);
+        metrics.is_public.insert(dep.name.clone(), is_public);
         metrics.usage_count.insert(dep.name.clone(), usage_count);
         metrics.importance_scores.insert(dep.name.clone(), importance_score);
         metrics.usage_types.insert(dep.name.clone(), usage_types);
         metrics.feature_usage.insert(dep.name.clone(), feature_usage);
         metrics.is_partially_used.insert(dep.name.clone(), is_partially_used);
+        
+        // A dependency declared in several tables is placed deliberately
+        let declarations = dependencies.iter().filter(|other| other.name == dep.name).count();
+        if declarations == 1 {
+            if let Some(misplacement) = find_misplacement(dep, usages) {
+                metrics.misplaced.insert(dep.name.clone(), misplacement);
+            }
+        }
     }
     
-    metrics.removable_dependencies = find_removable_dependencies(&metrics);
+    metrics.removable_dependencies = find_removable_dependencies(&metrics, removal_threshold);
     
     Ok(metrics)
 }
@@ -58,9 +72,134 @@ fn count_usage_types(usages: &[crate::analyzer::DependencyUsage]) -> HashMap<Usa
     counts
 }
 
+/// Compare the table a dependency is declared in with the targets using it: normal dependencies
+/// only used by tests or the build script, dev-dependencies used by library, binary or build
+/// script code, and build-dependencies the build script does not use
+fn find_misplacement(
+    dep: &Dependency,
+    usages: &[crate::analyzer::DependencyUsage],
+) -> Option<Misplacement> {
+    // Bundlers let npm packages be imported from anywhere, whatever field declares them
+    if usages.is_empty() || dep.ecosystem() != Ecosystem::Rust {
+        return None;
+    }
+    
+    let used_by = |kinds: &[TargetKind]| usages.iter().any(|usage| kinds.contains(&usage.target));
+    let in_code = used_by(&[TargetKind::Lib, TargetKind::Bin]);
+    let in_tests = used_by(&[TargetKind::Test, TargetKind::Bench, TargetKind::Example]);
+    let in_build_script = used_by(&[TargetKind::Build]);
+    
+    let suggested = match dep.dependency_type {
+        // Dependencies needed by both tests and the build script have to be declared twice
+        DependencyType::Normal if in_code || (in_tests && in_build_script) => return None,
+        DependencyType::Normal if in_build_script => DependencyType::Build,
+        DependencyType::Normal => DependencyType::Development,
+        DependencyType::Development if in_code => DependencyType::Normal,
+        DependencyType::Development if in_build_script => DependencyType::Build,
+        DependencyType::Development => return None,
+        DependencyType::Build if in_build_script => return None,
+        DependencyType::Build if in_code => DependencyType::Normal,
+        DependencyType::Build => DependencyType::Development,
+        // Peer dependencies are provided by the dependent project, wherever they are used
+        DependencyType::Peer => return None,
+    };
+    
+    Some(Misplacement {
+        declared: dep.dependency_type.clone(),
+        suggested,
+    })
+}
+
+/// Group the usages of a dependency by the item they name, sorted by path. An item takes the
+/// kind of its first usage that is not an import.
+pub fn api_surface(usages: &[DependencyUsage]) -> Vec<ItemUsage> {
+    let mut items: Vec<ItemUsage> = Vec::new();
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    
+    for usage in usages {
+        let path = match &usage.item {
+            Some(path) => path,
+            None => continue,
+        };
+        
+        let item = match index.get(path.as_str()) {
+            Some(&i) => &mut items[i],
+            None => {
+                index.insert(path, items.len());
+                items.push(ItemUsage {
+                    path: path.clone(),
+                    kind: UsageType::Import,
+                    count: 0,
+                    files: Vec::new(),
+                });
+                items.last_mut().unwrap()
+            }
+        };
+        
+        item.count += 1;
+        if item.kind == UsageType::Import {
+            item.kind = usage.usage_type.clone();
+        }
+        if !item.files.contains(&usage.file) {
+            item.files.push(usage.file.clone());
+        }
+    }
+    
+    items.sort_by(|a, b| a.path.cmp(&b.path));
+    items
+}
+
+/// Check how the package's features enable its optional dependencies: optional dependencies
+/// no feature enables, and usages not guarded by a cfg for any feature enabling the dependency.
+/// All usages are checked, including those inactive for the selected features and target cfgs.
+pub fn find_optional_issues(
+    features: &FeatureGraph,
+    usage_data: &DependencyUsageData,
+) -> HashMap<String, OptionalIssue> {
+    let mut issues = HashMap::new();
+    
+    for dep_name in features.optional_dependencies() {
+        let enabled_by = features.features_enabling(dep_name);
+        if enabled_by.is_empty() {
+            issues.insert(dep_name.to_string(), OptionalIssue::NotEnabled);
+            continue;
+        }
+        
+        let locations: Vec<String> = usage_data.usage_locations.get(dep_name)
+            .into_iter()
+            .flatten()
+            .filter(|usage| !usage.cfg.as_ref().is_some_and(|cfg| cfg.requires_any_feature(&enabled_by)))
+            .map(|usage| format!("{}:{}", usage.file.display(), usage.line))
+            .collect();
+        
+        if !locations.is_empty() {
+            issues.insert(dep_name.to_string(), OptionalIssue::Ungated { enabled_by, locations });
+        }
+    }
+    
+    issues
+}
+
+/// Find the declared features that enable nothing the code uses: no cfg predicate names
+/// them or a feature they enable, and no dependency they turn on or configure is used.
+/// `default` only groups other features and is not reported.
+pub fn find_unused_package_features(features: &FeatureGraph, usage_data: &DependencyUsageData) -> Vec<String> {
+    let is_used = |dep_name: &String| usage_data.usage_locations.get(dep_name).is_some_and(|usages| !usages.is_empty());
+    
+    features.features()
+        .filter(|feature| *feature != "default")
+        .filter(|feature| {
+            let enabled = features.enabled_features(&[feature]);
+            let referenced = enabled.iter().any(|name| usage_data.cfg_features.contains(name));
+            !referenced && !features.configured_dependencies(&enabled).iter().any(is_used)
+        })
+        .map(str::to_string)
+        .collect()
+}
+
 /// Calculate which features of a dependency are used
 fn calculate_feature_usage(
-    dep: &CargoDependency, 
+    dep: &Dependency, 
     usages: &[crate::analyzer::DependencyUsage]
 ) -> HashMap<String, bool> {
     let mut feature_usage = HashMap::new();
@@ -98,7 +237,7 @@ fn calculate_feature_usage(
 
 /// Determine if a dependency is partially used (not all features are used)
 fn determine_if_partially_used(
-    dep: &CargoDependency,
+    dep: &Dependency,
     feature_usage: &HashMap<String, bool>
 ) -> bool {
     // If there are no features, it's not partially used
@@ -112,14 +251,14 @@ fn determine_if_partially_used(
 
 /// Calculate an enhanced importance score for a dependency
 fn calculate_importance_score(
-    dep: &CargoDependency,
+    dep: &Dependency,
     usages: &[crate::analyzer::DependencyUsage],
     usage_count: usize,
     usage_types: &HashMap<UsageType, usize>,
 ) -> f64 {
     // Factors that influence importance:
     // 1. Number of files using the dependency (coverage)
-    // 2. Variety of usage types (function calls, types, macros)
+    // 2. Variety of usage types (function calls, types, macros, derives, attributes)
     // 3. Whether it's a dev or normal dependency
     // 4. Whether it's optional
     // 5. Frequency and diversity of usage
@@ -140,9 +279,10 @@ fn calculate_importance_score(
     
     // Dependency type factors
     let type_factor = match dep.dependency_type {
-        crate::manifest::cargo::DependencyType::Normal => 1.0,
-        crate::manifest::cargo::DependencyType::Development => 0.5,
-        crate::manifest::cargo::DependencyType::Build => 0.7,
+        crate::manifest::dependency::DependencyType::Normal => 1.0,
+        crate::manifest::dependency::DependencyType::Development => 0.5,
+        crate::manifest::dependency::DependencyType::Build => 0.7,
+        crate::manifest::dependency::DependencyType::Peer => 0.8,
     };
     
     // Optional dependencies are less important
@@ -187,11 +327,24 @@ fn calculate_usage_depth(usage_types: &HashMap<UsageType, usize>) -> f64 {
         depth += 0.2 * (*macro_count as f64).min(10.0) / 10.0;
     }
     
+    // Derives generate code for the deriving type, a structural dependency like type usage
+    let derive_count = usage_types.get(&UsageType::Derive).unwrap_or(&0);
+    if *derive_count > 0 {
+        depth += 0.3 * (*derive_count as f64).min(10.0) / 10.0;
+    }
+    
+    // Attribute macros and derive helpers shape the annotated items
+    let attribute_count = usage_types.get(&UsageType::Attribute).unwrap_or(&0);
+    if *attribute_count > 0 {
+        depth += 0.2 * (*attribute_count as f64).min(10.0) / 10.0;
+    }
+    
     depth.min(1.0)
 }
 
-/// Find dependencies that can potentially be removed
-pub fn find_removable_dependencies(metrics: &DependencyMetrics) -> Vec<String> {
+/// Find dependencies that can potentially be removed, treating used dependencies with an
+/// importance score below `threshold` as removable
+pub fn find_removable_dependencies(metrics: &DependencyMetrics, threshold: f64) -> Vec<String> {
     let mut removable = Vec::new();
     
     for (dep_name, is_used) in &metrics.is_used {
@@ -202,13 +355,13 @@ pub fn find_removable_dependencies(metrics: &DependencyMetrics) -> Vec<String> {
             // For used dependencies, check if they're minimally used
             let score = metrics.importance_scores.get(dep_name).unwrap_or(&1.0);
             
-            if *score < 0.1 {
+            if *score < threshold {
                 // Very low importance score suggests it might be removable
                 removable.push(dep_name.clone());
             } else if let Some(true) = metrics.is_partially_used.get(dep_name) {
                 // If it's partially used and the importance score is still low,
                 // suggest it as potentially removable, but with lower confidence
-                if *score < 0.3 {
+                if *score < threshold.max(0.3) {
                     removable.push(dep_name.clone());
                 }
             }
@@ -216,4 +369,81 @@ pub fn find_removable_dependencies(metrics: &DependencyMetrics) -> Vec<String> {
     }
     
     removable
-} 
\ No newline at end of file
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::fs;
+    use crate::analyzer::{AnalysisResult, DependencyAnalyzer};
+    use crate::utils::config::Config;
+    use tempfile::TempDir;
+    
+    /// Analyze a package with the given manifest and library source
+    fn analyze(manifest: &str, lib: &str) -> (AnalysisResult, TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("Cargo.toml"), manifest).unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/lib.rs"), lib).unwrap();
+        
+        let mut config = Config::default();
+        config.analysis.use_cache = false;
+        (DependencyAnalyzer::new(dir.path()).with_config(config).analyze().unwrap(), dir)
+    }
+    
+    /// Optional issues of a package analyzed in `dir`, sorted by dependency, with locations
+    /// relative to `dir`
+    fn optional_issues((result, dir): &(AnalysisResult, TempDir)) -> Vec<(String, OptionalIssue)> {
+        let prefix = format!("{}/", dir.path().display());
+        let mut issues: Vec<(String, OptionalIssue)> = result.metrics.optional_issues.clone().into_iter()
+            .map(|(dep_name, issue)| match issue {
+                OptionalIssue::Ungated { enabled_by, locations } => (dep_name, OptionalIssue::Ungated {
+                    enabled_by,
+                    locations: locations.iter().map(|location| location.replace(&prefix, "")).collect(),
+                }),
+                issue => (dep_name, issue),
+            })
+            .collect();
+        issues.sort_by(|a, b| a.0.cmp(&b.0));
+        issues
+    }
+    
+    #[test]
+    fn reports_optional_dependencies_used_outside_their_features() {
+        let manifest = "[package]\nname = \"demo\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\nserde = { version = \"1\", optional = true }\nserde_json = { version = \"1\", optional = true }\nrand = { version = \"0.8\", optional = true }\nchrono = { version = \"0.4\", optional = true }\n\n[features]\njson = [\"dep:serde_json\"]\nrandom = [\"rand/std\"]\ndates = [\"chrono?/serde\"]\n";
+        let lib = "#[cfg(feature = \"serde\")]\nuse serde::Serialize;\n\n#[cfg(feature = \"random\")]\npub fn roll() -> u32 { rand::random() }\n\npub fn encode() -> String { serde_json::to_string(&1).unwrap() }\n\npub fn now() -> chrono::DateTime<chrono::Utc> { chrono::Utc::now() }\n";
+        
+        // `chrono?/serde` does not turn chrono on, so only its implicit feature does
+        assert_eq!(optional_issues(&analyze(manifest, lib)), vec![
+            ("chrono".to_string(), OptionalIssue::Ungated {
+                enabled_by: vec!["chrono".to_string()],
+                locations: vec!["src/lib.rs:9".to_string(), "src/lib.rs:9".to_string(), "src/lib.rs:9".to_string()],
+            }),
+            ("serde_json".to_string(), OptionalIssue::Ungated {
+                enabled_by: vec!["json".to_string()],
+                locations: vec!["src/lib.rs:7".to_string()],
+            }),
+        ]);
+    }
+    
+    #[test]
+    fn reports_optional_dependencies_no_feature_enables() {
+        let manifest = "[package]\nname = \"demo\"\nversion = \"0.1.0\"\nedition = \"2024\"\n\n[dependencies]\nrand = { version = \"0.8\", optional = true }\nserde = { version = \"1\", optional = true }\n\n[features]\nstd = [\"rand?/std\"]\nserde = [\"dep:serde\"]\n";
+        let lib = "#[cfg(feature = \"std\")]\npub fn roll() -> u32 { rand::random() }\n";
+        
+        // Edition 2024 has no implicit features, and weak entries do not turn dependencies on
+        assert_eq!(optional_issues(&analyze(manifest, lib)), vec![
+            ("rand".to_string(), OptionalIssue::NotEnabled),
+        ]);
+    }
+    
+    #[test]
+    fn reports_features_enabling_nothing_the_code_uses() {
+        let manifest = "[package]\nname = \"demo\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\nserde_json = { version = \"1\", optional = true }\nchrono = { version = \"0.4\", optional = true }\nrustls = { version = \"0.23\", optional = true }\n\n[features]\ndefault = [\"json\"]\njson = [\"dep:serde_json\"]\ndates = [\"chrono?/serde\"]\ntls = [\"rustls/tls12\"]\nfast = []\nfull = [\"fast\", \"tls\"]\nextra = []\n";
+        let lib = "#[cfg(feature = \"fast\")]\npub const FAST: bool = true;\n\npub fn encode() -> String { serde_json::to_string(&1).unwrap() }\n\npub fn now() -> chrono::DateTime<chrono::Utc> { chrono::Utc::now() }\n";
+        
+        // `dates` only configures chrono, which is used; `full` enables `fast`, which a cfg names
+        assert_eq!(analyze(manifest, lib).0.metrics.unused_package_features, vec!["extra", "tls"]);
+    }
+}
@@ -1,38 +1,148 @@
 pub mod rust_analyzer;
+pub mod js_analyzer;
+pub mod py_analyzer;
 pub mod metrics;
 pub mod dependency_graph;
+pub mod explain;
+pub mod check;
+pub mod fix;
+pub mod cache;
+pub mod module_graph;
+pub mod cfg;
+pub mod imports;
+pub mod lexer;
+pub mod source;
 
+use std::collections::HashSet;
+use std::fmt;
 use std::path::{Path, PathBuf};
 use anyhow::Result;
-use crate::manifest::cargo::CargoDependency;
-use serde::Serialize;
+use crate::manifest::{self, ManifestParserType};
+use crate::manifest::cargo::{CargoParser, CargoWorkspace, TargetKind};
+use crate::manifest::dependency::{Dependency, DependencySource, DependencyType, Ecosystem};
+use crate::manifest::editor::dependency_table_name;
+use crate::manifest::features::FeatureGraph;
+use crate::utils::config::Config;
+use crate::utils::fs::FileWalker;
+use serde::{Deserialize, Serialize};
 
 /// Main analyzer that orchestrates the analysis process
 pub struct DependencyAnalyzer {
     project_path: PathBuf,
+    config: Config,
 }
 
 // Structure to represent an analyzed dependency with all relevant metrics
 #[derive(Debug, Clone, Serialize)]
 pub struct AnalyzedDependency {
     pub name: String,
+    pub ecosystem: Ecosystem,
     pub version: String,
+    /// Actual package name when the dependency is renamed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub package: Option<String>,
+    pub source: DependencySource,
+    /// Whether default features are enabled, for ecosystems that have them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_features: Option<bool>,
+    /// Platforms the dependency is limited to by `[target.<platform>.*]` tables; empty when
+    /// it is declared for every platform
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub platforms: Vec<String>,
     pub usage_count: usize,
     pub importance_score: f64,
     pub removable: bool,
     pub used_features: Vec<String>,
     pub unused_features: Vec<String>,
+    /// Set when the dependency is declared in a table that does not match the targets using it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub misplaced: Option<Misplacement>,
+    /// Set for optional dependencies that no feature enables, or that are used without a cfg guard
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub optional_issue: Option<OptionalIssue>,
+    /// Whether the dependency appears in the public API, so upgrading it is a breaking change
+    pub public: bool,
+    /// Where the dependency appears in the public API
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exposures: Vec<ExposureLocation>,
+    /// Distinct items of the dependency the code uses
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub api_surface: Vec<ItemUsage>,
+    pub usages: Vec<DependencyUsage>,
+}
+
+/// A distinct item of a dependency used by the code, aggregated over its usages
+#[derive(Debug, Clone, Serialize)]
+pub struct ItemUsage {
+    /// Fully qualified path, e.g. `regex::Regex::new`
+    pub path: String,
+    /// How the item is used; `Import` only for items that are imported and never used otherwise
+    pub kind: UsageType,
+    /// Number of usages, imports included
+    pub count: usize,
+    /// Files using the item, in analysis order
+    pub files: Vec<PathBuf>,
+}
+
+/// A place where a dependency appears in the public API
+#[derive(Debug, Clone, Serialize)]
+pub struct ExposureLocation {
+    /// `file:line:column` of the usage
+    pub location: String,
+    pub kind: Exposure,
+    pub item: String,
 }
 
 /// Analysis result that will be returned to the main function and can be exported
 #[derive(Debug, Serialize)]
 pub struct Analysis {
+    /// Name of the analyzed package, or of the workspace or repository root directory
+    pub name: String,
+    /// Ecosystem of the manifest; not set for a repository with projects of several ecosystems
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ecosystem: Option<Ecosystem>,
+    /// Manifest the analysis was started from, or the root directory of a repository with several projects
+    pub manifest_path: PathBuf,
     pub dependencies: Vec<AnalyzedDependency>,
+    /// Source files not reachable from any target root through `mod` declarations, which were not analyzed
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub orphan_files: Vec<PathBuf>,
+    /// Features declared in `[features]` that enable nothing the code uses
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unused_package_features: Vec<String>,
+    /// Per-member analyses when the project is a Cargo workspace
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<Analysis>,
+    /// Per-project analyses when the repository holds several projects, by ecosystem and manifest path
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub projects: Vec<Analysis>,
 }
 
 impl Analysis {
+    /// Build the exportable representation of an analysis result
+    pub fn from_result(result: &AnalysisResult) -> Self {
+        let dependencies = result.dependencies.iter()
+            .map(|dep| summarize_dependency(result, dep))
+            .collect();
+        
+        Self {
+            name: result.name.clone(),
+            ecosystem: result.ecosystem,
+            manifest_path: result.manifest_path.clone(),
+            dependencies,
+            orphan_files: result.usage_data.orphan_files.clone(),
+            unused_package_features: result.metrics.unused_package_features.clone(),
+            members: result.members.iter().map(Self::from_result).collect(),
+            projects: result.projects.iter().map(Self::from_result).collect(),
+        }
+    }
+    
     pub fn filter_dependency(&mut self, dep_name: &str) {
         self.dependencies.retain(|dep| dep.name == dep_name);
+        
+        for member in self.members.iter_mut().chain(&mut self.projects) {
+            member.filter_dependency(dep_name);
+        }
     }
 }
 
@@ -41,80 +151,506 @@ impl DependencyAnalyzer {
     pub fn new<P: AsRef<Path>>(project_path: P) -> Self {
         Self {
             project_path: project_path.as_ref().to_path_buf(),
+            config: Config::default(),
         }
     }
     
-    /// Analyze a project to find dependency usage
+    /// Use the given configuration (exclude patterns, search depth, ...) instead of the defaults
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
+    }
+    
+    /// Analyze a project to find dependency usage. Every project found in the directory is
+    /// analyzed with the analyzer of its ecosystem; when there are several, they are returned
+    /// as the projects of a repository-wide result.
     pub fn analyze(&self) -> Result<AnalysisResult> {
-        // Find manifest file
-        let manifest_path = self.find_manifest_file()?;
+        let projects = self.find_projects()?;
+        let mut results = Vec::new();
         
+        for project in &projects {
+            // Files of projects of the same ecosystem nested in this one belong to those projects
+            let nested_projects: Vec<PathBuf> = projects.iter()
+                .filter(|other| other.ecosystem == project.ecosystem
+                    && other.dir() != project.dir()
+                    && other.dir().starts_with(project.dir()))
+                .map(|other| other.dir().to_path_buf())
+                .collect();
+            
+            // Workspaces are analyzed member by member against each member's own manifest
+            let result = match &project.workspace {
+                Some(workspace) if !workspace.members.is_empty() => {
+                    self.analyze_workspace(workspace, &project.manifest_path, &nested_projects)?
+                }
+                _ => {
+                    let enclosing = project.enclosing_workspace()?;
+                    let workspace = project.workspace.as_ref().or(enclosing.as_ref());
+                    self.analyze_package(project.dir(), &project.manifest_path, workspace, &nested_projects)?
+                }
+            };
+            results.push(result);
+        }
+        
+        if results.len() == 1 {
+            return Ok(results.remove(0));
+        }
+        
+        // Group the projects of a mixed repository by ecosystem, then by manifest path
+        results.sort_by(|a, b| {
+            a.ecosystem.cmp(&b.ecosystem)
+                .then_with(|| a.manifest_path.cmp(&b.manifest_path))
+        });
+        
+        Ok(AnalysisResult {
+            name: directory_name(&self.project_path),
+            ecosystem: None,
+            manifest_path: self.project_path.clone(),
+            dependencies: Vec::new(),
+            usage_data: DependencyUsageData::default(),
+            metrics: DependencyMetrics::default(),
+            dependency_graph: dependency_graph::DependencyGraph::new(&[]),
+            features: FeatureGraph::default(),
+            members: Vec::new(),
+            projects: results,
+        })
+    }
+    
+    /// Analyze a single package of `workspace`, if it belongs to one, skipping any directories
+    /// that belong to other packages
+    fn analyze_package(
+        &self,
+        package_path: &Path,
+        manifest_path: &Path,
+        workspace: Option<&CargoWorkspace>,
+        excluded_dirs: &[PathBuf],
+    ) -> Result<AnalysisResult> {
         // Parse manifest file
-        let dependencies = self.parse_manifest(&manifest_path)?;
+        let mut dependencies = self.parse_manifest(manifest_path, workspace)?;
+        let ecosystem = manifest::get_parser_type(manifest_path)?.ecosystem();
+        
+        let name = match manifest::read_package_name(manifest_path)? {
+            Some(name) => name,
+            None => directory_name(package_path),
+        };
         
         // Analyze code
-        let usage_data = self.analyze_code(&dependencies)?;
+        let mut usage_data = self.analyze_code(ecosystem, &name, package_path, manifest_path, &dependencies, excluded_dirs)?;
+        
+        // Usages in code compiled out for the selected features and target cfgs are not counted;
+        // only Rust has features and cfgs, and libraries named differently from their package
+        let features = match ecosystem {
+            Ecosystem::Rust => {
+                rust_analyzer::resolve_lib_names(&mut dependencies, &mut usage_data);
+                let features = FeatureGraph::load(manifest_path, &dependencies, workspace)?;
+                let selection = cfg::CfgSelection::for_package(&self.config.analysis, &features)?;
+                usage_data.mark_inactive(&selection);
+                features
+            }
+            Ecosystem::Node | Ecosystem::Python => FeatureGraph::default(),
+        };
         
         // Calculate metrics
-        let metrics = self.calculate_metrics(&dependencies, &usage_data)?;
+        let mut metrics = self.calculate_metrics(&dependencies, &usage_data.active())?;
+        metrics.optional_issues = metrics::find_optional_issues(&features, &usage_data);
+        metrics.unused_package_features = metrics::find_unused_package_features(&features, &usage_data);
         
         // Generate dependency graph
-        let dependency_graph = self.generate_dependency_graph(&dependencies)?;
+        let dependency_graph = self.generate_dependency_graph(manifest_path, workspace, std::slice::from_ref(&name), &dependencies)?;
+        
+        Ok(AnalysisResult {
+            name,
+            ecosystem: Some(ecosystem),
+            manifest_path: manifest_path.to_path_buf(),
+            dependencies,
+            usage_data,
+            metrics,
+            dependency_graph,
+            features,
+            members: Vec::new(),
+            projects: Vec::new(),
+        })
+    }
+    
+    /// Analyze every member of a workspace and roll the results up into a workspace-wide view,
+    /// skipping the directories of other projects nested in the workspace
+    fn analyze_workspace(
+        &self,
+        workspace: &CargoWorkspace,
+        manifest_path: &Path,
+        excluded_dirs: &[PathBuf],
+    ) -> Result<AnalysisResult> {
+        let mut members = Vec::new();
+        
+        for member in &workspace.members {
+            // Files of nested members belong to those members, not to the enclosing one
+            let nested_members: Vec<PathBuf> = workspace.members.iter()
+                .filter(|other| other.path != member.path && other.path.starts_with(&member.path))
+                .map(|other| other.path.clone())
+                .chain(excluded_dirs.iter().cloned())
+                .collect();
+            
+            members.push(self.analyze_package(&member.path, &member.manifest_path, Some(workspace), &nested_members)?);
+        }
+        
+        // Merge member dependencies and usages into the workspace rollup
+        let mut dependencies: Vec<Dependency> = Vec::new();
+        let mut usage_data = DependencyUsageData::default();
+        
+        for member in &members {
+            for dep in &member.dependencies {
+                match dependencies.iter_mut().find(|existing| existing.name == dep.name) {
+                    Some(existing) => {
+                        // A dependency that is a normal dependency of any member is a normal dependency of the workspace
+                        if dep.dependency_type == DependencyType::Normal {
+                            existing.dependency_type = DependencyType::Normal;
+                            existing.optional = existing.optional && dep.optional;
+                        }
+                        
+                        for feature in &dep.features {
+                            if !existing.features.contains(feature) {
+                                existing.features.push(feature.clone());
+                            }
+                        }
+                    }
+                    None => dependencies.push(dep.clone()),
+                }
+            }
+            
+            for (dep_name, usages) in &member.usage_data.usage_locations {
+                usage_data.usage_locations.entry(dep_name.clone())
+                    .or_default()
+                    .extend(usages.iter().cloned());
+            }
+            usage_data.orphan_files.extend(member.usage_data.orphan_files.iter().cloned());
+        }
+        
+        let mut metrics = self.calculate_metrics(&dependencies, &usage_data.active())?;
+        // Whether a dependency is misplaced depends on the manifest of the member declaring it,
+        // and features belong to members; the workspace rollup has neither
+        metrics.misplaced.clear();
+        let member_names: Vec<String> = members.iter().map(|member| member.name.clone()).collect();
+        let dependency_graph = self.generate_dependency_graph(manifest_path, Some(workspace), &member_names, &dependencies)?;
         
         Ok(AnalysisResult {
+            name: directory_name(&workspace.root),
+            ecosystem: Some(Ecosystem::Rust),
+            manifest_path: manifest_path.to_path_buf(),
             dependencies,
             usage_data,
             metrics,
             dependency_graph,
+            features: FeatureGraph::default(),
+            members,
+            projects: Vec::new(),
         })
     }
     
-    fn find_manifest_file(&self) -> Result<PathBuf> {
-        let cargo_toml = self.project_path.join("Cargo.toml");
-        if cargo_toml.exists() {
-            return Ok(cargo_toml);
+    /// Resolve the packages and dependency graph of every Cargo project in the project directory
+    /// from manifests and Cargo.lock only, without analyzing any source code
+    pub fn resolve_graphs(&self) -> Result<Vec<ProjectGraph>> {
+        let projects: Vec<Project> = self.find_projects()?
+            .into_iter()
+            .filter(|project| project.ecosystem == Ecosystem::Rust)
+            .collect();
+        
+        if projects.is_empty() {
+            return Err(anyhow::anyhow!("No Cargo.toml found in {:?}; only Cargo.lock can be explained", self.project_path));
         }
         
-        Err(anyhow::anyhow!("No supported manifest file found in {:?}", self.project_path))
+        projects.into_iter()
+            .map(|project| self.resolve_graph(project))
+            .collect()
     }
     
-    fn parse_manifest(&self, manifest_path: &Path) -> Result<Vec<CargoDependency>> {
-        use crate::manifest;
+    /// Resolve the packages and dependency graph of a single Cargo project
+    fn resolve_graph(&self, project: Project) -> Result<ProjectGraph> {
+        let manifests: Vec<(String, PathBuf)> = match &project.workspace {
+            Some(workspace) if !workspace.members.is_empty() => workspace.members.iter()
+                .map(|member| (member.name.clone(), member.manifest_path.clone()))
+                .collect(),
+            _ => {
+                let name = match manifest::read_package_name(&project.manifest_path)? {
+                    Some(name) => name,
+                    None => directory_name(project.dir()),
+                };
+                vec![(name, project.manifest_path.clone())]
+            }
+        };
+        
+        let enclosing = project.enclosing_workspace()?;
+        let workspace = project.workspace.or(enclosing);
+        
+        let mut packages = Vec::new();
+        for (name, manifest_path) in manifests {
+            let dependencies = self.parse_manifest(&manifest_path, workspace.as_ref())?;
+            packages.push(ProjectPackage {
+                name,
+                manifest_path,
+                dependencies,
+            });
+        }
+        
+        let roots: Vec<String> = packages.iter().map(|package| package.name.clone()).collect();
+        let all_dependencies: Vec<Dependency> = packages.iter()
+            .flat_map(|package| package.dependencies.iter().cloned())
+            .collect();
+        let dependency_graph = self.generate_dependency_graph(&project.manifest_path, workspace.as_ref(), &roots, &all_dependencies)?;
+        
+        Ok(ProjectGraph {
+            packages,
+            dependency_graph,
+            workspace,
+        })
+    }
+    
+    /// Find the projects in the project directory: every supported manifest within it, respecting
+    /// the exclude patterns and `max_search_depth`, except the members of Cargo workspaces and
+    /// the packages other projects depend on by path
+    fn find_projects(&self) -> Result<Vec<Project>> {
+        let walker = FileWalker::new(&self.project_path, &self.config)?;
+        let mut manifests = walker.files_named(&self.project_path, manifest::is_manifest_file_name);
+        
+        // Outer manifests first, so workspaces claim their members before these are reached
+        manifests.sort_by_key(|path| {
+            let file_name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+            (path.components().count(), path.parent().map(Path::to_path_buf), manifest::manifest_precedence(&file_name))
+        });
+        
+        let mut workspace_members = HashSet::new();
+        // Directories of the path dependencies of the projects found so far, by ecosystem
+        let mut path_dependencies = HashSet::new();
+        let mut projects = Vec::new();
+        
+        for manifest_path in manifests {
+            if workspace_members.contains(&canonical_path(&manifest_path)) {
+                continue;
+            }
+            
+            // A directory holds one project per ecosystem; the manifests of a Python project
+            // are read together, through the one of highest precedence
+            let ecosystem = manifest::get_parser_type(&manifest_path)?.ecosystem();
+            let dir = manifest_path.parent();
+            if projects.iter().any(|project: &Project| project.ecosystem == ecosystem && Some(project.dir()) == dir) {
+                continue;
+            }
+            
+            // A package depended on by path, such as a proc-macro crate in a subdirectory, is
+            // part of the project depending on it
+            if dir.is_some_and(|dir| path_dependencies.contains(&(ecosystem, canonical_path(dir)))) {
+                continue;
+            }
+            
+            let workspace = self.load_workspace(&manifest_path)?;
+            if let Some(workspace) = &workspace {
+                workspace_members.extend(workspace.members.iter().map(|member| canonical_path(&member.manifest_path)));
+            }
+            
+            let package_manifests: Vec<&Path> = match &workspace {
+                Some(workspace) => workspace.members.iter().map(|member| member.manifest_path.as_path()).collect(),
+                None => vec![manifest_path.as_path()],
+            };
+            for package_manifest in package_manifests {
+                for dep in self.parse_manifest(package_manifest, workspace.as_ref())? {
+                    if let DependencySource::Path { path } = &dep.source {
+                        path_dependencies.insert((ecosystem, canonical_path(path)));
+                    }
+                }
+            }
+            
+            projects.push(Project {
+                ecosystem,
+                manifest_path,
+                workspace,
+            });
+        }
+        
+        if projects.is_empty() {
+            return Err(anyhow::anyhow!("No supported manifest file found in {:?}", self.project_path));
+        }
         
-        manifest::parse_dependencies(manifest_path)
+        Ok(projects)
+    }
+    
+    /// Load the Cargo workspace declared by a manifest; manifests of other ecosystems declare none
+    fn load_workspace(&self, manifest_path: &Path) -> Result<Option<CargoWorkspace>> {
+        match manifest::get_parser_type(manifest_path)? {
+            ManifestParserType::Cargo => CargoWorkspace::load(manifest_path),
+            ManifestParserType::NodeJs | ManifestParserType::Python => Ok(None),
+        }
     }
     
-    fn analyze_code(&self, dependencies: &[CargoDependency]) -> Result<DependencyUsageData> {
-        let analyzer = rust_analyzer::RustAnalyzer::new(&self.project_path);
+    /// Parse the dependencies of a manifest; Cargo manifests inherit from `workspace`
+    fn parse_manifest(&self, manifest_path: &Path, workspace: Option<&CargoWorkspace>) -> Result<Vec<Dependency>> {
+        match manifest::get_parser_type(manifest_path)? {
+            ManifestParserType::Cargo => CargoParser::parse_in_workspace(manifest_path, workspace),
+            ManifestParserType::NodeJs | ManifestParserType::Python => manifest::parse_dependencies(manifest_path),
+        }
+    }
+    
+    fn analyze_code(
+        &self,
+        ecosystem: Ecosystem,
+        package_name: &str,
+        package_path: &Path,
+        manifest_path: &Path,
+        dependencies: &[Dependency],
+        excluded_dirs: &[PathBuf],
+    ) -> Result<DependencyUsageData> {
+        // Exclude patterns are relative to the analyzed project, also for workspace members
+        let walker = FileWalker::new(&self.project_path, &self.config)?
+            .with_excluded_dirs(excluded_dirs.to_vec());
+        // The cache lives in the project's target directory, one file per package manifest
+        let cache_file = self.config.analysis.use_cache
+            .then(|| cache::cache_file(&self.project_path, ecosystem, package_name, manifest_path));
+        
+        match ecosystem {
+            Ecosystem::Rust => {}
+            Ecosystem::Node => {
+                let mut analyzer = js_analyzer::JsAnalyzer::new(package_path)?
+                    .with_walker(walker)
+                    .with_threads(self.config.analysis.threads);
+                if let Some(cache_file) = cache_file {
+                    analyzer = analyzer.with_cache(cache_file);
+                }
+                return analyzer.analyze(dependencies);
+            }
+            Ecosystem::Python => {
+                let mut analyzer = py_analyzer::PyAnalyzer::new(package_path)?
+                    .with_walker(walker)
+                    .with_threads(self.config.analysis.threads)
+                    .with_module_map(&self.config.analysis.python_modules);
+                if let Some(cache_file) = cache_file {
+                    analyzer = analyzer.with_cache(cache_file);
+                }
+                return analyzer.analyze(dependencies);
+            }
+        }
+        
+        let mut analyzer = rust_analyzer::RustAnalyzer::new(package_path)?
+            .with_walker(walker)
+            .with_threads(self.config.analysis.threads)
+            .with_follow_modules(self.config.analysis.follow_modules)
+            .with_trait_methods(&self.config.analysis.trait_methods);
+        if let Some(cache_file) = cache_file {
+            analyzer = analyzer.with_cache(cache_file);
+        }
+        
         analyzer.analyze(dependencies)
     }
     
     fn calculate_metrics(&self, 
-                        dependencies: &[CargoDependency], 
+                        dependencies: &[Dependency], 
                         usage_data: &DependencyUsageData) -> Result<DependencyMetrics> {
-        metrics::calculate_metrics(dependencies, usage_data)
+        metrics::calculate_metrics(dependencies, usage_data, self.config.analysis.removal_threshold)
     }
     
-    fn generate_dependency_graph(&self, dependencies: &[CargoDependency]) -> Result<dependency_graph::DependencyGraph> {
-        // Check for Cargo.lock file
-        let cargo_lock_path = self.project_path.join("Cargo.lock");
-        if cargo_lock_path.exists() {
+    /// Build the dependency graph of the packages `roots` declared by the manifest at `manifest_path`
+    fn generate_dependency_graph(
+        &self,
+        manifest_path: &Path,
+        workspace: Option<&CargoWorkspace>,
+        roots: &[String],
+        dependencies: &[Dependency],
+    ) -> Result<dependency_graph::DependencyGraph> {
+        // Only Cargo.lock is understood, npm packages get a graph without relationships
+        if dependencies.iter().any(|dep| dep.ecosystem() != Ecosystem::Rust) {
+            return Ok(dependency_graph::DependencyGraph::new(dependencies));
+        }
+        
+        match find_cargo_lock(manifest_path, workspace) {
             // Use Cargo.lock to build a more accurate dependency graph
-            dependency_graph::DependencyGraph::from_cargo_lock(&cargo_lock_path, dependencies)
-        } else {
+            Some(cargo_lock_path) => dependency_graph::DependencyGraph::from_cargo_lock(&cargo_lock_path, roots, dependencies),
             // Create a simple graph without relationship information
-            Ok(dependency_graph::DependencyGraph::new(dependencies))
+            None => Ok(dependency_graph::DependencyGraph::new(dependencies)),
         }
     }
 }
 
+/// A manifest found in the project directory, with the Cargo workspace it declares
+struct Project {
+    manifest_path: PathBuf,
+    ecosystem: Ecosystem,
+    workspace: Option<CargoWorkspace>,
+}
+
+impl Project {
+    /// Directory of the project
+    fn dir(&self) -> &Path {
+        self.manifest_path.parent().unwrap_or_else(|| Path::new("."))
+    }
+    
+    /// Load the workspace a Cargo package declaring no workspace of its own belongs to, which
+    /// lies above it
+    fn enclosing_workspace(&self) -> Result<Option<CargoWorkspace>> {
+        if self.ecosystem != Ecosystem::Rust || self.workspace.is_some() {
+            return Ok(None);
+        }
+        CargoWorkspace::find_for(&self.manifest_path)
+    }
+}
+
 /// Result of the dependency analysis
 #[derive(Debug)]
 pub struct AnalysisResult {
-    pub dependencies: Vec<CargoDependency>,
+    /// Name of the analyzed package, or of the workspace or repository root directory
+    pub name: String,
+    /// Ecosystem of the analyzed manifest; `None` for a repository with several projects
+    pub ecosystem: Option<Ecosystem>,
+    /// Path to the manifest the analysis was started from, or to the root directory of a
+    /// repository with several projects
+    pub manifest_path: PathBuf,
+    pub dependencies: Vec<Dependency>,
     pub usage_data: DependencyUsageData,
     pub metrics: DependencyMetrics,
     pub dependency_graph: dependency_graph::DependencyGraph,
+    /// Features of the package and what they enable; empty for the workspace rollup
+    pub features: FeatureGraph,
+    /// Per-member results for Cargo workspaces, in which case the fields above hold the workspace-wide rollup
+    pub members: Vec<AnalysisResult>,
+    /// Per-project results for a repository with several projects, grouped by ecosystem and
+    /// manifest path, in which case the fields above are empty
+    pub projects: Vec<AnalysisResult>,
+}
+
+impl AnalysisResult {
+    /// Whether this result is the rollup of a multi-member workspace
+    pub fn is_workspace(&self) -> bool {
+        !self.members.is_empty()
+    }
+    
+    /// Whether this result holds the projects of a repository with several of them
+    pub fn is_multi_project(&self) -> bool {
+        !self.projects.is_empty()
+    }
+    
+    /// The individual packages analyzed: the members of every workspace and the other projects
+    pub fn packages(&self) -> Vec<&AnalysisResult> {
+        if self.is_multi_project() {
+            self.projects.iter().flat_map(|project| project.packages()).collect()
+        } else if self.is_workspace() {
+            self.members.iter().collect()
+        } else {
+            vec![self]
+        }
+    }
+}
+
+/// Manifest-level view of a project: its packages and their resolved dependency graph
+#[derive(Debug)]
+pub struct ProjectGraph {
+    /// Analyzed packages: the root package or the workspace members
+    pub packages: Vec<ProjectPackage>,
+    pub dependency_graph: dependency_graph::DependencyGraph,
+    /// Workspace the packages belong to, if any
+    pub workspace: Option<CargoWorkspace>,
+}
+
+/// A package of a project with the dependencies its manifest declares
+#[derive(Debug)]
+pub struct ProjectPackage {
+    pub name: String,
+    pub manifest_path: PathBuf,
+    pub dependencies: Vec<Dependency>,
 }
 
 /// Data about how dependencies are used in the project
@@ -122,28 +658,139 @@ pub struct AnalysisResult {
 pub struct DependencyUsageData {
     /// Maps dependency name to a map of files where it's used
     pub usage_locations: std::collections::HashMap<String, Vec<DependencyUsage>>,
+    /// Source files outside the module tree of every target, which were skipped
+    pub orphan_files: Vec<PathBuf>,
+    /// Features named by cfg predicates anywhere in the analyzed code
+    pub cfg_features: std::collections::BTreeSet<String>,
+    /// Usages of crate names matching no dependency's import name, by crate name; these may
+    /// belong to a dependency whose library has a custom `[lib] name`
+    pub unknown_crates: std::collections::HashMap<String, Vec<DependencyUsage>>,
+}
+
+impl DependencyUsageData {
+    /// Add the usages of `other`, such as those of another file, after the ones already recorded
+    pub fn merge(&mut self, other: DependencyUsageData) {
+        for (dep_name, usages) in other.usage_locations {
+            self.usage_locations.entry(dep_name).or_default().extend(usages);
+        }
+        for (crate_name, usages) in other.unknown_crates {
+            self.unknown_crates.entry(crate_name).or_default().extend(usages);
+        }
+        self.orphan_files.extend(other.orphan_files);
+        self.cfg_features.extend(other.cfg_features);
+    }
+    
+    /// Mark the usages whose cfg predicate is false for the selected features and target cfgs
+    pub fn mark_inactive(&mut self, selection: &cfg::CfgSelection) {
+        for usage in self.usage_locations.values_mut().flatten() {
+            usage.inactive = usage.cfg.as_ref().is_some_and(|cfg| !selection.is_active(cfg));
+        }
+    }
+    
+    /// Usage data without the inactive usages
+    pub fn active(&self) -> DependencyUsageData {
+        let usage_locations = self.usage_locations.iter()
+            .map(|(dep_name, usages)| {
+                let active = usages.iter().filter(|usage| !usage.inactive).cloned().collect();
+                (dep_name.clone(), active)
+            })
+            .collect();
+        
+        DependencyUsageData {
+            usage_locations,
+            orphan_files: self.orphan_files.clone(),
+            cfg_features: self.cfg_features.clone(),
+            unknown_crates: self.unknown_crates.clone(),
+        }
+    }
 }
 
 /// A specific usage of a dependency in the code
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DependencyUsage {
     pub file: PathBuf,
+    /// Line where the usage starts (1-based)
     pub line: usize,
+    /// Column where the usage starts (1-based)
+    pub column: usize,
+    /// Line where the usage ends (1-based)
+    pub end_line: usize,
+    /// Column just past the end of the usage (1-based)
+    pub end_column: usize,
     pub imported_item: String,
+    /// Fully qualified path of the used item, e.g. `regex::Regex::new`; not set for crate
+    /// and module prefixes of imports, glob imports and derive helper attributes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub item: Option<String>,
     pub usage_type: UsageType,
+    /// Kind of target the usage is compiled into; code under `#[cfg(test)]` counts as a test
+    pub target: TargetKind,
+    /// Module path of the usage within its crate, e.g. `crate::net::http`, when known
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub module: String,
+    /// cfg predicate the usage is compiled under, combining those of all enclosing items
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cfg: Option<cfg::Cfg>,
+    /// Whether `cfg` is false for the selected features and target cfgs, so the usage is not counted
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub inactive: bool,
+    /// Whether the usage was found inside the arguments of a macro invocation
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub in_macro: bool,
+    /// How the usage is part of the library's public API, if it is
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exposure: Option<Exposure>,
+}
+
+impl DependencyUsage {
+    /// Format the start of the usage as `file:line:column`, as understood by editors
+    pub fn location(&self) -> String {
+        format!("{}:{}:{}", self.file.display(), self.line, self.column)
+    }
 }
 
 /// Type of dependency usage
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum UsageType {
     Import,
     Function,
     Type,
     Trait,
     Macro,
+    /// Derive macro, e.g. `#[derive(Serialize)]`
+    Derive,
+    /// Attribute macro or derive helper attribute, e.g. `#[tokio::main]` or `#[serde(...)]`
+    Attribute,
     Other,
 }
 
+/// How a dependency appears in the public API of the library, making upgrades of the
+/// dependency breaking changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Exposure {
+    /// In the signature of a public function or method, or the type of a public constant,
+    /// static or type alias
+    Signature,
+    /// In the type of a public field of a public struct, or of a public enum's variant
+    Field,
+    /// In a trait implemented, or derived, for a public type
+    TraitImpl,
+    /// Re-exported with `pub use`
+    ReExport,
+}
+
+impl fmt::Display for Exposure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Exposure::Signature => write!(f, "public signature"),
+            Exposure::Field => write!(f, "public field"),
+            Exposure::TraitImpl => write!(f, "trait impl on a public type"),
+            Exposure::ReExport => write!(f, "re-export"),
+        }
+    }
+}
+
 /// Metrics calculated for dependencies
 #[derive(Debug, Default)]
 pub struct DependencyMetrics {
@@ -151,6 +798,8 @@ pub struct DependencyMetrics {
     pub importance_scores: std::collections::HashMap<String, f64>,
     /// Maps dependency name to whether it's used or not
     pub is_used: std::collections::HashMap<String, bool>,
+    /// Maps dependency name to whether it appears in the public API of the library
+    pub is_public: std::collections::HashMap<String, bool>,
     /// Maps dependency name to how many files it's used in
     pub usage_count: std::collections::HashMap<String, usize>,
     /// Maps dependency name to counts of different usage types
@@ -161,47 +810,253 @@ pub struct DependencyMetrics {
     pub is_partially_used: std::collections::HashMap<String, bool>,
     /// List of dependencies that could potentially be removed
     pub removable_dependencies: Vec<String>,
+    /// Maps dependency name to the table it should move to, for dependencies declared in the wrong one
+    pub misplaced: std::collections::HashMap<String, Misplacement>,
+    /// Maps optional dependency name to what is wrong with how the package's features enable it
+    pub optional_issues: std::collections::HashMap<String, OptionalIssue>,
+    /// Features of the package that enable nothing the code uses
+    pub unused_package_features: Vec<String>,
+}
+
+/// A dependency declared in a table that does not match the targets using it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Misplacement {
+    /// Table the dependency is declared in
+    pub declared: DependencyType,
+    /// Table matching the targets the dependency is used by
+    pub suggested: DependencyType,
+}
+
+impl Misplacement {
+    /// Explain the misplacement and how to fix it
+    pub fn message(&self) -> String {
+        let to_build_script = self.suggested == DependencyType::Build;
+        let reason = match self.declared {
+            DependencyType::Normal if to_build_script => "only used by the build script",
+            DependencyType::Normal => "only used by tests, examples or benchmarks",
+            DependencyType::Development if to_build_script => "dev-dependency used by the build script",
+            DependencyType::Development => "dev-dependency used by library or binary code",
+            DependencyType::Build => "build-dependency not used by the build script",
+            DependencyType::Peer => "peer dependency",
+        };
+        
+        format!("{}, move it to [{}]", reason, dependency_table_name(&self.suggested))
+    }
+}
+
+/// An optional dependency that the package's features do not enable properly
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum OptionalIssue {
+    /// No feature enables the dependency, so it is never compiled
+    NotEnabled,
+    /// Code using the dependency is not behind a cfg for any feature enabling it, so
+    /// it fails to compile when those features are off
+    Ungated {
+        /// Features turning the dependency on
+        enabled_by: Vec<String>,
+        /// `file:line` of the unguarded usages
+        locations: Vec<String>,
+    },
+}
+
+impl OptionalIssue {
+    /// Explain the issue and how to fix it
+    pub fn message(&self) -> String {
+        match self {
+            OptionalIssue::NotEnabled => {
+                "optional, but no feature enables it; reference it from [features] with `dep:` or make it non-optional".to_string()
+            }
+            OptionalIssue::Ungated { enabled_by, locations } => {
+                let mut message = format!(
+                    "optional, but used without #[cfg(feature = ...)] for a feature enabling it ({}) at {}",
+                    enabled_by.join(", "),
+                    locations.iter().take(3).cloned().collect::<Vec<_>>().join(", ")
+                );
+                if locations.len() > 3 {
+                    message.push_str(&format!(" and {} more", locations.len() - 3));
+                }
+                message
+            }
+        }
+    }
 }
 
 /// Analyze a project and return a simplified representation for export
-pub fn analyze<P: AsRef<Path>>(project_path: P, manifest: &[CargoDependency]) -> Result<Analysis> {
-    let analyzer = DependencyAnalyzer::new(project_path);
+pub fn analyze<P: AsRef<Path>>(project_path: P, config: &Config) -> Result<Analysis> {
+    let analyzer = DependencyAnalyzer::new(project_path).with_config(config.clone());
     let analysis_result = analyzer.analyze()?;
     
-    // Create analyzed dependencies by combining data from the analysis result
-    let dependencies = manifest.iter()
-        .map(|dep| {
-            let name = &dep.name;
-            let version = dep.version.clone().unwrap_or_else(|| "".to_string());
-            let usage_count = *analysis_result.metrics.usage_count.get(name).unwrap_or(&0);
-            let importance_score = *analysis_result.metrics.importance_scores.get(name).unwrap_or(&0.0);
-            let removable = analysis_result.metrics.removable_dependencies.contains(name);
-            
-            // Extract used and unused features
-            let mut used_features = Vec::new();
-            let mut unused_features = Vec::new();
-            
-            if let Some(feature_map) = analysis_result.metrics.feature_usage.get(name) {
-                for (feature, is_used) in feature_map {
-                    if *is_used {
-                        used_features.push(feature.clone());
-                    } else {
-                        unused_features.push(feature.clone());
-                    }
-                }
-            }
-            
-            AnalyzedDependency {
-                name: name.clone(),
-                version,
-                usage_count,
-                importance_score,
-                removable,
-                used_features,
-                unused_features,
-            }
-        })
+    Ok(Analysis::from_result(&analysis_result))
+}
+
+/// Combine the data collected for a dependency into its exportable representation
+fn summarize_dependency(result: &AnalysisResult, dep: &Dependency) -> AnalyzedDependency {
+    let name = &dep.name;
+    let version = dep.version.clone().unwrap_or_default();
+    let usage_count = *result.metrics.usage_count.get(name).unwrap_or(&0);
+    let importance_score = *result.metrics.importance_scores.get(name).unwrap_or(&0.0);
+    let removable = result.metrics.removable_dependencies.contains(name);
+    let usages = result.usage_data.usage_locations.get(name).cloned().unwrap_or_default();
+    let misplaced = result.metrics.misplaced.get(name).cloned();
+    let optional_issue = result.metrics.optional_issues.get(name).cloned();
+    let public = *result.metrics.is_public.get(name).unwrap_or(&false);
+    let exposures = usages.iter()
+        .filter(|usage| !usage.inactive)
+        .filter_map(|usage| usage.exposure.map(|kind| ExposureLocation {
+            location: usage.location(),
+            kind,
+            item: usage.imported_item.clone(),
+        }))
         .collect();
+    let active: Vec<DependencyUsage> = usages.iter().filter(|usage| !usage.inactive).cloned().collect();
+    let api_surface = metrics::api_surface(&active);
+    
+    // Extract used and unused features
+    let mut used_features = Vec::new();
+    let mut unused_features = Vec::new();
     
-    Ok(Analysis { dependencies })
-} 
\ No newline at end of file
+    if let Some(feature_map) = result.metrics.feature_usage.get(name) {
+        for (feature, is_used) in feature_map {
+            if *is_used {
+                used_features.push(feature.clone());
+            } else {
+                unused_features.push(feature.clone());
+            }
+        }
+    }
+    
+    // Feature usage is kept in a hash map; sort so exports are the same on every run
+    used_features.sort();
+    unused_features.sort();
+    
+    AnalyzedDependency {
+        name: name.clone(),
+        ecosystem: dep.ecosystem(),
+        version,
+        package: dep.package.clone(),
+        source: dep.source.clone(),
+        default_features: dep.cargo().map(|cargo| cargo.default_features),
+        platforms: match dep.cargo() {
+            Some(cargo) if !cargo.all_platforms => cargo.platforms.clone(),
+            _ => Vec::new(),
+        },
+        usage_count,
+        importance_score,
+        removable,
+        used_features,
+        unused_features,
+        misplaced,
+        optional_issue,
+        public,
+        exposures,
+        api_surface,
+        usages,
+    }
+}
+
+/// Find the Cargo.lock of the package whose manifest is at `manifest_path`: next to it, or at the
+/// root of `workspace`, the workspace it belongs to
+fn find_cargo_lock(manifest_path: &Path, workspace: Option<&CargoWorkspace>) -> Option<PathBuf> {
+    let cargo_lock_path = manifest_path.with_file_name("Cargo.lock");
+    if cargo_lock_path.exists() {
+        return Some(cargo_lock_path);
+    }
+    
+    workspace
+        .map(|workspace| workspace.root.join("Cargo.lock"))
+        .filter(|cargo_lock_path| cargo_lock_path.exists())
+}
+
+/// Resolve a path to its canonical form, keeping it as is if that fails
+fn canonical_path(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Get a display name for a directory, resolving relative paths such as `.`
+fn directory_name(path: &Path) -> String {
+    canonical_path(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::fs;
+    
+    /// Write `files`, given as `(path, contents)`, into a new temporary directory
+    fn project_dir(files: &[(&str, &str)]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for (path, contents) in files {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        dir
+    }
+    
+    fn analyzer(dir: &Path) -> DependencyAnalyzer {
+        let mut config = Config::default();
+        config.analysis.use_cache = false;
+        DependencyAnalyzer::new(dir).with_config(config)
+    }
+    
+    /// Ecosystems and manifest paths, relative to `dir`, of the projects found in `dir`
+    fn projects(dir: &Path) -> Vec<(Ecosystem, PathBuf)> {
+        analyzer(dir).find_projects().unwrap()
+            .into_iter()
+            .map(|project| (project.ecosystem, project.manifest_path.strip_prefix(dir).unwrap().to_path_buf()))
+            .collect()
+    }
+    
+    #[test]
+    fn keeps_path_dependencies_in_the_depending_project() {
+        let dir = project_dir(&[
+            ("Cargo.toml", "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\napp-macros = { path = \"macros\" }\n"),
+            ("src/main.rs", "fn main() {}\n"),
+            ("macros/Cargo.toml", "[package]\nname = \"app-macros\"\nversion = \"0.1.0\"\n\n[lib]\nproc-macro = true\n"),
+            ("macros/src/lib.rs", ""),
+        ]);
+        
+        assert_eq!(projects(dir.path()), vec![(Ecosystem::Rust, PathBuf::from("Cargo.toml"))]);
+        assert!(!analyzer(dir.path()).analyze().unwrap().is_multi_project());
+    }
+    
+    #[test]
+    fn keeps_path_dependencies_of_workspace_members_in_the_workspace() {
+        let dir = project_dir(&[
+            ("Cargo.toml", "[workspace]\nmembers = [\"crates/app\"]\n"),
+            ("crates/app/Cargo.toml", "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\nvendored = { path = \"../../vendor/vendored\" }\n"),
+            ("crates/app/src/lib.rs", ""),
+            ("vendor/vendored/Cargo.toml", "[package]\nname = \"vendored\"\nversion = \"0.1.0\"\n"),
+            ("vendor/vendored/src/lib.rs", ""),
+        ]);
+        
+        assert_eq!(projects(dir.path()), vec![(Ecosystem::Rust, PathBuf::from("Cargo.toml"))]);
+    }
+    
+    #[test]
+    fn finds_a_frontend_next_to_a_rust_backend() {
+        let dir = project_dir(&[
+            ("Cargo.toml", "[package]\nname = \"backend\"\nversion = \"0.1.0\"\n\n[dependencies]\nserde = \"1\"\n"),
+            ("src/main.rs", "use serde::Serialize;\nfn main() {}\n"),
+            ("web/package.json", "{\"name\": \"web\", \"dependencies\": {\"react\": \"^18.0.0\"}}"),
+            ("web/src/index.js", "import React from 'react';\n"),
+        ]);
+        
+        assert_eq!(
+            projects(dir.path()),
+            vec![(Ecosystem::Rust, PathBuf::from("Cargo.toml")), (Ecosystem::Node, PathBuf::from("web/package.json"))],
+        );
+        
+        let result = analyzer(dir.path()).analyze().unwrap();
+        assert!(result.is_multi_project());
+        let packages: Vec<(&str, Option<Ecosystem>)> = result.projects.iter()
+            .map(|project| (project.name.as_str(), project.ecosystem))
+            .collect();
+        assert_eq!(packages, vec![("backend", Some(Ecosystem::Rust)), ("web", Some(Ecosystem::Node))]);
+    }
+}
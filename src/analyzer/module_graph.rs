@@ -0,0 +1,396 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+use proc_macro2::{Delimiter, TokenStream, TokenTree};
+use syn::punctuated::Punctuated;
+use syn::Token;
+
+use crate::analyzer::cfg::Cfg;
+use crate::manifest::cargo::{CargoTargets, TargetKind};
+
+/// Place of a source file in the module tree of a package
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModuleInfo {
+    /// Kind of target the file is compiled into
+    pub target: TargetKind,
+    /// Module path of the file within its crate, e.g. `crate::net::http`
+    pub module: String,
+    /// cfg predicate of the `mod` declarations leading to the file
+    pub cfg: Option<Cfg>,
+    /// Whether `pub` items of the file are part of the library's public API: the file
+    /// belongs to the library and every `mod` declaration leading to it is `pub`
+    pub public: bool,
+}
+
+/// Source files reachable from the roots of a package's targets through `mod` declarations
+#[derive(Debug, Default)]
+pub struct ModuleGraph {
+    /// Reachable files keyed by their normalized path
+    modules: HashMap<PathBuf, ModuleInfo>,
+}
+
+/// Where `mod` declarations in a part of a file resolve to
+struct Scope {
+    /// Directory `mod foo;` looks for `foo.rs` and `foo/mod.rs` in
+    dir: PathBuf,
+    /// Directory `#[path = "..."]` attributes are relative to
+    path_attr_dir: PathBuf,
+    /// Module path of the scope
+    module: Vec<String>,
+    target: TargetKind,
+    /// cfg predicates of the enclosing modules
+    cfgs: Vec<Cfg>,
+    /// Whether the scope is part of the library's public API
+    public: bool,
+}
+
+/// Attributes and visibility seen since the last item, applying to the next `mod` declaration
+#[derive(Default)]
+struct PendingAttrs {
+    path: Option<String>,
+    test_only: bool,
+    cfgs: Vec<Cfg>,
+    /// A plain `pub`, not `pub(crate)` or other restricted visibility
+    public: bool,
+}
+
+impl ModuleGraph {
+    /// Follow `mod` declarations from every target root of a package. A file reachable
+    /// from several targets belongs to the first one, starting with the library.
+    pub fn build(targets: &CargoTargets) -> Self {
+        let mut graph = Self::default();
+        
+        for (root, target) in targets.roots() {
+            graph.add_file(&root, true, vec!["crate".to_string()], target, Vec::new(), target == TargetKind::Lib);
+        }
+        
+        graph
+    }
+    
+    /// Get the place of a file in the module tree, if it is reachable from a target root
+    pub fn get(&self, file: &Path) -> Option<&ModuleInfo> {
+        self.modules.get(&normalize(file))
+    }
+    
+    /// Add a module file and, recursively, the files of the modules it declares.
+    /// Root files, `mod.rs` files and files loaded through `#[path]` own their directory.
+    fn add_file(&mut self, path: &Path, owns_dir: bool, module: Vec<String>, target: TargetKind, cfgs: Vec<Cfg>, public: bool) {
+        let key = normalize(path);
+        if self.modules.contains_key(&key) || !key.is_file() {
+            return;
+        }
+        
+        self.modules.insert(key.clone(), ModuleInfo {
+            target,
+            module: module.join("::"),
+            cfg: Cfg::all_of(&cfgs),
+            public,
+        });
+        
+        // Only `mod` declarations are needed, so lexing is enough; this also finds
+        // declarations inside macros such as `cfg_if!`
+        let tokens = match fs::read_to_string(&key).ok().and_then(|content| TokenStream::from_str(&content).ok()) {
+            Some(tokens) => tokens,
+            None => return,
+        };
+        
+        let parent = key.parent().unwrap_or(Path::new("")).to_path_buf();
+        let dir = match key.file_stem() {
+            Some(stem) if !owns_dir => parent.join(stem),
+            _ => parent.clone(),
+        };
+        
+        let scope = Scope {
+            dir,
+            path_attr_dir: parent,
+            module,
+            target,
+            cfgs,
+            public,
+        };
+        self.scan(tokens, &scope);
+    }
+    
+    /// Find the `mod` declarations in the tokens of a scope
+    fn scan(&mut self, tokens: TokenStream, scope: &Scope) {
+        let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+        let mut pending = PendingAttrs::default();
+        // Inner `#![cfg(...)]` attributes apply to every module declared after them
+        let mut scope_cfgs = scope.cfgs.clone();
+        let mut i = 0;
+        
+        while i < tokens.len() {
+            match &tokens[i] {
+                TokenTree::Punct(punct) if punct.as_char() == '#' => {
+                    match tokens.get(i + 1) {
+                        // Inner attributes such as `#![cfg(...)]` apply to the enclosing module
+                        Some(TokenTree::Punct(bang)) if bang.as_char() == '!' => {
+                            if let Some(TokenTree::Group(group)) = tokens.get(i + 2) {
+                                let meta = syn::parse2::<syn::Meta>(group.stream()).ok();
+                                scope_cfgs.extend(meta.as_ref().and_then(Cfg::from_attribute));
+                            }
+                            i += 2;
+                        }
+                        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Bracket => {
+                            if let Ok(meta) = syn::parse2::<syn::Meta>(group.stream()) {
+                                pending.path = pending.path.or_else(|| path_attr(&meta));
+                                pending.test_only |= is_test_only(&meta);
+                                pending.cfgs.extend(Cfg::from_attribute(&meta));
+                            }
+                            i += 1;
+                        }
+                        _ => {}
+                    }
+                }
+                TokenTree::Ident(ident) if ident == "pub" => {
+                    // `pub(crate)`, `pub(super)` and `pub(in path)` do not export the module
+                    pending.public = !matches!(tokens.get(i + 1), Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis);
+                }
+                TokenTree::Ident(ident) if ident == "mod" => {
+                    if let Some(TokenTree::Ident(name)) = tokens.get(i + 1) {
+                        let name = name.to_string();
+                        let name = name.strip_prefix("r#").unwrap_or(&name);
+                        let mut module = scope.module.clone();
+                        module.push(name.to_string());
+                        let target = if pending.test_only { TargetKind::Test } else { scope.target };
+                        let public = scope.public && pending.public && target != TargetKind::Test;
+                        let mut cfgs = scope_cfgs.clone();
+                        cfgs.append(&mut pending.cfgs);
+                        
+                        match tokens.get(i + 2) {
+                            Some(TokenTree::Punct(semi)) if semi.as_char() == ';' => {
+                                let (path, owns_dir) = match &pending.path {
+                                    Some(path) => (scope.path_attr_dir.join(path), true),
+                                    None => module_file(&scope.dir, name),
+                                };
+                                self.add_file(&path, owns_dir, module, target, cfgs, public);
+                                i += 2;
+                            }
+                            Some(TokenTree::Group(body)) if body.delimiter() == Delimiter::Brace => {
+                                // Inline modules nest their file modules, and `#[path]` attributes, in a directory
+                                let dir = match &pending.path {
+                                    Some(path) => scope.path_attr_dir.join(path),
+                                    None => scope.dir.join(name),
+                                };
+                                let inline = Scope {
+                                    dir: dir.clone(),
+                                    path_attr_dir: dir,
+                                    module,
+                                    target,
+                                    cfgs,
+                                    public,
+                                };
+                                self.scan(body.stream(), &inline);
+                                i += 2;
+                            }
+                            _ => {}
+                        }
+                    }
+                    pending = PendingAttrs::default();
+                }
+                TokenTree::Punct(punct) if punct.as_char() == ';' => pending = PendingAttrs::default(),
+                TokenTree::Group(group) => {
+                    // Declarations in macro invocations belong to this scope; `cfg_if!` branches
+                    // (`if #[cfg(unix)] { mod unix; }`) are also governed by the cfg before them
+                    let mut cfgs = scope_cfgs.clone();
+                    if group.delimiter() == Delimiter::Brace {
+                        cfgs.append(&mut pending.cfgs);
+                    }
+                    
+                    if cfgs == scope.cfgs {
+                        self.scan(group.stream(), scope);
+                    } else {
+                        let nested = Scope {
+                            dir: scope.dir.clone(),
+                            path_attr_dir: scope.path_attr_dir.clone(),
+                            module: scope.module.clone(),
+                            target: scope.target,
+                            cfgs,
+                            public: scope.public,
+                        };
+                        self.scan(group.stream(), &nested);
+                    }
+                    if group.delimiter() == Delimiter::Brace {
+                        pending = PendingAttrs::default();
+                    }
+                }
+                _ => {}
+            }
+            
+            i += 1;
+        }
+    }
+}
+
+/// Whether an attribute makes its item test-only: `#[cfg(test)]` and predicates requiring
+/// `test`, or test and benchmark attributes such as `#[test]` and `#[tokio::test]`
+pub fn is_test_only(meta: &syn::Meta) -> bool {
+    match meta {
+        syn::Meta::Path(path) => path.segments.last()
+            .is_some_and(|segment| segment.ident == "test" || segment.ident == "bench"),
+        syn::Meta::List(list) if list.path.is_ident("cfg") => list.parse_args::<syn::Meta>()
+            .is_ok_and(|predicate| cfg_requires_test(&predicate)),
+        _ => false,
+    }
+}
+
+/// Whether a cfg predicate can only hold when compiling tests
+fn cfg_requires_test(predicate: &syn::Meta) -> bool {
+    match predicate {
+        syn::Meta::Path(path) => path.is_ident("test"),
+        syn::Meta::List(list) => {
+            let nested = match list.parse_args_with(Punctuated::<syn::Meta, Token![,]>::parse_terminated) {
+                Ok(nested) => nested,
+                Err(_) => return false,
+            };
+            
+            if list.path.is_ident("all") {
+                nested.iter().any(cfg_requires_test)
+            } else if list.path.is_ident("any") {
+                !nested.is_empty() && nested.iter().all(cfg_requires_test)
+            } else {
+                false
+            }
+        }
+        syn::Meta::NameValue(_) => false,
+    }
+}
+
+/// The file of a `#[path = "..."]` attribute
+fn path_attr(meta: &syn::Meta) -> Option<String> {
+    match meta {
+        syn::Meta::NameValue(syn::MetaNameValue {
+            path,
+            value: syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(file), .. }),
+            ..
+        }) if path.is_ident("path") => Some(file.value()),
+        _ => None,
+    }
+}
+
+/// Resolve `mod name;` to `dir/name.rs` or `dir/name/mod.rs`, and whether the file owns its directory
+fn module_file(dir: &Path, name: &str) -> (PathBuf, bool) {
+    let file = dir.join(format!("{}.rs", name));
+    if file.exists() {
+        (file, false)
+    } else {
+        (dir.join(name).join("mod.rs"), true)
+    }
+}
+
+/// Remove `.` and resolve `..` components lexically, so paths reached through different
+/// `mod` and `#[path]` routes compare equal
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if matches!(normalized.components().next_back(), Some(Component::Normal(_))) => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    
+    /// Write the files of a package, given as `(path, content)`, into `dir`
+    fn write_package(dir: &Path, files: &[(&str, &str)]) {
+        for (path, content) in files {
+            let path = dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+    }
+    
+    /// Module path, target, cfg and visibility of a file of the package
+    fn module(graph: &ModuleGraph, dir: &Path, path: &str) -> Option<(String, TargetKind, Option<String>, bool)> {
+        graph.get(&dir.join(path))
+            .map(|info| (info.module.clone(), info.target, info.cfg.as_ref().map(Cfg::to_string), info.public))
+    }
+    
+    fn info(module: &str, target: TargetKind, cfg: Option<&str>, public: bool) -> Option<(String, TargetKind, Option<String>, bool)> {
+        Some((module.to_string(), target, cfg.map(str::to_string), public))
+    }
+    
+    #[test]
+    fn follows_mod_declarations_from_target_roots() {
+        let dir = tempfile::tempdir().unwrap();
+        write_package(dir.path(), &[
+            ("Cargo.toml", "[package]\nname = \"pkg\"\nversion = \"0.1.0\"\n"),
+            ("src/lib.rs", "pub mod api;\npub(crate) mod internal;\n#[cfg(test)]\nmod tests;\ncfg_if::cfg_if! {\n    if #[cfg(unix)] { pub mod unix; }\n}\n"),
+            ("src/api/mod.rs", "pub mod v1;\nmod r#priv;\n"),
+            ("src/api/v1.rs", ""),
+            ("src/api/priv.rs", ""),
+            ("src/internal.rs", "pub mod helpers;\n"),
+            ("src/internal/helpers.rs", ""),
+            ("src/tests.rs", ""),
+            ("src/unix.rs", ""),
+            ("src/main.rs", "mod cli;\nmod api;\n"),
+            ("src/cli.rs", ""),
+            ("src/unreachable.rs", ""),
+        ]);
+        
+        let graph = ModuleGraph::build(&CargoTargets::load(dir.path().join("Cargo.toml")).unwrap());
+        let module = |path| module(&graph, dir.path(), path);
+        
+        assert_eq!(module("src/lib.rs"), info("crate", TargetKind::Lib, None, true));
+        // Also declared by the binary, but files reachable from several targets belong to the library
+        assert_eq!(module("src/api/mod.rs"), info("crate::api", TargetKind::Lib, None, true));
+        assert_eq!(module("src/api/v1.rs"), info("crate::api::v1", TargetKind::Lib, None, true));
+        assert_eq!(module("src/api/priv.rs"), info("crate::api::priv", TargetKind::Lib, None, false));
+        // `pub(crate)` does not export the module, nor anything under it
+        assert_eq!(module("src/internal.rs"), info("crate::internal", TargetKind::Lib, None, false));
+        assert_eq!(module("src/internal/helpers.rs"), info("crate::internal::helpers", TargetKind::Lib, None, false));
+        assert_eq!(module("src/tests.rs"), info("crate::tests", TargetKind::Test, Some("test"), false));
+        assert_eq!(module("src/unix.rs"), info("crate::unix", TargetKind::Lib, Some("unix"), true));
+        assert_eq!(module("src/main.rs"), info("crate", TargetKind::Bin, None, false));
+        assert_eq!(module("src/cli.rs"), info("crate::cli", TargetKind::Bin, None, false));
+        assert_eq!(module("src/unreachable.rs"), None);
+    }
+    
+    #[test]
+    fn resolves_path_attributes_in_inline_modules() {
+        let dir = tempfile::tempdir().unwrap();
+        write_package(dir.path(), &[
+            ("Cargo.toml", "[package]\nname = \"pkg\"\nversion = \"0.1.0\"\n"),
+            ("src/lib.rs", "#[cfg(feature = \"net\")]\npub mod net {\n    pub mod http;\n    #[path = \"custom_tls.rs\"]\n    pub mod tls;\n}\n#[path = \"platform\"]\nmod sys {\n    mod linux;\n}\n#[path = \"other/shared.rs\"]\nmod shared;\n"),
+            ("src/net/http.rs", ""),
+            ("src/net/custom_tls.rs", ""),
+            ("src/platform/linux.rs", ""),
+            ("src/other/shared.rs", "mod nested;\n"),
+            ("src/other/nested.rs", ""),
+        ]);
+        
+        let graph = ModuleGraph::build(&CargoTargets::load(dir.path().join("Cargo.toml")).unwrap());
+        let module = |path| module(&graph, dir.path(), path);
+        
+        assert_eq!(module("src/net/http.rs"), info("crate::net::http", TargetKind::Lib, Some("feature = \"net\""), true));
+        assert_eq!(module("src/net/custom_tls.rs"), info("crate::net::tls", TargetKind::Lib, Some("feature = \"net\""), true));
+        assert_eq!(module("src/platform/linux.rs"), info("crate::sys::linux", TargetKind::Lib, None, false));
+        // A file loaded through `#[path]` owns its directory, like `mod.rs`
+        assert_eq!(module("src/other/shared.rs"), info("crate::shared", TargetKind::Lib, None, false));
+        assert_eq!(module("src/other/nested.rs"), info("crate::shared::nested", TargetKind::Lib, None, false));
+    }
+    
+    #[test]
+    fn finds_test_only_attributes() {
+        let is_test_only = |attr: &str| is_test_only(&syn::parse_str::<syn::Meta>(attr).unwrap());
+        
+        assert!(is_test_only("test"));
+        assert!(is_test_only("tokio::test"));
+        assert!(is_test_only("cfg(all(test, unix))"));
+        assert!(is_test_only("cfg(any(test, all(test, unix)))"));
+        assert!(!is_test_only("cfg(any(test, unix))"));
+        assert!(!is_test_only("cfg(not(test))"));
+        assert!(!is_test_only("derive(Debug)"));
+    }
+}
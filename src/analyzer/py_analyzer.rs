@@ -0,0 +1,732 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use anyhow::Result;
+
+use crate::manifest::cargo::TargetKind;
+use crate::manifest::dependency::Dependency;
+use crate::manifest::python::{default_module_name, normalize_name};
+use crate::analyzer::{DependencyUsageData, DependencyUsage, UsageType};
+use crate::analyzer::lexer::{SourceReader, Token, TokenKind, TokenLookup};
+use crate::analyzer::module_graph::ModuleInfo;
+use crate::analyzer::source::{analyze_files_cached, classify_by_dirs, relative_path, FileAnalysis};
+use crate::utils::config::Config;
+use crate::utils::fs::FileWalker;
+
+/// Extensions of the Python source and stub files that are analyzed
+pub const PY_EXTENSIONS: &[&str] = &["py", "pyi"];
+
+/// Modules of popular distributions that are not imported by their own name, keyed by
+/// normalized distribution name
+const DISTRIBUTION_MODULES: &[(&str, &[&str])] = &[
+    ("attrs", &["attr", "attrs"]),
+    ("beautifulsoup4", &["bs4"]),
+    ("discord-py", &["discord"]),
+    ("django-cors-headers", &["corsheaders"]),
+    ("django-filter", &["django_filters"]),
+    ("djangorestframework", &["rest_framework"]),
+    ("faiss-cpu", &["faiss"]),
+    ("faiss-gpu", &["faiss"]),
+    ("google-api-python-client", &["googleapiclient"]),
+    ("grpcio", &["grpc"]),
+    ("grpcio-tools", &["grpc_tools"]),
+    ("ipython", &["IPython"]),
+    ("msgpack-python", &["msgpack"]),
+    ("mysqlclient", &["MySQLdb"]),
+    ("opencv-contrib-python", &["cv2"]),
+    ("opencv-python", &["cv2"]),
+    ("opencv-python-headless", &["cv2"]),
+    ("pillow", &["PIL"]),
+    ("protobuf", &["google.protobuf"]),
+    ("psycopg2-binary", &["psycopg2"]),
+    ("pycryptodome", &["Crypto"]),
+    ("pycryptodomex", &["Cryptodome"]),
+    ("pygobject", &["gi"]),
+    ("pyjwt", &["jwt"]),
+    ("pymupdf", &["fitz"]),
+    ("pyopenssl", &["OpenSSL"]),
+    ("pyserial", &["serial"]),
+    ("pysocks", &["socks", "sockshandler"]),
+    ("python-dateutil", &["dateutil"]),
+    ("python-docx", &["docx"]),
+    ("python-dotenv", &["dotenv"]),
+    ("python-jose", &["jose"]),
+    ("python-magic", &["magic"]),
+    ("python-multipart", &["multipart", "python_multipart"]),
+    ("python-pptx", &["pptx"]),
+    ("python-telegram-bot", &["telegram"]),
+    ("pywin32", &["win32api", "win32con", "win32com", "win32gui", "pythoncom", "pywintypes"]),
+    ("pyyaml", &["yaml"]),
+    ("pyzmq", &["zmq"]),
+    ("scikit-image", &["skimage"]),
+    ("scikit-learn", &["sklearn"]),
+    ("setuptools", &["setuptools", "pkg_resources"]),
+    ("tensorflow-cpu", &["tensorflow"]),
+    ("tensorflow-gpu", &["tensorflow"]),
+    ("websocket-client", &["websocket"]),
+];
+
+/// Prefixes of distribution families that install into a namespace package, with the
+/// dashes of their name standing for dots (`google-cloud-storage` is `google.cloud.storage`)
+const NAMESPACE_FAMILIES: &[&str] = &["azure-", "google-cloud-"];
+
+/// Analyzer for Python files of Python projects
+pub struct PyAnalyzer {
+    project_path: PathBuf,
+    /// Decides which files below the project path are analyzed
+    walker: FileWalker,
+    /// Number of threads scanning files in parallel (`None` uses one per CPU)
+    threads: Option<usize>,
+    /// File caching per-file results between runs, if caching is enabled
+    cache_file: Option<PathBuf>,
+    /// Modules distributions are imported as, keyed by normalized distribution name
+    module_map: BTreeMap<String, Vec<String>>,
+}
+
+/// Splits source into tokens, skipping whitespace, comments and line continuations
+struct Lexer {
+    reader: SourceReader,
+    /// Nesting of brackets, inside which line breaks do not end the logical line
+    depth: usize,
+}
+
+/// A local name bound by an import, e.g. `np` in `import numpy as np`
+#[derive(Debug, Clone)]
+struct Binding {
+    /// Module the binding is imported from
+    module: String,
+    /// Dotted path of what the binding refers to: the module itself, or `module.name` for
+    /// a name imported from it
+    path: String,
+}
+
+/// Finds the imports of a single file and the usages of the names they bind
+struct ImportCollector<'a> {
+    tokens: &'a [Token],
+    /// Dependency names keyed by the top-level modules they install
+    modules: &'a HashMap<String, Vec<String>>,
+    file_path: PathBuf,
+    target: TargetKind,
+    bindings: HashMap<String, Binding>,
+    /// Tokens declaring bindings, which are not usages of them
+    declarations: HashSet<usize>,
+    usage_data: &'a mut DependencyUsageData,
+}
+
+impl PyAnalyzer {
+    /// Create a new Python analyzer for the given project path, using the default configuration
+    pub fn new<P: AsRef<Path>>(project_path: P) -> Result<Self> {
+        let project_path = project_path.as_ref().to_path_buf();
+        let walker = FileWalker::new(&project_path, &Config::default())?;
+        
+        Ok(Self {
+            project_path,
+            walker,
+            threads: None,
+            cache_file: None,
+            module_map: DISTRIBUTION_MODULES.iter()
+                .map(|(name, modules)| (name.to_string(), modules.iter().map(|module| module.to_string()).collect()))
+                .collect(),
+        })
+    }
+    
+    /// Discover files with the given walker instead of the default one
+    pub fn with_walker(mut self, walker: FileWalker) -> Self {
+        self.walker = walker;
+        self
+    }
+    
+    /// Scan files with the given number of threads instead of one per CPU
+    pub fn with_threads(mut self, threads: Option<usize>) -> Self {
+        self.threads = threads;
+        self
+    }
+    
+    /// Reuse the results of unchanged files from the cache at `cache_file`, and update it
+    pub fn with_cache(mut self, cache_file: PathBuf) -> Self {
+        self.cache_file = Some(cache_file);
+        self
+    }
+    
+    /// Attribute imports of the given modules to the distributions they are keyed by, instead
+    /// of the built-in modules of those distributions
+    pub fn with_module_map(mut self, module_map: &BTreeMap<String, Vec<String>>) -> Self {
+        for (name, modules) in module_map {
+            self.module_map.insert(normalize_name(name), modules.clone());
+        }
+        self
+    }
+    
+    /// Analyze Python code to detect dependency usage
+    pub fn analyze(&self, dependencies: &[Dependency]) -> Result<DependencyUsageData> {
+        // Find all source files in the project, in a stable order
+        let files = self.walker.files_with_extensions(&self.project_path, PY_EXTENSIONS);
+        let modules = self.module_owners(dependencies);
+        
+        let module_of = |file_path: &Path| ModuleInfo {
+            target: classify(relative_path(&self.project_path, file_path)),
+            module: String::new(),
+            cfg: None,
+            public: false,
+        };
+        let options = FileAnalysis {
+            project_path: &self.project_path,
+            threads: self.threads,
+            cache_file: self.cache_file.as_deref(),
+        };
+        
+        // The module map decides what is attributed to whom, so changing it invalidates the cache
+        analyze_files_cached(&files, dependencies, &self.module_map, &options, module_of, |file_content, file_path, module, file_data| {
+            analyze_file(file_content, file_path, module.target, &modules, file_data)
+        })
+    }
+    
+    /// Dependencies keyed by each module they are imported as. Type stub distributions
+    /// (`types-requests`, `pandas-stubs`) are imported as the distribution they describe.
+    fn module_owners(&self, dependencies: &[Dependency]) -> HashMap<String, Vec<String>> {
+        let mut owners: HashMap<String, Vec<String>> = HashMap::new();
+        
+        for dep in dependencies {
+            let lowercase = dep.name.to_lowercase();
+            let described = lowercase.strip_prefix("types-")
+                .or_else(|| lowercase.strip_suffix("-stubs"))
+                .unwrap_or(&lowercase);
+            let normalized = normalize_name(described);
+            
+            let modules = match self.module_map.get(&normalized) {
+                Some(modules) => modules.clone(),
+                None if NAMESPACE_FAMILIES.iter().any(|family| normalized.starts_with(family)) => {
+                    vec![normalized.replace('-', ".")]
+                }
+                None => vec![default_module_name(described)],
+            };
+            
+            for module in modules {
+                let names = owners.entry(module).or_default();
+                if !names.contains(&dep.name) {
+                    names.push(dep.name.clone());
+                }
+            }
+        }
+        
+        owners
+    }
+}
+
+/// Analyze the content of a single Python file for dependency usage
+fn analyze_file(
+    file_content: &str,
+    file_path: &Path,
+    target: TargetKind,
+    modules: &HashMap<String, Vec<String>>,
+    usage_data: &mut DependencyUsageData,
+) {
+    let tokens = Lexer::lex(file_content);
+    
+    let mut collector = ImportCollector {
+        tokens: &tokens,
+        modules,
+        file_path: file_path.to_path_buf(),
+        target,
+        bindings: HashMap::new(),
+        declarations: HashSet::new(),
+        usage_data,
+    };
+    
+    // Functions may use names imported further down the module, so all imports are collected first
+    for i in 0..tokens.len() {
+        collector.collect_import(i);
+    }
+    for i in 0..tokens.len() {
+        collector.collect_binding_usage(i);
+    }
+}
+
+/// Determine the kind of target a file belongs to from its path: test modules and pytest
+/// configuration, benchmarks, examples, `setup.py`, and everything else as the package itself
+fn classify(relative: &Path) -> TargetKind {
+    let is_test = |file_name: &str| {
+        let stem = file_name.split('.').next().unwrap_or_default();
+        stem.starts_with("test_") || stem.ends_with("_test") || stem == "conftest"
+    };
+    
+    match classify_by_dirs(relative, &["test", "tests", "testing"], is_test) {
+        Some(kind) => kind,
+        None if relative == Path::new("setup.py") => TargetKind::Build,
+        None => TargetKind::Lib,
+    }
+}
+
+impl<'a> TokenLookup<'a> for ImportCollector<'a> {
+    fn tokens(&self) -> &'a [Token] {
+        self.tokens
+    }
+}
+
+impl<'a> ImportCollector<'a> {
+    /// Whether a statement starts at token `i`: at the start of a logical line, after `;`, or
+    /// after the `:` of a compound statement on one line (`try: import x`)
+    fn is_statement_start(&self, i: usize) -> bool {
+        i == 0 || matches!(self.kind(i - 1), Some(TokenKind::Newline) | Some(TokenKind::Punct(';' | ':')))
+    }
+    
+    /// Read a dotted name such as `os.path` starting at token `i`, returning it with the index
+    /// of its last token
+    fn dotted_name(&self, i: usize) -> Option<(String, usize)> {
+        let mut name = self.ident(i)?.to_string();
+        let mut last = i;
+        
+        while let Some(segment) = self.ident(last + 2).filter(|_| self.is_punct(last + 1, '.')) {
+            name.push('.');
+            name.push_str(segment);
+            last += 2;
+        }
+        
+        Some((name, last))
+    }
+    
+    /// Dependencies a dotted module path belongs to, found by its longest prefix naming a module
+    /// that a dependency is imported as (`google.cloud.storage.Client` for `google.cloud.storage`)
+    fn dependencies_of(&self, path: &str) -> Vec<String> {
+        let mut prefix = path;
+        
+        loop {
+            if let Some(dependencies) = self.modules.get(prefix) {
+                return dependencies.clone();
+            }
+            match prefix.rfind('.') {
+                Some(dot) => prefix = &prefix[..dot],
+                None => return Vec::new(),
+            }
+        }
+    }
+    
+    /// Whether a dotted path leads to a module a dependency is imported as, possibly through
+    /// namespace packages (`google` for `google.protobuf`)
+    fn leads_to_dependency(&self, path: &str) -> bool {
+        !self.dependencies_of(path).is_empty()
+            || self.modules.keys().any(|module| module.strip_prefix(path).is_some_and(|rest| rest.starts_with('.')))
+    }
+    
+    /// Record a usage spanning tokens `start` to `end` for every dependency given
+    fn record_usage(&mut self, dependencies: &[String], start: usize, end: usize, imported_item: String, item: String, usage_type: UsageType) {
+        let (first, last) = (&self.tokens[start], &self.tokens[end]);
+        
+        for dep_name in dependencies {
+            if let Some(usages) = self.usage_data.usage_locations.get_mut(dep_name) {
+                usages.push(DependencyUsage {
+                    file: self.file_path.clone(),
+                    line: first.line,
+                    column: first.column,
+                    end_line: last.end_line,
+                    end_column: last.end_column,
+                    imported_item: imported_item.clone(),
+                    item: Some(item.clone()),
+                    usage_type: usage_type.clone(),
+                    target: self.target,
+                    module: String::new(),
+                    cfg: None,
+                    inactive: false,
+                    in_macro: false,
+                    exposure: None,
+                });
+            }
+        }
+    }
+    
+    /// Record the import starting at token `i`, if any: an `import` or `from ... import`
+    /// statement, or a `__import__()` or `importlib.import_module()` call
+    fn collect_import(&mut self, i: usize) {
+        match self.ident(i) {
+            Some("import_module") => self.collect_dynamic_import(i),
+            _ if self.is_property(i) => {}
+            Some("__import__") => self.collect_dynamic_import(i),
+            Some("import") if self.is_statement_start(i) => self.collect_import_statement(i),
+            Some("from") if self.is_statement_start(i) => self.collect_from_import(i),
+            _ => {}
+        }
+    }
+    
+    /// `import a.b`, `import a.b as c` and several of them separated by commas
+    fn collect_import_statement(&mut self, i: usize) {
+        let mut j = i + 1;
+        
+        while let Some((path, last)) = self.dotted_name(j) {
+            let alias = self.ident(last + 2).filter(|_| self.is_ident(last + 1, "as"));
+            let dependencies = self.dependencies_of(&path);
+            
+            let (imported_item, end) = match alias {
+                Some(alias) => (format!("{} as {}", path, alias), last + 2),
+                None => (path.clone(), last),
+            };
+            self.record_usage(&dependencies, j, end, imported_item, path.clone(), UsageType::Import);
+            self.declarations.extend(j..=end);
+            
+            // `import a.b` binds `a`, while `import a.b as c` binds `c` to `a.b`
+            match alias {
+                Some(alias) => self.bind(alias, &path, path.clone()),
+                None => {
+                    let top_level = path.split('.').next().unwrap_or(&path).to_string();
+                    self.bind(&top_level, &top_level, top_level.clone());
+                }
+            }
+            
+            if !self.is_punct(end + 1, ',') {
+                break;
+            }
+            j = end + 2;
+        }
+    }
+    
+    /// `from a.b import c`, `from a import c as d, e`, `from a import (c, d)` and `from a import *`.
+    /// Relative imports are of the project's own modules.
+    fn collect_from_import(&mut self, i: usize) {
+        let (module, last) = match self.dotted_name(i + 1) {
+            Some(name) => name,
+            None => return,
+        };
+        if !self.is_ident(last + 1, "import") {
+            return;
+        }
+        
+        let mut j = last + 2;
+        if self.is_punct(j, '(') {
+            j += 1;
+        }
+        
+        if self.is_punct(j, '*') {
+            let dependencies = self.dependencies_of(&module);
+            self.record_usage(&dependencies, j, j, format!("* (from {})", module), module.clone(), UsageType::Import);
+            return;
+        }
+        
+        while let Some(name) = self.ident(j) {
+            let alias = self.ident(j + 2).filter(|_| self.is_ident(j + 1, "as"));
+            // The imported name may be a submodule, which can belong to another distribution
+            // sharing a namespace package (`from google.cloud import storage`)
+            let path = format!("{}.{}", module, name);
+            let dependencies = self.dependencies_of(&path);
+            
+            let (imported_item, end) = match alias {
+                Some(alias) => (format!("{} as {} (from {})", name, alias, module), j + 2),
+                None => (format!("{} (from {})", name, module), j),
+            };
+            self.record_usage(&dependencies, j, end, imported_item, path.clone(), UsageType::Import);
+            self.declarations.extend(j..=end);
+            self.bind(alias.unwrap_or(name), &module, path);
+            
+            if !self.is_punct(end + 1, ',') {
+                break;
+            }
+            j = end + 2;
+        }
+    }
+    
+    /// `__import__('x')` and `importlib.import_module('x')` with a literal, absolute module name
+    fn collect_dynamic_import(&mut self, i: usize) {
+        let module = match self.string(i + 2) {
+            Some(module) if self.is_punct(i + 1, '(') && !module.starts_with('.') => module,
+            _ => return,
+        };
+        
+        let dependencies = self.dependencies_of(module);
+        self.record_usage(&dependencies, i + 2, i + 2, module.to_string(), module.to_string(), UsageType::Import);
+    }
+    
+    fn bind(&mut self, local: &str, module: &str, path: String) {
+        if !self.leads_to_dependency(&path) {
+            return;
+        }
+        
+        self.bindings.insert(local.to_string(), Binding {
+            module: module.to_string(),
+            path,
+        });
+    }
+    
+    /// Record a usage of an imported name at token `i`, together with the attributes accessed
+    /// on it: calls, instantiations, decorators and other references
+    fn collect_binding_usage(&mut self, i: usize) {
+        let name = match self.ident(i) {
+            Some(name) => name,
+            None => return,
+        };
+        let binding = match self.bindings.get(name) {
+            Some(binding) if !self.declarations.contains(&i) => binding.clone(),
+            _ => return,
+        };
+        
+        // Attributes of other objects, keyword arguments, assignments and definitions
+        // shadowing the name are not references to the binding
+        let previous = i.checked_sub(1);
+        let is_assigned = self.is_punct(i + 1, '=') && !self.is_punct(i + 2, '=');
+        let is_definition = previous.and_then(|p| self.ident(p)).is_some_and(|word| word == "def" || word == "class");
+        if self.is_property(i) || is_assigned || is_definition {
+            return;
+        }
+        
+        // Follow the attributes accessed on the binding: `np.linalg.norm`
+        let mut path = binding.path.clone();
+        let mut chain = name.to_string();
+        let mut last = i;
+        while let Some(attribute) = self.ident(last + 2).filter(|_| self.is_punct(last + 1, '.')) {
+            path = format!("{}.{}", path, attribute);
+            chain = format!("{}.{}", chain, attribute);
+            last += 2;
+        }
+        
+        let dependencies = self.dependencies_of(&path);
+        if dependencies.is_empty() {
+            return;
+        }
+        
+        // Classes are capitalized by convention, and calling one creates an instance
+        let referenced = self.ident(last).unwrap_or(name);
+        let is_decorator = previous.is_some_and(|p| self.is_punct(p, '@'));
+        let (imported_item, usage_type) = if referenced.starts_with(|c: char| c.is_uppercase()) {
+            (format!("{} (from {})", chain, binding.module), UsageType::Type)
+        } else if self.is_punct(last + 1, '(') || is_decorator {
+            (format!("{}() (from {})", chain, binding.module), UsageType::Function)
+        } else {
+            (format!("{} (from {})", chain, binding.module), UsageType::Other)
+        };
+        
+        self.record_usage(&dependencies, i, last, imported_item, path, usage_type);
+    }
+}
+
+impl Lexer {
+    /// Split source into tokens
+    fn lex(content: &str) -> Vec<Token> {
+        let mut lexer = Lexer {
+            reader: SourceReader::new(content),
+            depth: 0,
+        };
+        lexer.lex_tokens();
+        
+        lexer.reader.tokens
+    }
+    
+    fn lex_tokens(&mut self) {
+        while let Some(c) = self.reader.peek(0) {
+            let (line, column) = (self.reader.line, self.reader.column);
+            
+            match c {
+                '\n' => {
+                    self.reader.bump();
+                    // Blank lines and lines inside brackets do not end a logical line
+                    let ends_line = self.reader.tokens.last().is_some_and(|token| token.kind != TokenKind::Newline);
+                    if self.depth == 0 && ends_line {
+                        self.reader.push(TokenKind::Newline, line, column);
+                    }
+                }
+                c if c.is_whitespace() => {
+                    self.reader.bump();
+                }
+                '#' => self.reader.skip_line(),
+                '\\' => {
+                    // A backslash at the end of a line continues the logical line
+                    self.reader.bump();
+                    if self.reader.peek(0) == Some('\r') {
+                        self.reader.bump();
+                    }
+                    if self.reader.peek(0) == Some('\n') {
+                        self.reader.bump();
+                    }
+                }
+                '\'' | '"' => {
+                    let value = self.string(c);
+                    self.reader.push(TokenKind::Str(value), line, column);
+                }
+                c if c.is_alphabetic() || c == '_' => {
+                    let ident = self.reader.read_while(|c| c.is_alphanumeric() || c == '_');
+                    
+                    // `r'...'`, `b"..."`, `f'''...'''` and other prefixed strings
+                    match self.reader.peek(0) {
+                        Some(quote @ ('\'' | '"')) if is_string_prefix(&ident) => {
+                            let value = self.string(quote);
+                            self.reader.push(TokenKind::Str(value), line, column);
+                        }
+                        _ => self.reader.push(TokenKind::Ident(ident), line, column),
+                    }
+                }
+                c if c.is_ascii_digit() => {
+                    self.reader.skip_number();
+                    self.reader.push(TokenKind::Value, line, column);
+                }
+                c => {
+                    match c {
+                        '(' | '[' | '{' => self.depth += 1,
+                        ')' | ']' | '}' => self.depth = self.depth.saturating_sub(1),
+                        _ => {}
+                    }
+                    self.reader.bump();
+                    self.reader.push(TokenKind::Punct(c), line, column);
+                }
+            }
+        }
+    }
+    
+    /// Read a single- or triple-quoted string literal; unterminated single-quoted strings end
+    /// at the end of the line
+    fn string(&mut self, quote: char) -> String {
+        let triple = self.reader.peek(1) == Some(quote) && self.reader.peek(2) == Some(quote);
+        let mut value = String::new();
+        
+        for _ in 0..if triple { 3 } else { 1 } {
+            self.reader.bump();
+        }
+        
+        while let Some(c) = self.reader.peek(0) {
+            match c {
+                '\n' if !triple => break,
+                '\\' => {
+                    self.reader.bump();
+                    value.extend(self.reader.bump());
+                }
+                c if c == quote && !triple => {
+                    self.reader.bump();
+                    break;
+                }
+                c if c == quote && self.reader.peek(1) == Some(quote) && self.reader.peek(2) == Some(quote) => {
+                    for _ in 0..3 {
+                        self.reader.bump();
+                    }
+                    break;
+                }
+                c => {
+                    value.push(c);
+                    self.reader.bump();
+                }
+            }
+        }
+        
+        value
+    }
+}
+
+/// Whether an identifier directly followed by a quote is the prefix of a string literal
+fn is_string_prefix(ident: &str) -> bool {
+    matches!(
+        ident.to_ascii_lowercase().as_str(),
+        "r" | "u" | "b" | "f" | "t" | "br" | "rb" | "fr" | "rf" | "tr" | "rt"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use crate::manifest::dependency::{DependencyExtras, DependencySource, DependencyType, PythonExtras};
+    
+    /// Usages found in `source` as `(dependency, line, imported item, usage type)`, given the
+    /// modules each dependency is imported as
+    fn usages(source: &str, owners: &[(&str, &str)]) -> Vec<(String, usize, String, UsageType)> {
+        let mut modules: HashMap<String, Vec<String>> = HashMap::new();
+        let mut usage_data = DependencyUsageData::default();
+        for (module, dep_name) in owners {
+            modules.entry(module.to_string()).or_default().push(dep_name.to_string());
+            usage_data.usage_locations.insert(dep_name.to_string(), Vec::new());
+        }
+        
+        analyze_file(source, Path::new("app.py"), TargetKind::Lib, &modules, &mut usage_data);
+        
+        let mut usages: Vec<_> = usage_data.usage_locations.into_iter()
+            .flat_map(|(dep_name, usages)| usages.into_iter()
+                .map(move |usage| (dep_name.clone(), usage.line, usage.imported_item, usage.usage_type)))
+            .collect();
+        usages.sort_by_key(|(_, line, imported_item, _)| (*line, imported_item.clone()));
+        usages
+    }
+    
+    fn usage(dep_name: &str, line: usize, imported_item: &str, usage_type: UsageType) -> (String, usize, String, UsageType) {
+        (dep_name.to_string(), line, imported_item.to_string(), usage_type)
+    }
+    
+    #[test]
+    fn follows_aliases_and_attribute_chains() {
+        let source = "import numpy as np, os\nfrom requests import (Session,\n    get as fetch)\n\nx = np.linalg.norm(v)\ns = Session()\nfetch(url)\n";
+        
+        assert_eq!(usages(source, &[("numpy", "numpy"), ("requests", "requests")]), vec![
+            usage("numpy", 1, "numpy as np", UsageType::Import),
+            usage("requests", 2, "Session (from requests)", UsageType::Import),
+            usage("requests", 3, "get as fetch (from requests)", UsageType::Import),
+            usage("numpy", 5, "np.linalg.norm() (from numpy)", UsageType::Function),
+            usage("requests", 6, "Session (from requests)", UsageType::Type),
+            usage("requests", 7, "fetch() (from requests)", UsageType::Function),
+        ]);
+    }
+    
+    #[test]
+    fn attributes_namespace_packages_by_longest_module() {
+        let source = "from google.cloud import storage\nclient = storage.Client()\n";
+        let owners = [("google.cloud.storage", "google-cloud-storage"), ("google.protobuf", "protobuf")];
+        
+        assert_eq!(usages(source, &owners), vec![
+            usage("google-cloud-storage", 1, "storage (from google.cloud)", UsageType::Import),
+            usage("google-cloud-storage", 2, "storage.Client (from google.cloud)", UsageType::Type),
+        ]);
+    }
+    
+    #[test]
+    fn finds_imports_only_where_statements_start() {
+        let source = "# import yaml\ntext = \"import yaml\"\ntry: import yaml\nexcept ImportError: pass\nmod = importlib.import_module('yaml.parser')\n__import__(\"yaml\")\n";
+        
+        assert_eq!(usages(source, &[("yaml", "pyyaml")]), vec![
+            usage("pyyaml", 3, "yaml", UsageType::Import),
+            usage("pyyaml", 5, "yaml.parser", UsageType::Import),
+            usage("pyyaml", 6, "yaml", UsageType::Import),
+        ]);
+    }
+    
+    #[test]
+    fn ignores_assignments_keyword_arguments_and_definitions() {
+        let source = "import attr\nf(attr=1)\nobj.attr = 2\ndef attr(): pass\nattr = None\n";
+        
+        assert_eq!(usages(source, &[("attr", "attrs")]), vec![
+            usage("attrs", 1, "attr", UsageType::Import),
+        ]);
+    }
+    
+    #[test]
+    fn maps_distributions_to_modules() {
+        let mut module_map = BTreeMap::new();
+        module_map.insert("Internal_Tools".to_string(), vec!["tools".to_string()]);
+        let analyzer = PyAnalyzer::new(".").unwrap().with_module_map(&module_map);
+        let dependencies: Vec<Dependency> = ["Pillow", "types-requests", "pandas-stubs", "google-cloud-storage", "internal-tools", "typing-extensions"]
+            .iter()
+            .map(|name| Dependency {
+                name: name.to_string(),
+                import_name: default_module_name(name),
+                package: None,
+                version: None,
+                features: Vec::new(),
+                optional: false,
+                dependency_type: DependencyType::Normal,
+                source: DependencySource::default(),
+                extras: DependencyExtras::Python(PythonExtras::default()),
+            })
+            .collect();
+        
+        let owners: BTreeMap<String, Vec<String>> = analyzer.module_owners(&dependencies).into_iter().collect();
+        let owner = |module: &str| owners.get(module).cloned().unwrap_or_default();
+        
+        assert_eq!(owner("PIL"), vec!["Pillow"]);
+        assert_eq!(owner("requests"), vec!["types-requests"]);
+        assert_eq!(owner("pandas"), vec!["pandas-stubs"]);
+        assert_eq!(owner("google.cloud.storage"), vec!["google-cloud-storage"]);
+        assert_eq!(owner("tools"), vec!["internal-tools"]);
+        assert_eq!(owner("typing_extensions"), vec!["typing-extensions"]);
+    }
+    
+    #[test]
+    fn classifies_files_by_path() {
+        assert_eq!(classify(Path::new("tests/helpers.py")), TargetKind::Test);
+        assert_eq!(classify(Path::new("pkg/test_models.py")), TargetKind::Test);
+        assert_eq!(classify(Path::new("pkg/models_test.py")), TargetKind::Test);
+        assert_eq!(classify(Path::new("conftest.py")), TargetKind::Test);
+        assert_eq!(classify(Path::new("benchmarks/run.py")), TargetKind::Bench);
+        assert_eq!(classify(Path::new("examples/demo.py")), TargetKind::Example);
+        assert_eq!(classify(Path::new("setup.py")), TargetKind::Build);
+        assert_eq!(classify(Path::new("pkg/setup.py")), TargetKind::Lib);
+    }
+}
@@ -1,85 +1,181 @@
-use std::collections::{HashMap, HashSet};
-use std::fs;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use anyhow::{Result, Context};
-use walkdir::WalkDir;
+use anyhow::Result;
 use syn::{self, visit::{Visit, self}, parse_file, ItemUse, UseTree, UsePath, UseGroup, UseName};
 use syn::spanned::Spanned;
+use proc_macro2::{Span, TokenStream, TokenTree};
+use syn::punctuated::Punctuated;
+use syn::Token;
 
-use crate::manifest::cargo::CargoDependency;
-use crate::analyzer::{DependencyUsageData, DependencyUsage, UsageType};
+use crate::manifest::cargo::{self, CargoTargets, TargetKind};
+use crate::manifest::dependency::Dependency;
+use crate::analyzer::{DependencyUsageData, DependencyUsage, Exposure, UsageType};
+use crate::analyzer::cfg::{self, Cfg};
+use crate::analyzer::imports::{qualify, ImportResolver, Resolution};
+use crate::analyzer::module_graph::{is_test_only, ModuleGraph, ModuleInfo};
+use crate::analyzer::source::{analyze_files_cached, FileAnalysis};
+use crate::utils::config::Config;
+use crate::utils::fs::FileWalker;
 
 /// Analyzer for Rust code files
 pub struct RustAnalyzer {
     project_path: PathBuf,
+    /// Decides which files below the project path are analyzed
+    walker: FileWalker,
+    /// Number of threads parsing files in parallel (`None` uses one per CPU)
+    threads: Option<usize>,
+    /// File caching per-file results between runs, if caching is enabled
+    cache_file: Option<PathBuf>,
+    /// Targets of the package, deciding which target each file belongs to
+    targets: CargoTargets,
+    /// Whether only files reachable through `mod` declarations from a target root are analyzed
+    follow_modules: bool,
+    /// Methods of traits and preludes that method calls are attributed to, keyed by path
+    trait_methods: BTreeMap<String, Vec<String>>,
 }
 
 impl RustAnalyzer {
-    /// Create a new Rust analyzer for the given project path
-    pub fn new<P: AsRef<Path>>(project_path: P) -> Self {
-        Self {
-            project_path: project_path.as_ref().to_path_buf(),
+    /// Create a new Rust analyzer for the given project path, using the default configuration.
+    /// Targets are read from the project's Cargo.toml when there is one.
+    pub fn new<P: AsRef<Path>>(project_path: P) -> Result<Self> {
+        let project_path = project_path.as_ref().to_path_buf();
+        let walker = FileWalker::new(&project_path, &Config::default())?;
+        
+        let manifest_path = project_path.join("Cargo.toml");
+        let targets = if manifest_path.exists() {
+            CargoTargets::load(&manifest_path)?
+        } else {
+            CargoTargets::default()
+        };
+        
+        Ok(Self {
+            project_path,
+            walker,
+            threads: None,
+            cache_file: None,
+            targets,
+            follow_modules: false,
+            trait_methods: TRAIT_METHODS.iter()
+                .map(|(path, methods)| (path.to_string(), methods.iter().map(|method| method.to_string()).collect()))
+                .collect(),
+        })
+    }
+    
+    /// Discover files with the given walker instead of the default one
+    pub fn with_walker(mut self, walker: FileWalker) -> Self {
+        self.walker = walker;
+        self
+    }
+    
+    /// Parse files with the given number of threads instead of one per CPU
+    pub fn with_threads(mut self, threads: Option<usize>) -> Self {
+        self.threads = threads;
+        self
+    }
+    
+    /// Reuse the results of unchanged files from the cache at `cache_file`, and update it
+    pub fn with_cache(mut self, cache_file: PathBuf) -> Self {
+        self.cache_file = Some(cache_file);
+        self
+    }
+    
+    /// Only analyze files reachable from the package's target roots through `mod` declarations;
+    /// other files are reported as orphans
+    pub fn with_follow_modules(mut self, follow_modules: bool) -> Self {
+        self.follow_modules = follow_modules;
+        self
+    }
+    
+    /// Attribute method calls to the given traits and preludes as well, in addition to the built-in ones
+    pub fn with_trait_methods(mut self, trait_methods: &BTreeMap<String, Vec<String>>) -> Self {
+        for (path, methods) in trait_methods {
+            let known = self.trait_methods.entry(path.clone()).or_default();
+            for method in methods {
+                if !known.contains(method) {
+                    known.push(method.clone());
+                }
+            }
         }
+        self
     }
     
     /// Analyze Rust code to detect dependency usage
-    pub fn analyze(&self, dependencies: &[CargoDependency]) -> Result<DependencyUsageData> {
-        let mut usage_data = DependencyUsageData::default();
+    pub fn analyze(&self, dependencies: &[Dependency]) -> Result<DependencyUsageData> {
+        // Find all Rust files in the project, in a stable order
+        let files = self.walker.files(&self.project_path, "rs");
         
-        // Initialize usage locations for all dependencies
-        for dep in dependencies {
-            usage_data.usage_locations.insert(dep.name.clone(), Vec::new());
-        }
+        // Files outside the module tree are dead code, fixtures or belong to other crates
+        let module_graph = self.follow_modules.then(|| ModuleGraph::build(&self.targets));
+        let (files, orphan_files): (Vec<PathBuf>, Vec<PathBuf>) = match &module_graph {
+            Some(graph) => files.into_iter().partition(|file| graph.get(file).is_some()),
+            None => (files, Vec::new()),
+        };
         
-        // Find all Rust files in the project
-        for entry in WalkDir::new(&self.project_path)
-            .into_iter()
-            .filter_entry(|e| !Self::is_excluded(e.path()))
-            .filter_map(Result::ok)
-            .filter(|e| e.path().extension().map_or(false, |ext| ext == "rs"))
-        {
-            let file_path = entry.path();
-            self.analyze_file(file_path, dependencies, &mut usage_data)?;
-        }
+        let module_of = |file_path: &Path| match module_graph.as_ref().and_then(|graph| graph.get(file_path)) {
+            Some(module) => module.clone(),
+            None => {
+                let target = self.targets.classify(file_path);
+                ModuleInfo {
+                    target,
+                    module: String::new(),
+                    cfg: None,
+                    // Without the module tree, assume every `pub` item of the library is exported
+                    public: target == TargetKind::Lib,
+                }
+            }
+        };
+        let options = FileAnalysis {
+            project_path: &self.project_path,
+            threads: self.threads,
+            cache_file: self.cache_file.as_deref(),
+        };
+        
+        let mut usage_data = analyze_files_cached(&files, dependencies, &self.trait_methods, &options, module_of, |file_content, file_path, module, file_data| {
+            file_data.cfg_features.extend(cfg::referenced_features(file_content));
+            self.analyze_file(file_content, file_path, module, dependencies, file_data);
+        })?;
+        usage_data.orphan_files = orphan_files;
         
         Ok(usage_data)
     }
     
-    /// Determine if a path should be excluded from analysis
-    fn is_excluded(path: &Path) -> bool {
-        let path_str = path.to_string_lossy();
-        path_str.contains("/target/") || path_str.contains("/.git/")
-    }
-    
-    /// Analyze a single Rust file for dependency usage
+    /// Analyze the content of a single Rust file for dependency usage
     fn analyze_file(
         &self,
+        file_content: &str,
         file_path: &Path,
-        dependencies: &[CargoDependency],
+        module: &ModuleInfo,
+        dependencies: &[Dependency],
         usage_data: &mut DependencyUsageData,
-    ) -> Result<()> {
-        let file_content = fs::read_to_string(file_path)
-            .with_context(|| format!("Failed to read file: {:?}", file_path))?;
-        
+    ) {
         // Advanced approach: parse the file to an AST and use a visitor to analyze dependency usage
-        match parse_file(&file_content) {
+        match parse_file(file_content) {
             Ok(file) => {
+                let module_path: Vec<String> = module.module.split("::").filter(|name| !name.is_empty()).map(str::to_string).collect();
                 let mut visitor = RustDependencyVisitor {
                     file_path: file_path.to_path_buf(),
+                    target: module.target,
+                    module: module_path.clone(),
                     dependencies,
                     usage_data,
-                    current_imports: HashMap::new(),
+                    imports: ImportResolver::new(&self.trait_methods, module_path),
+                    macro_depth: 0,
+                    derive_scopes: Vec::new(),
+                    test_depth: 0,
+                    cfgs: module.cfg.iter().cloned().collect(),
+                    public: module.public,
+                    exposure: None,
+                    public_types: HashSet::new(),
+                    exported_members: ExportedMembers::None,
                 };
                 visitor.visit_file(&file);
             }
             Err(err) => {
                 // Fall back to simple text-based parsing if AST parsing fails
                 eprintln!("Warning: Failed to parse file {:?}: {}", file_path, err);
-                self.analyze_file_simple(&file_content, file_path, dependencies, usage_data);
+                self.analyze_file_simple(file_content, file_path, module, dependencies, usage_data);
             }
         }
-        
-        Ok(())
     }
     
     /// Simple text-based analysis fallback
@@ -87,19 +183,20 @@ impl RustAnalyzer {
         &self,
         file_content: &str,
         file_path: &Path,
-        dependencies: &[CargoDependency],
+        module: &ModuleInfo,
+        dependencies: &[Dependency],
         usage_data: &mut DependencyUsageData,
     ) {
         // Track line numbers
         for (line_number, line) in file_content.lines().enumerate() {
             // Check for use statements
             if line.trim().starts_with("use ") {
-                self.process_use_statement(line, line_number + 1, file_path, dependencies, usage_data);
+                self.process_use_statement(line, line_number + 1, file_path, module, dependencies, usage_data);
             }
             
             // Check for extern crate statements
             if line.trim().starts_with("extern crate ") {
-                self.process_extern_crate(line, line_number + 1, file_path, dependencies, usage_data);
+                self.process_extern_crate(line, line_number + 1, file_path, module, dependencies, usage_data);
             }
         }
     }
@@ -110,22 +207,35 @@ impl RustAnalyzer {
         line: &str, 
         line_number: usize, 
         file_path: &Path,
-        dependencies: &[CargoDependency],
+        module: &ModuleInfo,
+        dependencies: &[Dependency],
         usage_data: &mut DependencyUsageData,
     ) {
+        let (column, end_column) = line_columns(line);
+        
         // Extract the first part of the use statement
         let line = line.trim().trim_start_matches("use ");
         let first_part = line.split("::").next().unwrap_or("");
         
         // Check if this matches a dependency
         for dep in dependencies {
-            if first_part == dep.name {
+            if first_part == dep.import_name {
                 if let Some(usages) = usage_data.usage_locations.get_mut(&dep.name) {
                     usages.push(DependencyUsage {
                         file: file_path.to_path_buf(),
                         line: line_number,
+                        column,
+                        end_line: line_number,
+                        end_column,
                         imported_item: line.trim_end_matches(';').to_owned(),
+                        item: None,
                         usage_type: UsageType::Import,
+                        target: module.target,
+                        module: module.module.clone(),
+                        cfg: module.cfg.clone(),
+                        inactive: false,
+                        in_macro: false,
+                        exposure: None,
                     });
                 }
             }
@@ -138,22 +248,35 @@ impl RustAnalyzer {
         line: &str, 
         line_number: usize, 
         file_path: &Path,
-        dependencies: &[CargoDependency],
+        module: &ModuleInfo,
+        dependencies: &[Dependency],
         usage_data: &mut DependencyUsageData,
     ) {
+        let (column, end_column) = line_columns(line);
+        
         // Extract the crate name
         let line = line.trim().trim_start_matches("extern crate ");
         let crate_name = line.split_whitespace().next().unwrap_or("").trim_end_matches(';');
         
         // Check if this matches a dependency
         for dep in dependencies {
-            if crate_name == dep.name {
+            if crate_name == dep.import_name {
                 if let Some(usages) = usage_data.usage_locations.get_mut(&dep.name) {
                     usages.push(DependencyUsage {
                         file: file_path.to_path_buf(),
                         line: line_number,
+                        column,
+                        end_line: line_number,
+                        end_column,
                         imported_item: crate_name.to_owned(),
+                        item: None,
                         usage_type: UsageType::Import,
+                        target: module.target,
+                        module: module.module.clone(),
+                        cfg: module.cfg.clone(),
+                        inactive: false,
+                        in_macro: false,
+                        exposure: None,
                     });
                 }
             }
@@ -164,213 +287,1062 @@ impl RustAnalyzer {
 /// AST visitor to extract dependency usage information
 struct RustDependencyVisitor<'a> {
     file_path: PathBuf,
-    dependencies: &'a [CargoDependency],
+    /// Target the visited file belongs to
+    target: TargetKind,
+    /// Module path of the visited item, empty when module paths are not known
+    module: Vec<String>,
+    dependencies: &'a [Dependency],
     usage_data: &'a mut DependencyUsageData,
-    current_imports: HashMap<String, String>, // Maps local name to fully qualified name
+    /// Imports, local items and variables of the enclosing scopes
+    imports: ImportResolver<'a>,
+    /// Number of macro invocations whose arguments are currently being visited
+    macro_depth: usize,
+    /// Crates deriving traits on each enclosing struct, enum or union, for derive helper attributes
+    derive_scopes: Vec<Vec<String>>,
+    /// Number of enclosing items only compiled for tests, such as `#[cfg(test)]` modules
+    test_depth: usize,
+    /// cfg predicates of the enclosing modules and items
+    cfgs: Vec<Cfg>,
+    /// Whether `pub` items of the current module are part of the library's public API
+    public: bool,
+    /// How the usages being visited appear in the public API
+    exposure: Option<Exposure>,
+    /// Names of the `pub` types declared in the file, whose trait impls are public
+    public_types: HashSet<String>,
+    /// Members of the enclosing struct, enum, union, impl or trait that are public API
+    exported_members: ExportedMembers,
+}
+
+/// Which members of an item are part of the public API
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ExportedMembers {
+    None,
+    /// Members declared `pub`: fields of a public struct, methods of an inherent impl on a public type
+    Public,
+    /// Every member: fields of a public enum's variants, items of a public trait
+    All,
 }
 
 impl<'a, 'ast> Visit<'ast> for RustDependencyVisitor<'a> {
+    fn visit_file(&mut self, node: &'ast syn::File) {
+        // Trait impls may come before the type they are for
+        collect_public_types(&node.items, &mut self.public_types);
+        
+        // Items of a macro invocation are declared in the scope of the invocation
+        if self.imports.is_empty() {
+            self.imports.enter_module(None);
+        } else {
+            self.imports.enter_scope();
+        }
+        self.imports.declare_items(&node.items);
+        
+        // Inner `#![cfg(...)]` attributes apply to the whole file
+        let cfgs = self.enter_cfgs(&node.attrs);
+        visit::visit_file(self, node);
+        self.exit_cfgs(cfgs);
+        self.imports.exit();
+    }
+    
+    fn visit_item(&mut self, node: &'ast syn::Item) {
+        // Usages inside test-only items belong to the test target, whatever file they are in
+        let test_only = item_attrs(node).iter().any(|attr| is_test_only(&attr.meta));
+        
+        if test_only {
+            self.test_depth += 1;
+        }
+        let cfgs = self.enter_cfgs(item_attrs(node));
+        visit::visit_item(self, node);
+        self.exit_cfgs(cfgs);
+        if test_only {
+            self.test_depth -= 1;
+        }
+    }
+    
+    fn visit_impl_item(&mut self, node: &'ast syn::ImplItem) {
+        let attrs = match node {
+            syn::ImplItem::Const(item) => &item.attrs,
+            syn::ImplItem::Fn(item) => &item.attrs,
+            syn::ImplItem::Type(item) => &item.attrs,
+            syn::ImplItem::Macro(item) => &item.attrs,
+            _ => &[][..],
+        };
+        
+        let cfgs = self.enter_cfgs(attrs);
+        visit::visit_impl_item(self, node);
+        self.exit_cfgs(cfgs);
+    }
+    
+    fn visit_trait_item(&mut self, node: &'ast syn::TraitItem) {
+        let attrs = match node {
+            syn::TraitItem::Const(item) => &item.attrs,
+            syn::TraitItem::Fn(item) => &item.attrs,
+            syn::TraitItem::Type(item) => &item.attrs,
+            syn::TraitItem::Macro(item) => &item.attrs,
+            _ => &[][..],
+        };
+        
+        let cfgs = self.enter_cfgs(attrs);
+        visit::visit_trait_item(self, node);
+        self.exit_cfgs(cfgs);
+    }
+    
+    fn visit_stmt(&mut self, node: &'ast syn::Stmt) {
+        let attrs = match node {
+            syn::Stmt::Local(local) => &local.attrs,
+            syn::Stmt::Macro(mac) => &mac.attrs,
+            _ => &[][..],
+        };
+        
+        let cfgs = self.enter_cfgs(attrs);
+        visit::visit_stmt(self, node);
+        self.exit_cfgs(cfgs);
+    }
+    
+    fn visit_arm(&mut self, node: &'ast syn::Arm) {
+        let cfgs = self.enter_cfgs(&node.attrs);
+        self.imports.enter_scope();
+        self.imports.bind(&node.pat);
+        visit::visit_arm(self, node);
+        self.imports.exit();
+        self.exit_cfgs(cfgs);
+    }
+    
+    fn visit_local(&mut self, node: &'ast syn::Local) {
+        // The variables are only in scope after the initializer
+        visit::visit_local(self, node);
+        self.imports.bind(&node.pat);
+    }
+    
+    fn visit_expr_let(&mut self, node: &'ast syn::ExprLet) {
+        visit::visit_expr_let(self, node);
+        self.imports.bind(&node.pat);
+    }
+    
+    fn visit_expr_if(&mut self, node: &'ast syn::ExprIf) {
+        // Variables bound by `if let` are scoped to the expression
+        self.imports.enter_scope();
+        visit::visit_expr_if(self, node);
+        self.imports.exit();
+    }
+    
+    fn visit_expr_while(&mut self, node: &'ast syn::ExprWhile) {
+        self.imports.enter_scope();
+        visit::visit_expr_while(self, node);
+        self.imports.exit();
+    }
+    
+    fn visit_expr_for_loop(&mut self, node: &'ast syn::ExprForLoop) {
+        for attr in &node.attrs {
+            self.visit_attribute(attr);
+        }
+        self.visit_expr(&node.expr);
+        self.imports.enter_scope();
+        self.imports.bind(&node.pat);
+        self.visit_pat(&node.pat);
+        self.visit_block(&node.body);
+        self.imports.exit();
+    }
+    
+    fn visit_expr_closure(&mut self, node: &'ast syn::ExprClosure) {
+        self.imports.enter_scope();
+        for input in &node.inputs {
+            self.imports.bind(input);
+        }
+        visit::visit_expr_closure(self, node);
+        self.imports.exit();
+    }
+    
+    fn visit_field(&mut self, node: &'ast syn::Field) {
+        let cfgs = self.enter_cfgs(&node.attrs);
+        if self.is_exported_member(&node.vis) {
+            for attr in &node.attrs {
+                self.visit_attribute(attr);
+            }
+            self.visit_exposed(Exposure::Field, |visitor| visitor.visit_type(&node.ty));
+        } else {
+            visit::visit_field(self, node);
+        }
+        self.exit_cfgs(cfgs);
+    }
+    
+    fn visit_variant(&mut self, node: &'ast syn::Variant) {
+        let cfgs = self.enter_cfgs(&node.attrs);
+        visit::visit_variant(self, node);
+        self.exit_cfgs(cfgs);
+    }
+    
+    fn visit_item_mod(&mut self, node: &'ast syn::ItemMod) {
+        // Inline modules extend the module path of the file
+        let inline = node.content.is_some() && !self.module.is_empty();
+        let public = self.public;
+        
+        if inline {
+            self.module.push(node.ident.to_string().trim_start_matches("r#").to_string());
+        }
+        self.public = self.is_exported(&node.vis);
+        // Inline modules only see what they declare and import themselves
+        if let Some((_, items)) = &node.content {
+            self.imports.enter_module(Some(node.ident.to_string().trim_start_matches("r#")));
+            self.imports.declare_items(items);
+        }
+        visit::visit_item_mod(self, node);
+        if node.content.is_some() {
+            self.imports.exit();
+        }
+        self.public = public;
+        if inline {
+            self.module.pop();
+        }
+    }
+    
     fn visit_item_use(&mut self, node: &'ast ItemUse) {
-        // Process imports and update current_imports map
-        // For simplicity, we're using line 0 as we can't easily get the line number
-        // In a real implementation, you would extract the line number properly
-        let line = 0;
-        self.process_use_tree(&node.tree, "", line);
+        // Imports were declared on entering the scope, this records their usages
+        let leading_colon = node.leading_colon.is_some();
+        if self.is_exported(&node.vis) {
+            self.visit_exposed(Exposure::ReExport, |visitor| visitor.process_use_tree(&node.tree, &mut Vec::new(), leading_colon));
+        } else {
+            self.process_use_tree(&node.tree, &mut Vec::new(), leading_colon);
+        }
         
         // Continue visiting
         visit::visit_item_use(self, node);
     }
     
-    fn visit_macro(&mut self, node: &'ast syn::Macro) {
-        // Detect macro usage
-        if let Some(segment) = node.path.segments.first() {
-            let macro_name = segment.ident.to_string();
-            
-            // Check if this macro is from a tracked dependency
-            for (local_name, full_path) in &self.current_imports {
-                if macro_name == *local_name {
-                    // Extract the crate name from the full path
-                    let crate_name = full_path.split("::").next().unwrap_or("");
-                    
-                    for dep in self.dependencies {
-                        if crate_name == dep.name {
-                            if let Some(usages) = self.usage_data.usage_locations.get_mut(&dep.name) {
-                                // Get span info, defaulting to line 0 if unavailable
-                                let line = 0; // In a real implementation, extract the correct line number
-                                
-                                usages.push(DependencyUsage {
-                                    file: self.file_path.clone(),
-                                    line,
-                                    imported_item: format!("{}!", macro_name),
-                                    usage_type: UsageType::Macro,
-                                });
-                            }
-                        }
-                    }
+    fn visit_block(&mut self, node: &'ast syn::Block) {
+        // Items declared in blocks are never reachable from outside
+        let public = std::mem::replace(&mut self.public, false);
+        let members = std::mem::replace(&mut self.exported_members, ExportedMembers::None);
+        // Items and imports of a block are only in scope within it
+        self.imports.enter_scope();
+        self.imports.declare_items(node.stmts.iter().filter_map(|stmt| match stmt {
+            syn::Stmt::Item(item) => Some(item),
+            _ => None,
+        }));
+        visit::visit_block(self, node);
+        self.imports.exit();
+        self.public = public;
+        self.exported_members = members;
+    }
+    
+    fn visit_item_fn(&mut self, node: &'ast syn::ItemFn) {
+        let exported = self.is_exported(&node.vis);
+        self.visit_fn(&node.attrs, &node.sig, Some(&node.block), exported);
+    }
+    
+    fn visit_impl_item_fn(&mut self, node: &'ast syn::ImplItemFn) {
+        let exported = self.is_exported_member(&node.vis);
+        self.visit_fn(&node.attrs, &node.sig, Some(&node.block), exported);
+    }
+    
+    fn visit_trait_item_fn(&mut self, node: &'ast syn::TraitItemFn) {
+        let exported = self.exported_members == ExportedMembers::All;
+        self.visit_fn(&node.attrs, &node.sig, node.default.as_ref(), exported);
+    }
+    
+    fn visit_item_impl(&mut self, node: &'ast syn::ItemImpl) {
+        let public_type = self.public && self.test_depth == 0
+            && type_name(&node.self_ty).is_some_and(|name| self.public_types.contains(&name));
+        
+        // Methods of trait impls are part of the trait, not of the type
+        let members = if public_type && node.trait_.is_none() { ExportedMembers::Public } else { ExportedMembers::None };
+        let outer = std::mem::replace(&mut self.exported_members, members);
+        
+        match &node.trait_ {
+            Some((_, trait_path, _)) if public_type => {
+                for attr in &node.attrs {
+                    self.visit_attribute(attr);
+                }
+                self.visit_generics(&node.generics);
+                self.visit_exposed(Exposure::TraitImpl, |visitor| visitor.visit_path(trait_path));
+                self.visit_type(&node.self_ty);
+                for item in &node.items {
+                    self.visit_impl_item(item);
+                }
+            }
+            _ => visit::visit_item_impl(self, node),
+        }
+        
+        self.exported_members = outer;
+    }
+    
+    fn visit_item_trait(&mut self, node: &'ast syn::ItemTrait) {
+        let exported = self.is_exported(&node.vis);
+        let members = if exported { ExportedMembers::All } else { ExportedMembers::None };
+        let outer = std::mem::replace(&mut self.exported_members, members);
+        
+        if exported {
+            for attr in &node.attrs {
+                self.visit_attribute(attr);
+            }
+            self.visit_generics(&node.generics);
+            self.visit_exposed(Exposure::Signature, |visitor| {
+                for bound in &node.supertraits {
+                    visitor.visit_type_param_bound(bound);
                 }
+            });
+            for item in &node.items {
+                self.visit_trait_item(item);
+            }
+        } else {
+            visit::visit_item_trait(self, node);
+        }
+        
+        self.exported_members = outer;
+    }
+    
+    fn visit_item_type(&mut self, node: &'ast syn::ItemType) {
+        if self.is_exported(&node.vis) {
+            for attr in &node.attrs {
+                self.visit_attribute(attr);
+            }
+            self.visit_generics(&node.generics);
+            self.visit_exposed(Exposure::Signature, |visitor| visitor.visit_type(&node.ty));
+        } else {
+            visit::visit_item_type(self, node);
+        }
+    }
+    
+    fn visit_item_const(&mut self, node: &'ast syn::ItemConst) {
+        if self.is_exported(&node.vis) {
+            for attr in &node.attrs {
+                self.visit_attribute(attr);
+            }
+            self.visit_exposed(Exposure::Signature, |visitor| visitor.visit_type(&node.ty));
+            self.visit_expr(&node.expr);
+        } else {
+            visit::visit_item_const(self, node);
+        }
+    }
+    
+    fn visit_item_static(&mut self, node: &'ast syn::ItemStatic) {
+        if self.is_exported(&node.vis) {
+            for attr in &node.attrs {
+                self.visit_attribute(attr);
             }
+            self.visit_exposed(Exposure::Signature, |visitor| visitor.visit_type(&node.ty));
+            self.visit_expr(&node.expr);
+        } else {
+            visit::visit_item_static(self, node);
+        }
+    }
+    
+    fn visit_attribute(&mut self, node: &'ast syn::Attribute) {
+        // Attribute paths are recorded as derives or attributes rather than as plain paths
+        self.process_attribute_meta(&node.meta);
+    }
+    
+    fn visit_item_struct(&mut self, node: &'ast syn::ItemStruct) {
+        let members = if self.is_exported(&node.vis) { ExportedMembers::Public } else { ExportedMembers::None };
+        let outer = std::mem::replace(&mut self.exported_members, members);
+        self.derive_scopes.push(self.derive_crates(&node.attrs));
+        visit::visit_item_struct(self, node);
+        self.derive_scopes.pop();
+        self.exported_members = outer;
+    }
+    
+    fn visit_item_enum(&mut self, node: &'ast syn::ItemEnum) {
+        let members = if self.is_exported(&node.vis) { ExportedMembers::All } else { ExportedMembers::None };
+        let outer = std::mem::replace(&mut self.exported_members, members);
+        self.derive_scopes.push(self.derive_crates(&node.attrs));
+        visit::visit_item_enum(self, node);
+        self.derive_scopes.pop();
+        self.exported_members = outer;
+    }
+    
+    fn visit_item_union(&mut self, node: &'ast syn::ItemUnion) {
+        let members = if self.is_exported(&node.vis) { ExportedMembers::Public } else { ExportedMembers::None };
+        let outer = std::mem::replace(&mut self.exported_members, members);
+        self.derive_scopes.push(self.derive_crates(&node.attrs));
+        visit::visit_item_union(self, node);
+        self.derive_scopes.pop();
+        self.exported_members = outer;
+    }
+    
+    fn visit_macro(&mut self, node: &'ast syn::Macro) {
+        // Detect usage of imported macros
+        let segments = path_segments(&node.path);
+        if let Resolution::Import(full_path, consumed) = self.imports.resolve(&segments, node.path.leading_colon.is_some(), false) {
+            let crate_name = full_path.split("::").next().unwrap_or("");
+            self.record_usage(crate_name, node.span(), format!("{}!", segments.join("::")), Some(qualify(&full_path, &segments[consumed..])), UsageType::Macro);
         }
         
         // Continue visiting
         visit::visit_macro(self, node);
+        
+        // syn leaves macro arguments as raw tokens, so look inside them separately
+        self.macro_depth += 1;
+        self.visit_macro_tokens(node);
+        self.macro_depth -= 1;
     }
     
     fn visit_path(&mut self, node: &'ast syn::Path) {
         // Check if this path refers to a tracked dependency
-        if let Some(segment) = node.segments.first() {
-            let name = segment.ident.to_string();
-            
+        let segments = path_segments(node);
+        let path_str = segments.join("::");
+        match self.imports.resolve(&segments, node.leading_colon.is_some(), true) {
             // Direct usage of dependencies (e.g., some_crate::func())
-            for dep in self.dependencies {
-                if name == dep.name {
-                    if let Some(usages) = self.usage_data.usage_locations.get_mut(&dep.name) {
-                        let path_str = path_to_string(node);
-                        let usage_type = determine_usage_type(node);
-                        
-                        // Use 0 as placeholder for line number
-                        let line = 0; // In a real implementation, extract the correct line number
-                        
-                        usages.push(DependencyUsage {
-                            file: self.file_path.clone(),
-                            line,
-                            imported_item: path_str,
-                            usage_type,
-                        });
-                    }
-                }
+            Resolution::Crate if segments.len() > 1 => {
+                self.record_usage(&segments[0], node.span(), path_str.clone(), Some(path_str), determine_usage_type(node));
             }
-            
             // Usage through imports (e.g., use some_crate::Thing; ... Thing::new())
-            if let Some(full_path) = self.current_imports.get(&name) {
+            Resolution::Import(full_path, consumed) => {
                 let crate_name = full_path.split("::").next().unwrap_or("");
                 
-                for dep in self.dependencies {
-                    if crate_name == dep.name {
-                        if let Some(usages) = self.usage_data.usage_locations.get_mut(&dep.name) {
-                            let path_str = path_to_string(node);
-                            let usage_type = determine_usage_type(node);
-                            
-                            // Use 0 as placeholder for line number
-                            let line = 0; // In a real implementation, extract the correct line number
-                            
-                            usages.push(DependencyUsage {
-                                file: self.file_path.clone(),
-                                line,
-                                imported_item: format!("{} (from {})", path_str, full_path),
-                                usage_type,
-                            });
-                        }
-                    }
-                }
+                self.record_usage(
+                    crate_name,
+                    node.span(),
+                    format!("{} (from {})", path_str, full_path),
+                    Some(qualify(&full_path, &segments[consumed..])),
+                    determine_usage_type(node),
+                );
             }
+            // Local items and variables, and lone crate names
+            _ => {}
         }
         
         // Continue visiting
         visit::visit_path(self, node);
     }
+    
+    fn visit_expr_method_call(&mut self, node: &'ast syn::ExprMethodCall) {
+        // Methods of imported traits (`.par_iter()`, `.context(...)`) never name their crate
+        let method = node.method.to_string().trim_start_matches("r#").to_string();
+        let mut crates = HashSet::new();
+        
+        for path in self.imports.traits_providing(&method) {
+            // Several traits of one crate may provide the method, e.g. through a prelude
+            let crate_name = path.split("::").next().unwrap_or("").to_string();
+            if crates.insert(crate_name.clone()) {
+                self.record_usage(
+                    &crate_name,
+                    node.method.span(),
+                    format!(".{}() (from {})", method, path),
+                    Some(format!("{}::{}", path, method)),
+                    UsageType::Trait,
+                );
+            }
+        }
+        
+        visit::visit_expr_method_call(self, node);
+    }
 }
 
 impl<'a> RustDependencyVisitor<'a> {
-    /// Process a use tree to extract import information
-    fn process_use_tree(&mut self, tree: &UseTree, prefix: &str, line: usize) {
+    /// Record a usage of `crate_name` at the given span if it is a tracked dependency, or as a
+    /// usage of an unknown crate if it may be one. `crate_name` is the identifier used in code,
+    /// which may differ from the manifest name; `item` is the fully qualified path of the used item,
+    /// if the usage names one.
+    fn record_usage(&mut self, crate_name: &str, span: Span, imported_item: String, item: Option<String>, usage_type: UsageType) {
+        let usages = match self.dependencies.iter().find(|dep| dep.import_name == crate_name) {
+            Some(dep) => match self.usage_data.usage_locations.get_mut(&dep.name) {
+                Some(usages) => usages,
+                None => return,
+            },
+            // Possibly a registry dependency whose library is not named after its package
+            None if may_name_crate(crate_name) => self.usage_data.unknown_crates.entry(crate_name.to_string()).or_default(),
+            None => return,
+        };
+        
+        // proc-macro2 reports 1-based lines and 0-based columns
+        let start = span.start();
+        let end = span.end();
+        
+        usages.push(DependencyUsage {
+            file: self.file_path.clone(),
+            line: start.line,
+            column: start.column + 1,
+            end_line: end.line,
+            end_column: end.column + 1,
+            imported_item,
+            item,
+            usage_type,
+            target: if self.test_depth > 0 { TargetKind::Test } else { self.target },
+            module: self.module.join("::"),
+            cfg: Cfg::all_of(&self.cfgs),
+            inactive: false,
+            in_macro: self.macro_depth > 0,
+            exposure: self.exposure,
+        });
+    }
+    
+    /// Whether an item with visibility `vis` in the current module is part of the public API
+    fn is_exported(&self, vis: &syn::Visibility) -> bool {
+        self.public && self.test_depth == 0 && matches!(vis, syn::Visibility::Public(_))
+    }
+    
+    /// Whether a member of the enclosing item with visibility `vis` is part of the public API
+    fn is_exported_member(&self, vis: &syn::Visibility) -> bool {
+        match self.exported_members {
+            ExportedMembers::None => false,
+            ExportedMembers::Public => matches!(vis, syn::Visibility::Public(_)),
+            ExportedMembers::All => true,
+        }
+    }
+    
+    /// Visit with the usages found marked as exposed in the public API
+    fn visit_exposed(&mut self, exposure: Exposure, visit: impl FnOnce(&mut Self)) {
+        let outer = self.exposure.replace(exposure);
+        visit(self);
+        self.exposure = outer;
+    }
+    
+    /// Visit a function, treating its signature as public API when `exported`
+    fn visit_fn(&mut self, attrs: &[syn::Attribute], sig: &syn::Signature, block: Option<&syn::Block>, exported: bool) {
+        for attr in attrs {
+            self.visit_attribute(attr);
+        }
+        
+        // Parameters are local variables of the function
+        self.imports.enter_scope();
+        for input in &sig.inputs {
+            if let syn::FnArg::Typed(arg) = input {
+                self.imports.bind(&arg.pat);
+            }
+        }
+        if exported {
+            self.visit_exposed(Exposure::Signature, |visitor| visitor.visit_signature(sig));
+        } else {
+            self.visit_signature(sig);
+        }
+        if let Some(block) = block {
+            self.visit_block(block);
+        }
+        self.imports.exit();
+    }
+    
+    /// Push the `#[cfg(...)]` predicates among `attrs`, returning how many were pushed
+    fn enter_cfgs(&mut self, attrs: &[syn::Attribute]) -> usize {
+        let before = self.cfgs.len();
+        self.cfgs.extend(attrs.iter().filter_map(|attr| Cfg::from_attribute(&attr.meta)));
+        self.cfgs.len() - before
+    }
+    
+    /// Pop the predicates pushed by `enter_cfgs`
+    fn exit_cfgs(&mut self, count: usize) {
+        self.cfgs.truncate(self.cfgs.len() - count);
+    }
+    
+    /// Record the derives, attribute macros and derive helpers of an attribute
+    fn process_attribute_meta(&mut self, meta: &syn::Meta) {
+        let path = meta.path();
+        
+        if path.is_ident("derive") {
+            // Only structs, enums and unions take derives, so exported members mean a public type
+            let outer = self.exposure;
+            if self.exported_members != ExportedMembers::None {
+                self.exposure = Some(Exposure::TraitImpl);
+            }
+            for derive in derive_paths(meta) {
+                if let Some((crate_name, item, path)) = self.resolve_attribute_path(&derive) {
+                    self.record_usage(&crate_name, derive.span(), item, Some(path), UsageType::Derive);
+                }
+            }
+            self.exposure = outer;
+            return;
+        }
+        
+        // `#[cfg_attr(predicate, attr, ...)]` applies the attributes after the predicate
+        if path.is_ident("cfg_attr") {
+            if let syn::Meta::List(list) = meta {
+                if let Ok(metas) = list.parse_args_with(Punctuated::<syn::Meta, Token![,]>::parse_terminated) {
+                    let cfg = metas.first().and_then(Cfg::from_meta);
+                    let pushed = cfg.is_some();
+                    self.cfgs.extend(cfg);
+                    
+                    for meta in metas.iter().skip(1) {
+                        self.process_attribute_meta(meta);
+                    }
+                    
+                    if pushed {
+                        self.cfgs.pop();
+                    }
+                }
+            }
+            return;
+        }
+        
+        if let Some((crate_name, item, qualified)) = self.resolve_attribute_path(path) {
+            // `#[async_trait] (from async_trait::async_trait)` reads better than wrapping the whole item
+            let item = match item.split_once(' ') {
+                Some((name, origin)) => format!("#[{}] {}", name, origin),
+                None => format!("#[{}]", item),
+            };
+            self.record_usage(&crate_name, path.span(), item, Some(qualified), UsageType::Attribute);
+        } else if let Some(crate_name) = self.derive_helper_crate(path) {
+            // Helper attributes are inert and not items of the crate
+            self.record_usage(&crate_name, path.span(), format!("#[{}] (derive helper)", path_to_string(path)), None, UsageType::Attribute);
+        }
+        
+        // Values such as `#[doc = include_str!(...)]` can still use dependencies
+        if let syn::Meta::NameValue(name_value) = meta {
+            self.visit_expr(&name_value.value);
+        }
+    }
+    
+    /// Resolve the crate of a derive or attribute path, either written out
+    /// (`serde::Serialize`, `tokio::main`) or imported (`Serialize`, `async_trait`),
+    /// along with how to display it and its fully qualified path
+    fn resolve_attribute_path(&self, path: &syn::Path) -> Option<(String, String, String)> {
+        let segments = path_segments(path);
+        let path_str = segments.join("::");
+        
+        match self.imports.resolve(&segments, path.leading_colon.is_some(), false) {
+            Resolution::Crate if segments.len() > 1 => Some((segments[0].clone(), path_str.clone(), path_str)),
+            Resolution::Import(full_path, consumed) => {
+                let crate_name = full_path.split("::").next().unwrap_or("").to_string();
+                Some((crate_name, format!("{} (from {})", path_str, full_path), qualify(&full_path, &segments[consumed..])))
+            }
+            _ => None,
+        }
+    }
+    
+    /// Find the crate whose derive introduced a helper attribute such as `#[serde(...)]` or
+    /// `#[error(...)]`, among the crates deriving on the enclosing item
+    fn derive_helper_crate(&self, path: &syn::Path) -> Option<String> {
+        let name = path.get_ident()?.to_string();
+        let derived = self.derive_scopes.last()?;
+        
+        derived.iter()
+            .find(|crate_name| {
+                **crate_name == name
+                    || DERIVE_HELPERS.iter().any(|(helper, owner)| *helper == name && owner == crate_name)
+            })
+            .cloned()
+    }
+    
+    /// Crates providing the derives of an item, including those applied through `cfg_attr`
+    fn derive_crates(&self, attrs: &[syn::Attribute]) -> Vec<String> {
+        let mut crates = Vec::new();
+        let mut metas: Vec<syn::Meta> = attrs.iter().map(|attr| attr.meta.clone()).collect();
+        
+        while let Some(meta) = metas.pop() {
+            if meta.path().is_ident("derive") {
+                crates.extend(derive_paths(&meta)
+                    .iter()
+                    .filter_map(|derive| self.resolve_attribute_path(derive))
+                    .map(|(crate_name, _, _)| crate_name));
+            } else if meta.path().is_ident("cfg_attr") {
+                if let syn::Meta::List(list) = &meta {
+                    if let Ok(nested) = list.parse_args_with(Punctuated::<syn::Meta, Token![,]>::parse_terminated) {
+                        metas.extend(nested.into_iter().skip(1));
+                    }
+                }
+            }
+        }
+        
+        crates
+    }
+    
+    /// Find usages in the arguments of a macro invocation. The tokens are parsed as
+    /// comma-separated expressions (`format!`, `vec!`, `json!`, ...) or as items where
+    /// possible; anything else is scanned for crate paths token by token.
+    fn visit_macro_tokens(&mut self, node: &syn::Macro) {
+        if let Ok(exprs) = node.parse_body_with(Punctuated::<syn::Expr, Token![,]>::parse_terminated) {
+            for expr in &exprs {
+                self.visit_expr(expr);
+            }
+            return;
+        }
+        
+        if let Ok(file) = syn::parse2::<syn::File>(node.tokens.clone()) {
+            // Items in macros are visited in the scope of the invocation
+            self.visit_file(&file);
+            return;
+        }
+        
+        self.scan_tokens(node.tokens.clone());
+    }
+    
+    /// Record `crate::path` sequences and imported names found in raw tokens
+    fn scan_tokens(&mut self, tokens: TokenStream) {
+        let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+        let mut i = 0;
+        
+        while i < tokens.len() {
+            match &tokens[i] {
+                TokenTree::Group(group) => self.scan_tokens(group.stream()),
+                TokenTree::Ident(ident) => {
+                    // Collect the whole `a::b::c` path starting at this identifier
+                    let mut segments = vec![ident.to_string()];
+                    let mut end = ident.span();
+                    while let (Some(TokenTree::Punct(first)), Some(TokenTree::Punct(second)), Some(TokenTree::Ident(next))) =
+                        (tokens.get(i + 1), tokens.get(i + 2), tokens.get(i + 3))
+                    {
+                        if first.as_char() != ':' || second.as_char() != ':' {
+                            break;
+                        }
+                        segments.push(next.to_string());
+                        end = next.span();
+                        i += 3;
+                    }
+                    
+                    let span = ident.span().join(end).unwrap_or_else(|| ident.span());
+                    let path = segments.join("::");
+                    let usage_type = match tokens.get(i + 1) {
+                        Some(TokenTree::Punct(punct)) if punct.as_char() == '!' => UsageType::Macro,
+                        _ => UsageType::Other,
+                    };
+                    
+                    match self.imports.resolve(&segments, false, true) {
+                        Resolution::Crate if segments.len() > 1 => {
+                            self.record_usage(&segments[0], span, path.clone(), Some(path), usage_type);
+                        }
+                        Resolution::Import(full_path, consumed) => {
+                            let crate_name = full_path.split("::").next().unwrap_or("");
+                            let qualified = qualify(&full_path, &segments[consumed..]);
+                            self.record_usage(crate_name, span, format!("{} (from {})", path, full_path), Some(qualified), usage_type);
+                        }
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+            
+            i += 1;
+        }
+    }
+    
+    /// Record the usages of a use tree; the names it imports were declared on entering the scope
+    fn process_use_tree(&mut self, tree: &UseTree, prefix: &mut Vec<String>, leading_colon: bool) {
         match tree {
-            UseTree::Path(UsePath { ident, tree, .. }) => {
-                let new_prefix = if prefix.is_empty() {
-                    ident.to_string()
-                } else {
-                    format!("{}::{}", prefix, ident)
-                };
-                
+            UseTree::Path(UsePath { ident, tree: subtree, .. }) => {
                 // Check if this is a dependency
-                for dep in self.dependencies {
-                    if ident.to_string() == dep.name && prefix.is_empty() {
-                        if let Some(usages) = self.usage_data.usage_locations.get_mut(&dep.name) {
-                            usages.push(DependencyUsage {
-                                file: self.file_path.clone(),
-                                line,
-                                imported_item: format!("{}::<rest>", ident),
-                                usage_type: UsageType::Import,
-                            });
-                        }
+                if prefix.is_empty() {
+                    let name = vec![ident.to_string()];
+                    let crate_name = match self.imports.resolve(&name, leading_colon, false) {
+                        Resolution::Crate => Some(ident.to_string()),
+                        Resolution::Import(full_path, _) => full_path.split("::").next().map(str::to_string),
+                        Resolution::Local => None,
+                    };
+                    if let Some(crate_name) = crate_name {
+                        self.record_usage(&crate_name, tree.span(), format!("{}::<rest>", ident), None, UsageType::Import);
                     }
                 }
                 
-                self.process_use_tree(tree, &new_prefix, line);
+                prefix.push(ident.to_string());
+                self.process_use_tree(subtree, prefix, leading_colon);
+                prefix.pop();
             },
             UseTree::Name(UseName { ident, .. }) => {
-                let full_path = if prefix.is_empty() {
-                    ident.to_string()
-                } else {
-                    format!("{}::{}", prefix, ident)
-                };
-                
-                // Add to imports map
-                self.current_imports.insert(ident.to_string(), full_path.clone());
+                let mut path = prefix.clone();
+                if ident != "self" {
+                    path.push(ident.to_string());
+                }
                 
-                // Check if the prefix is a dependency
-                let crate_name = prefix.split("::").next().unwrap_or("");
-                for dep in self.dependencies {
-                    if crate_name == dep.name {
-                        if let Some(usages) = self.usage_data.usage_locations.get_mut(&dep.name) {
-                            usages.push(DependencyUsage {
-                                file: self.file_path.clone(),
-                                line,
-                                imported_item: full_path.clone(),
-                                usage_type: UsageType::Import,
-                            });
-                        }
-                    }
+                if let Some((crate_name, display, full_path)) = self.resolve_use_path(&path, leading_colon) {
+                    self.record_usage(&crate_name, tree.span(), display, Some(full_path), UsageType::Import);
                 }
             },
             UseTree::Rename(rename) => {
-                let full_path = if prefix.is_empty() {
-                    rename.ident.to_string()
-                } else {
-                    format!("{}::{}", prefix, rename.ident)
-                };
-                
-                // Add to imports map with the renamed identifier
-                self.current_imports.insert(rename.rename.to_string(), full_path.clone());
+                let mut path = prefix.clone();
+                if rename.ident != "self" {
+                    path.push(rename.ident.to_string());
+                }
                 
-                // Check if the prefix is a dependency
-                let crate_name = prefix.split("::").next().unwrap_or("");
-                for dep in self.dependencies {
-                    if crate_name == dep.name {
-                        if let Some(usages) = self.usage_data.usage_locations.get_mut(&dep.name) {
-                            usages.push(DependencyUsage {
-                                file: self.file_path.clone(),
-                                line,
-                                imported_item: format!("{} as {}", full_path, rename.rename),
-                                usage_type: UsageType::Import,
-                            });
-                        }
-                    }
+                if let Some((crate_name, display, full_path)) = self.resolve_use_path(&path, leading_colon) {
+                    self.record_usage(
+                        &crate_name,
+                        tree.span(),
+                        format!("{} as {}", display, rename.rename),
+                        Some(full_path),
+                        UsageType::Import,
+                    );
                 }
             },
             UseTree::Glob(_) => {
                 // For glob imports (e.g., use some_crate::*;)
-                let crate_name = prefix.split("::").next().unwrap_or("");
-                for dep in self.dependencies {
-                    if crate_name == dep.name {
-                        if let Some(usages) = self.usage_data.usage_locations.get_mut(&dep.name) {
-                            usages.push(DependencyUsage {
-                                file: self.file_path.clone(),
-                                line,
-                                imported_item: format!("{}::*", prefix),
-                                usage_type: UsageType::Import,
-                            });
-                        }
-                    }
+                if let Some((crate_name, display, _)) = self.resolve_use_path(prefix, leading_colon) {
+                    let display = match display.split_once(' ') {
+                        Some((path, origin)) => format!("{}::* {}", path, origin),
+                        None => format!("{}::*", display),
+                    };
+                    self.record_usage(&crate_name, tree.span(), display, None, UsageType::Import);
                 }
             },
             UseTree::Group(UseGroup { items, .. }) => {
                 // For grouped imports (e.g., use some_crate::{Thing1, Thing2};)
                 for item in items {
-                    self.process_use_tree(item, prefix, line);
+                    self.process_use_tree(item, prefix, leading_colon);
                 }
             },
         }
     }
+    
+    /// Resolve the path of a use tree leaf to the crate it imports from, how to display it
+    /// and the fully qualified path of the imported item
+    fn resolve_use_path(&self, path: &[String], leading_colon: bool) -> Option<(String, String, String)> {
+        let path_str = path.join("::");
+        
+        match self.imports.resolve(path, leading_colon, false) {
+            Resolution::Crate => Some((path.first()?.clone(), path_str.clone(), path_str)),
+            Resolution::Import(full_path, consumed) => {
+                let qualified = qualify(&full_path, &path[consumed..]);
+                let crate_name = qualified.split("::").next().unwrap_or("").to_string();
+                // `use regex::{self};` imports the crate under its own name
+                let display = if qualified == path_str { path_str } else { format!("{} (from {})", path_str, qualified) };
+                Some((crate_name, display, qualified))
+            }
+            Resolution::Local => None,
+        }
+    }
+}
+
+
+/// Traits that crates are mostly used through, with the methods attributed to them once the
+/// trait, or the prelude bringing it in, is imported. Methods std types also have, such as
+/// `map` or `next`, are left out, as a call to them says nothing about the trait.
+const TRAIT_METHODS: &[(&str, &[&str])] = &[
+    ("rayon::prelude", &[
+        "par_iter", "par_iter_mut", "into_par_iter", "par_bridge", "par_extend", "par_chunks", "par_chunks_mut",
+        "par_windows", "par_split", "par_sort", "par_sort_by", "par_sort_by_key", "par_sort_unstable",
+        "par_sort_unstable_by", "par_sort_unstable_by_key", "par_chars", "par_lines", "par_split_whitespace",
+    ]),
+    ("rayon::iter::IntoParallelIterator", &["into_par_iter"]),
+    ("rayon::iter::IntoParallelRefIterator", &["par_iter"]),
+    ("rayon::iter::IntoParallelRefMutIterator", &["par_iter_mut"]),
+    ("rayon::iter::ParallelBridge", &["par_bridge"]),
+    ("rayon::iter::ParallelExtend", &["par_extend"]),
+    ("rayon::slice::ParallelSlice", &["par_chunks", "par_windows", "par_split"]),
+    ("rayon::slice::ParallelSliceMut", &[
+        "par_chunks_mut", "par_sort", "par_sort_by", "par_sort_by_key", "par_sort_unstable", "par_sort_unstable_by",
+        "par_sort_unstable_by_key",
+    ]),
+    ("rayon::str::ParallelString", &["par_chars", "par_lines", "par_split_whitespace"]),
+    ("itertools::Itertools", &[
+        "interleave", "intersperse", "cartesian_product", "multi_cartesian_product", "tuple_windows",
+        "circular_tuple_windows", "tuples", "chunk_by", "group_by", "unique", "unique_by", "duplicates",
+        "duplicates_by", "sorted", "sorted_by", "sorted_by_key", "sorted_unstable", "collect_vec", "try_collect",
+        "positions", "find_position", "kmerge", "merge_join_by", "coalesce", "batching", "peeking_take_while",
+        "take_while_ref", "minmax", "minmax_by_key", "exactly_one", "at_most_one", "counts", "counts_by",
+        "into_group_map", "into_group_map_by", "all_equal", "all_unique", "combinations", "permutations",
+        "powerset", "pad_using", "zip_eq", "zip_longest", "with_position", "dropping", "dropping_back",
+        "tree_reduce", "k_smallest", "fold_while", "partition_map", "format_with", "map_ok", "filter_ok",
+        "flatten_ok",
+    ]),
+    ("futures::prelude", &[
+        "buffer_unordered", "buffered", "for_each_concurrent", "select_next_some", "ready_chunks",
+        "flat_map_unordered", "try_next", "try_collect", "try_for_each_concurrent", "try_buffer_unordered",
+        "try_buffered", "into_async_read", "boxed", "boxed_local", "now_or_never", "remote_handle", "shared",
+        "send_all", "sink_map_err",
+    ]),
+    ("futures::StreamExt", &[
+        "buffer_unordered", "buffered", "for_each_concurrent", "select_next_some", "ready_chunks",
+        "flat_map_unordered", "boxed", "boxed_local",
+    ]),
+    ("futures::stream::StreamExt", &[
+        "buffer_unordered", "buffered", "for_each_concurrent", "select_next_some", "ready_chunks",
+        "flat_map_unordered", "boxed", "boxed_local",
+    ]),
+    ("futures::TryStreamExt", &[
+        "try_next", "try_collect", "try_for_each_concurrent", "try_buffer_unordered", "try_buffered", "into_async_read",
+    ]),
+    ("futures::FutureExt", &["boxed", "boxed_local", "now_or_never", "remote_handle", "shared"]),
+    ("futures::future::FutureExt", &["boxed", "boxed_local", "now_or_never", "remote_handle", "shared"]),
+    ("futures::SinkExt", &["send_all", "sink_map_err"]),
+    ("tokio_stream::StreamExt", &["throttle", "timeout", "chunks_timeout", "merge"]),
+    ("tokio::io::AsyncReadExt", &[
+        "read_exact", "read_to_end", "read_to_string", "read_buf", "read_u8", "read_u16", "read_u32", "read_u64",
+        "read_i8", "read_i16", "read_i32", "read_i64",
+    ]),
+    ("tokio::io::AsyncWriteExt", &[
+        "write_all", "write_buf", "write_u8", "write_u16", "write_u32", "write_u64", "write_i8", "write_i16",
+        "write_i32", "write_i64",
+    ]),
+    ("tokio::io::AsyncBufReadExt", &["read_line", "read_until"]),
+    ("anyhow::Context", &["context", "with_context"]),
+    ("eyre::WrapErr", &["wrap_err", "wrap_err_with"]),
+    ("color_eyre::eyre::WrapErr", &["wrap_err", "wrap_err_with"]),
+    ("rand::Rng", &["gen", "gen_range", "gen_bool", "gen_ratio", "sample_iter", "random", "random_range", "random_bool"]),
+    ("rand::seq::SliceRandom", &["choose", "choose_multiple", "choose_weighted", "shuffle", "partial_shuffle"]),
+    ("rand::seq::IndexedRandom", &["choose", "choose_multiple", "choose_weighted"]),
+    ("rand::prelude", &[
+        "gen", "gen_range", "gen_bool", "gen_ratio", "sample_iter", "random", "random_range", "random_bool",
+        "choose", "choose_multiple", "choose_weighted", "shuffle", "partial_shuffle",
+    ]),
+    ("byteorder::ReadBytesExt", &[
+        "read_u16", "read_u32", "read_u64", "read_i16", "read_i32", "read_i64", "read_f32", "read_f64",
+    ]),
+    ("byteorder::WriteBytesExt", &[
+        "write_u16", "write_u32", "write_u64", "write_i16", "write_i32", "write_i64", "write_f32", "write_f64",
+    ]),
+    ("bytes::Buf", &[
+        "get_u8", "get_u16", "get_u32", "get_u64", "get_i8", "get_i16", "get_i32", "get_i64", "get_f32", "get_f64",
+        "copy_to_bytes", "has_remaining",
+    ]),
+    ("bytes::BufMut", &[
+        "put_u8", "put_u16", "put_u32", "put_u64", "put_i8", "put_i16", "put_i32", "put_i64", "put_f32", "put_f64",
+        "put_slice", "remaining_mut",
+    ]),
+    ("chrono::Datelike", &["year", "month", "month0", "day", "day0", "weekday", "ordinal", "iso_week", "with_year", "with_month", "with_day"]),
+    ("chrono::Timelike", &["hour", "minute", "second", "nanosecond", "with_hour", "with_minute", "with_second", "with_nanosecond"]),
+    ("chrono::prelude", &[
+        "year", "month", "month0", "day", "day0", "weekday", "ordinal", "iso_week", "with_year", "with_month",
+        "with_day", "hour", "minute", "second", "nanosecond", "with_hour", "with_minute", "with_second",
+        "with_nanosecond",
+    ]),
+    ("tracing::Instrument", &["instrument", "in_current_span"]),
+    ("tracing_subscriber::layer::SubscriberExt", &["with"]),
+    ("tracing_subscriber::util::SubscriberInitExt", &["init", "try_init", "set_default"]),
+    ("tracing_subscriber::prelude", &["with", "init", "try_init", "set_default"]),
+    ("indicatif::ProgressIterator", &["progress", "progress_count", "progress_with", "progress_with_style"]),
+    ("indicatif::ParallelProgressIterator", &["progress", "progress_count", "progress_with", "progress_with_style"]),
+    ("colored::Colorize", &[
+        "red", "green", "blue", "yellow", "cyan", "magenta", "purple", "white", "black", "bright_red",
+        "bright_green", "bright_blue", "bright_yellow", "bright_cyan", "bright_magenta", "bright_white",
+        "bright_black", "truecolor", "on_red", "on_green", "on_blue", "on_yellow", "bold", "dimmed", "italic",
+        "underline", "reversed", "strikethrough", "normal",
+    ]),
+    ("owo_colors::OwoColorize", &[
+        "red", "green", "blue", "yellow", "cyan", "magenta", "purple", "white", "black", "bright_red",
+        "bright_green", "bright_blue", "bright_yellow", "bright_cyan", "bright_magenta", "bright_white",
+        "bright_black", "truecolor", "fg", "bg", "on_red", "on_green", "on_blue", "on_yellow", "bold", "dimmed",
+        "italic", "underline", "reversed", "strikethrough",
+    ]),
+    ("digest::Digest", &["finalize", "finalize_reset", "chain_update"]),
+    ("sha2::Digest", &["finalize", "finalize_reset", "chain_update"]),
+];
+
+/// Derive helper attributes whose name differs from the crate providing the derive
+const DERIVE_HELPERS: &[(&str, &str)] = &[
+    ("error", "thiserror"),
+    ("source", "thiserror"),
+    ("from", "thiserror"),
+    ("backtrace", "thiserror"),
+    ("arg", "clap"),
+    ("command", "clap"),
+    ("value", "clap"),
+    ("group", "clap"),
+];
+
+/// Crates available without being declared as dependencies
+const BUILTIN_CRATES: &[&str] = &["std", "core", "alloc", "proc_macro", "test"];
+
+/// Primitive types, whose associated items are used through paths like `u8::MAX`
+const PRIMITIVE_TYPES: &[&str] = &[
+    "bool", "char", "str", "i8", "i16", "i32", "i64", "i128", "isize",
+    "u8", "u16", "u32", "u64", "u128", "usize", "f32", "f64",
+];
+
+/// Whether a path root matching no dependency may name a crate: crate names are lowercase,
+/// unlike types and enums such as `Vec` and `Option`, and are neither builtin crates nor primitives
+fn may_name_crate(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_lowercase())
+        && !BUILTIN_CRATES.contains(&name)
+        && !PRIMITIVE_TYPES.contains(&name)
+}
+
+/// Attribute the usages of unknown crates to unused registry dependencies whose library has a
+/// custom `[lib] name`. Only these dependencies are looked up in the local Cargo registry cache,
+/// and a dependency found this way is referred to by its library name from then on.
+pub fn resolve_lib_names(dependencies: &mut [Dependency], usage_data: &mut DependencyUsageData) {
+    if usage_data.unknown_crates.is_empty() {
+        return;
+    }
+    
+    // A dependency declared in several tables is looked up once
+    let mut lib_names: HashMap<String, Option<String>> = HashMap::new();
+    for dep in dependencies.iter_mut() {
+        let lib_name = lib_names.entry(dep.name.clone()).or_insert_with(|| {
+            if usage_data.usage_locations.get(&dep.name).is_some_and(|usages| !usages.is_empty()) {
+                return None;
+            }
+            let lib_name = cargo::registry_lib_name(dep)?;
+            let usages = usage_data.unknown_crates.remove(&lib_name)?;
+            usage_data.usage_locations.insert(dep.name.clone(), usages);
+            Some(lib_name)
+        });
+        
+        if let Some(lib_name) = lib_name {
+            dep.import_name = lib_name.clone();
+        }
+    }
+}
+
+/// Paths listed in a `#[derive(...)]` attribute
+fn derive_paths(meta: &syn::Meta) -> Vec<syn::Path> {
+    match meta {
+        syn::Meta::List(list) => list.parse_args_with(Punctuated::<syn::Path, Token![,]>::parse_terminated)
+            .map(|paths| paths.into_iter().collect())
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+/// Collect the names of the `pub` structs, enums, unions and type aliases among `items`,
+/// including those in inline modules
+fn collect_public_types(items: &[syn::Item], names: &mut HashSet<String>) {
+    for item in items {
+        let (vis, ident) = match item {
+            syn::Item::Struct(item) => (&item.vis, &item.ident),
+            syn::Item::Enum(item) => (&item.vis, &item.ident),
+            syn::Item::Union(item) => (&item.vis, &item.ident),
+            syn::Item::Type(item) => (&item.vis, &item.ident),
+            syn::Item::Mod(syn::ItemMod { content: Some((_, items)), .. }) => {
+                collect_public_types(items, names);
+                continue;
+            }
+            _ => continue,
+        };
+        
+        if matches!(vis, syn::Visibility::Public(_)) {
+            names.insert(ident.to_string());
+        }
+    }
+}
+
+/// Name of the type a path type refers to, e.g. `Wrapper` for `crate::Wrapper<T>`
+fn type_name(ty: &syn::Type) -> Option<String> {
+    match ty {
+        syn::Type::Path(path) => path.path.segments.last().map(|segment| segment.ident.to_string()),
+        _ => None,
+    }
+}
+
+/// Attributes of an item
+fn item_attrs(item: &syn::Item) -> &[syn::Attribute] {
+    match item {
+        syn::Item::Const(item) => &item.attrs,
+        syn::Item::Enum(item) => &item.attrs,
+        syn::Item::ExternCrate(item) => &item.attrs,
+        syn::Item::Fn(item) => &item.attrs,
+        syn::Item::ForeignMod(item) => &item.attrs,
+        syn::Item::Impl(item) => &item.attrs,
+        syn::Item::Macro(item) => &item.attrs,
+        syn::Item::Mod(item) => &item.attrs,
+        syn::Item::Static(item) => &item.attrs,
+        syn::Item::Struct(item) => &item.attrs,
+        syn::Item::Trait(item) => &item.attrs,
+        syn::Item::TraitAlias(item) => &item.attrs,
+        syn::Item::Type(item) => &item.attrs,
+        syn::Item::Union(item) => &item.attrs,
+        syn::Item::Use(item) => &item.attrs,
+        _ => &[],
+    }
+}
+
+/// Usage data with an empty usage list for every dependency
+pub fn empty_usage_data(dependencies: &[Dependency]) -> DependencyUsageData {
+    let mut usage_data = DependencyUsageData::default();
+    
+    for dep in dependencies {
+        usage_data.usage_locations.insert(dep.name.clone(), Vec::new());
+    }
+    
+    usage_data
+}
+
+/// Compute the 1-based start and end columns of the trimmed content of a source line
+fn line_columns(line: &str) -> (usize, usize) {
+    let indent = line.chars().take_while(|c| c.is_whitespace()).count();
+    let width = line.trim().chars().count();
+    (indent + 1, indent + width + 1)
+}
+
+/// Identifiers of the segments of a path, without the `r#` of raw identifiers
+fn path_segments(path: &syn::Path) -> Vec<String> {
+    path.segments.iter()
+        .map(|segment| segment.ident.to_string().trim_start_matches("r#").to_string())
+        .collect()
 }
 
 /// Convert a path to a string
@@ -411,10 +1383,203 @@ fn determine_usage_type(path: &syn::Path) -> UsageType {
 
 /// Check if a string starts with a lowercase letter
 fn is_lowercase_first(s: &str) -> bool {
-    s.chars().next().map_or(false, |c| c.is_lowercase())
+    s.chars().next().is_some_and(|c| c.is_lowercase())
 }
 
 /// Check if a string starts with an uppercase letter
 fn is_uppercase_first(s: &str) -> bool {
-    s.chars().next().map_or(false, |c| c.is_uppercase())
-} 
\ No newline at end of file
+    s.chars().next().is_some_and(|c| c.is_uppercase())
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use crate::manifest::dependency::{CargoExtras, DependencyExtras, DependencySource, DependencyType};
+    
+    /// A normal dependency on `name`, imported under its name with hyphens replaced
+    fn dependency(name: &str) -> Dependency {
+        Dependency {
+            name: name.to_string(),
+            import_name: name.replace('-', "_"),
+            package: None,
+            version: None,
+            features: Vec::new(),
+            optional: false,
+            dependency_type: DependencyType::Normal,
+            source: DependencySource::default(),
+            extras: DependencyExtras::Rust(CargoExtras {
+                default_features: true,
+                platforms: Vec::new(),
+                all_platforms: true,
+            }),
+        }
+    }
+    
+    /// Module info of the crate root of a library
+    fn library_root() -> ModuleInfo {
+        ModuleInfo {
+            target: TargetKind::Lib,
+            module: "crate".to_string(),
+            cfg: None,
+            public: true,
+        }
+    }
+    
+    /// Usages of the dependencies `names` in `source`, a file in `module`, in source order
+    fn usages_in(source: &str, module: &ModuleInfo, names: &[&str]) -> Vec<(String, DependencyUsage)> {
+        let dir = tempfile::tempdir().unwrap();
+        let analyzer = RustAnalyzer::new(dir.path()).unwrap();
+        let dependencies: Vec<Dependency> = names.iter().map(|name| dependency(name)).collect();
+        
+        let mut usage_data = empty_usage_data(&dependencies);
+        analyzer.analyze_file(source, Path::new("src/lib.rs"), module, &dependencies, &mut usage_data);
+        
+        let mut usages: Vec<(String, DependencyUsage)> = usage_data.usage_locations.into_iter()
+            .flat_map(|(dep_name, usages)| usages.into_iter().map(move |usage| (dep_name.clone(), usage)))
+            .collect();
+        usages.sort_by_key(|(_, usage)| (usage.line, usage.column));
+        usages
+    }
+    
+    /// Usages of the dependencies `names` in `source`, the root of a library
+    fn usages(source: &str, names: &[&str]) -> Vec<(String, DependencyUsage)> {
+        usages_in(source, &library_root(), names)
+    }
+    
+    #[test]
+    fn records_the_span_of_each_usage() {
+        let source = "use serde::{\n    Deserialize,\n    Serialize,\n};\n\nfn parse(text: &str) -> bool {\n    regex::Regex::new(text).is_ok()\n}\n";
+        let spans: Vec<(String, usize, usize, usize, usize, String)> = usages(source, &["serde", "regex"]).into_iter()
+            .map(|(dep, usage)| (dep, usage.line, usage.column, usage.end_line, usage.end_column, usage.imported_item))
+            .collect();
+        
+        assert_eq!(spans, vec![
+            ("serde".to_string(), 1, 5, 4, 2, "serde::<rest>".to_string()),
+            ("serde".to_string(), 2, 5, 2, 16, "serde::Deserialize".to_string()),
+            ("serde".to_string(), 3, 5, 3, 14, "serde::Serialize".to_string()),
+            ("regex".to_string(), 7, 5, 7, 22, "regex::Regex::new".to_string()),
+        ]);
+    }
+    
+    #[test]
+    fn finds_usages_inside_macro_arguments() {
+        let source = "fn main() {\n    println!(\"{}\", serde_json::to_string(&1).unwrap());\n    let pattern = vec![regex::escape(\"a\")];\n    regex::Regex::new(&pattern[0]).unwrap();\n}\n";
+        let found: Vec<(String, usize, Option<String>, UsageType, bool)> = usages(source, &["serde_json", "regex"]).into_iter()
+            .map(|(dep, usage)| (dep, usage.line, usage.item, usage.usage_type, usage.in_macro))
+            .collect();
+        
+        assert_eq!(found, vec![
+            ("serde_json".to_string(), 2, Some("serde_json::to_string".to_string()), UsageType::Function, true),
+            ("regex".to_string(), 3, Some("regex::escape".to_string()), UsageType::Function, true),
+            ("regex".to_string(), 4, Some("regex::Regex::new".to_string()), UsageType::Function, false),
+        ]);
+    }
+    
+    #[test]
+    fn records_derives_attribute_macros_and_derive_helpers() {
+        let source = "use serde::Deserialize;\n\n#[derive(Debug, Deserialize, serde::Serialize)]\nstruct Config {\n    #[serde(rename = \"n\")]\n    name: String,\n}\n\n#[tokio::main]\nasync fn main() {}\n";
+        let found: Vec<(usize, usize, String, Option<String>, UsageType)> = usages(source, &["serde", "tokio"]).into_iter()
+            .filter(|(_, usage)| usage.usage_type != UsageType::Import)
+            .map(|(_, usage)| (usage.line, usage.column, usage.imported_item, usage.item, usage.usage_type))
+            .collect();
+        
+        assert_eq!(found, vec![
+            (3, 17, "Deserialize (from serde::Deserialize)".to_string(), Some("serde::Deserialize".to_string()), UsageType::Derive),
+            (3, 30, "serde::Serialize".to_string(), Some("serde::Serialize".to_string()), UsageType::Derive),
+            (5, 7, "#[serde] (derive helper)".to_string(), None, UsageType::Attribute),
+            (9, 3, "#[tokio::main]".to_string(), Some("tokio::main".to_string()), UsageType::Attribute),
+        ]);
+    }
+    
+    #[test]
+    fn counts_test_only_code_as_the_test_target() {
+        let source = "use rand::Rng;\n\n#[test]\nfn works() {\n    regex::escape(\"a\");\n}\n\n#[cfg(all(test, unix))]\nfn helper() -> tempfile::TempDir { todo!() }\n\n#[cfg(test)]\nmod tests {\n    use serde::Serialize;\n}\n";
+        let names = ["rand", "regex", "tempfile", "serde"];
+        let targets = |module: &ModuleInfo| -> Vec<(String, TargetKind)> {
+            usages_in(source, module, &names).into_iter()
+                .filter(|(_, usage)| usage.item.is_some())
+                .map(|(dep, usage)| (dep, usage.target))
+                .collect()
+        };
+        
+        assert_eq!(targets(&library_root()), vec![
+            ("rand".to_string(), TargetKind::Lib),
+            ("regex".to_string(), TargetKind::Test),
+            ("tempfile".to_string(), TargetKind::Test),
+            ("serde".to_string(), TargetKind::Test),
+        ]);
+        
+        let binary_root = ModuleInfo {
+            target: TargetKind::Bin,
+            ..library_root()
+        };
+        assert_eq!(targets(&binary_root)[0], ("rand".to_string(), TargetKind::Bin));
+    }
+    
+    #[test]
+    fn records_how_dependencies_appear_in_the_public_api() {
+        let source = "pub use rand::Rng;\n\npub struct Wrapper {\n    pub value: serde_json::Value,\n    inner: regex::Regex,\n}\n\npub fn parse(text: &str) -> Result<url::Url, url::ParseError> { todo!() }\n\nfn private(_: tokio::runtime::Runtime) {}\n\n#[derive(serde::Serialize)]\npub struct Exported;\n";
+        let names = ["rand", "serde_json", "regex", "url", "tokio", "serde"];
+        let exposures = |module: &ModuleInfo| -> Vec<(String, Option<Exposure>)> {
+            usages_in(source, module, &names).into_iter()
+                .filter(|(_, usage)| usage.item.is_some())
+                .map(|(dep, usage)| (dep, usage.exposure))
+                .collect()
+        };
+        
+        assert_eq!(exposures(&library_root()), vec![
+            ("rand".to_string(), Some(Exposure::ReExport)),
+            ("serde_json".to_string(), Some(Exposure::Field)),
+            ("regex".to_string(), None),
+            ("url".to_string(), Some(Exposure::Signature)),
+            ("url".to_string(), Some(Exposure::Signature)),
+            ("tokio".to_string(), None),
+            ("serde".to_string(), Some(Exposure::TraitImpl)),
+        ]);
+        
+        let private_module = ModuleInfo {
+            module: "crate::internal".to_string(),
+            public: false,
+            ..library_root()
+        };
+        assert!(exposures(&private_module).iter().all(|(_, exposure)| exposure.is_none()));
+    }
+    
+    #[test]
+    fn attributes_method_calls_to_imported_traits() {
+        let source = "use itertools::Itertools;\nuse rayon::prelude::*;\n\nfn count(items: Vec<u32>) -> usize {\n    items.par_iter().count()\n}\n\nfn sort(items: Vec<u32>) {\n    items.iter().sorted();\n}\n";
+        let calls: Vec<(String, usize, String, Option<String>)> = usages(source, &["itertools", "rayon"]).into_iter()
+            .filter(|(_, usage)| usage.usage_type == UsageType::Trait)
+            .map(|(dep, usage)| (dep, usage.line, usage.imported_item, usage.item))
+            .collect();
+        
+        assert_eq!(calls, vec![
+            ("rayon".to_string(), 5, ".par_iter() (from rayon::prelude)".to_string(), Some("rayon::prelude::par_iter".to_string())),
+            ("itertools".to_string(), 9, ".sorted() (from itertools::Itertools)".to_string(), Some("itertools::Itertools::sorted".to_string())),
+        ]);
+    }
+    
+    #[test]
+    fn attributes_methods_of_configured_traits() {
+        let source = "use geo::Area;\n\nfn area(polygon: &geo::Polygon) -> f64 {\n    polygon.unsigned_area()\n}\n";
+        let dir = tempfile::tempdir().unwrap();
+        let trait_methods = BTreeMap::from([
+            ("geo::Area".to_string(), vec!["unsigned_area".to_string()]),
+        ]);
+        let analyzer = RustAnalyzer::new(dir.path()).unwrap().with_trait_methods(&trait_methods);
+        let dependencies = vec![dependency("geo")];
+        
+        let mut usage_data = empty_usage_data(&dependencies);
+        analyzer.analyze_file(source, Path::new("src/lib.rs"), &library_root(), &dependencies, &mut usage_data);
+        
+        let calls: Vec<String> = usage_data.usage_locations["geo"].iter()
+            .filter(|usage| usage.usage_type == UsageType::Trait)
+            .map(|usage| usage.imported_item.clone())
+            .collect();
+        assert_eq!(calls, vec![".unsigned_area() (from geo::Area)".to_string()]);
+        
+        let unconfigured = usages(source, &["geo"]);
+        assert!(unconfigured.iter().all(|(_, usage)| usage.usage_type != UsageType::Trait));
+    }
+}
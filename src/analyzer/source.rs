@@ -0,0 +1,127 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use anyhow::{Result, Context};
+use rayon::prelude::*;
+
+use crate::analyzer::DependencyUsageData;
+use crate::analyzer::cache::{self, UsageCache};
+use crate::analyzer::module_graph::ModuleInfo;
+use crate::analyzer::rust_analyzer::empty_usage_data;
+use crate::manifest::cargo::TargetKind;
+use crate::manifest::dependency::Dependency;
+
+/// Directories holding benchmarks, in every ecosystem without declared targets
+const BENCH_DIRS: &[&str] = &["bench", "benches", "benchmark", "benchmarks"];
+
+/// Directories holding examples, in every ecosystem without declared targets
+const EXAMPLE_DIRS: &[&str] = &["example", "examples"];
+
+/// How the source files of a package are analyzed
+pub struct FileAnalysis<'a> {
+    /// Directory of the package; cached results are keyed by paths relative to it
+    pub project_path: &'a Path,
+    /// Number of threads analyzing files in parallel (`None` uses one per CPU)
+    pub threads: Option<usize>,
+    /// File caching per-file results between runs, if caching is enabled
+    pub cache_file: Option<&'a Path>,
+}
+
+/// Analyze `files` in parallel, each into its own usage data, and merge the results in file order
+/// so usages are listed the same way on every run. `module_of` places a file in the package and
+/// `analyze_file` records the usages in its content. Unchanged files come from the cache, which is
+/// invalidated when the dependencies or `key`, whatever else the results depend on, change.
+pub fn analyze_files_cached<M, F>(
+    files: &[PathBuf],
+    dependencies: &[Dependency],
+    key: &BTreeMap<String, Vec<String>>,
+    options: &FileAnalysis,
+    module_of: M,
+    analyze_file: F,
+) -> Result<DependencyUsageData>
+where
+    M: Fn(&Path) -> ModuleInfo + Sync,
+    F: Fn(&str, &Path, &ModuleInfo, &mut DependencyUsageData) + Sync,
+{
+    let cache = options.cache_file.map(|path| UsageCache::load(path, dependencies, key));
+    
+    let analyze_files = || {
+        files.par_iter()
+            .map(|file_path| {
+                let file_content = fs::read_to_string(file_path)
+                    .with_context(|| format!("Failed to read file: {:?}", file_path))?;
+                let module = module_of(file_path);
+                let hash = cache::file_hash(&file_content, &module);
+                
+                let mut file_data = empty_usage_data(dependencies);
+                match cache.as_ref().and_then(|cache| cache.get(relative_path(options.project_path, file_path), file_path, hash)) {
+                    Some(cached) => file_data.merge(cached),
+                    None => analyze_file(&file_content, file_path, &module, &mut file_data),
+                }
+                
+                Ok((hash, file_data))
+            })
+            .collect::<Result<Vec<_>>>()
+    };
+    
+    let per_file = match options.threads {
+        Some(threads) => rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .context("Failed to create the analysis thread pool")?
+            .install(analyze_files)?,
+        None => analyze_files()?,
+    };
+    
+    let mut usage_data = empty_usage_data(dependencies);
+    // Only files seen in this run are kept, so deleted files drop out of the cache
+    let mut updated_cache = cache.map(|_| UsageCache::new(dependencies, key));
+    
+    for (file_path, (hash, file_data)) in files.iter().zip(per_file) {
+        if let Some(cache) = &mut updated_cache {
+            cache.insert(relative_path(options.project_path, file_path).to_path_buf(), hash, &file_data);
+        }
+        
+        usage_data.merge(file_data);
+    }
+    
+    // A cache that cannot be written only costs time on the next run
+    if let (Some(cache), Some(path)) = (updated_cache, options.cache_file) {
+        if let Err(err) = cache.save(path) {
+            eprintln!("Warning: {:#}", err);
+        }
+    }
+    
+    Ok(usage_data)
+}
+
+/// Path of a file relative to the project path, as used for cache keys and to classify files
+pub fn relative_path<'p>(project_path: &Path, file_path: &'p Path) -> &'p Path {
+    file_path.strip_prefix(project_path).unwrap_or(file_path)
+}
+
+/// Determine the kind of target a file belongs to from its path relative to the package, for
+/// ecosystems without declared targets: files in one of `test_dirs` or whose name `is_test`,
+/// then files in benchmark and example directories. Other files are left to the caller.
+pub fn classify_by_dirs(relative: &Path, test_dirs: &[&str], is_test: impl Fn(&str) -> bool) -> Option<TargetKind> {
+    let dirs: Vec<String> = relative.parent()
+        .into_iter()
+        .flat_map(Path::components)
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect();
+    let file_name = relative.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    let has_dir = |names: &[&str]| dirs.iter().any(|dir| names.contains(&dir.as_str()));
+    
+    if has_dir(test_dirs) || is_test(&file_name) {
+        Some(TargetKind::Test)
+    } else if has_dir(BENCH_DIRS) {
+        Some(TargetKind::Bench)
+    } else if has_dir(EXAMPLE_DIRS) {
+        Some(TargetKind::Example)
+    } else {
+        None
+    }
+}
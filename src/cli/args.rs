@@ -10,6 +10,26 @@ pub struct Args {
     #[arg(short, long, global = true)]
     pub config: Option<PathBuf>,
     
+    /// Number of threads used to analyze source files (overrides `analysis.threads`)
+    #[arg(short, long, global = true)]
+    pub jobs: Option<usize>,
+    
+    /// Parse every file again instead of reusing cached results
+    #[arg(long, global = true)]
+    pub no_cache: bool,
+    
+    /// Features to evaluate `#[cfg(feature = ...)]` code with, besides the default features (comma separated)
+    #[arg(long, global = true, value_delimiter = ',')]
+    pub features: Vec<String>,
+    
+    /// Evaluate `#[cfg(feature = ...)]` code with every feature enabled
+    #[arg(long, global = true)]
+    pub all_features: bool,
+    
+    /// Target cfg to evaluate `#[cfg(...)]` code with, e.g. `unix` or `target_os=linux` (repeatable)
+    #[arg(long, global = true, value_name = "CFG")]
+    pub target_cfg: Vec<String>,
+    
     #[command(subcommand)]
     pub command: Command,
 }
@@ -58,10 +78,83 @@ pub enum Command {
         dep: Option<String>,
     },
     
+    /// Explain why a crate is in the dependency tree by showing every path to it
+    Explain {
+        /// Crate to explain, optionally with a version (`name` or `name@version`)
+        #[arg(value_name = "CRATE")]
+        krate: String,
+        
+        /// Path to the project directory (defaults to current directory)
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+        
+        /// Print the explanation as JSON
+        #[arg(long)]
+        json: bool,
+        
+        /// Maximum number of paths to show per crate version
+        #[arg(long, default_value_t = 100)]
+        max_paths: usize,
+    },
+    
+    /// Check dependencies against the configured policies and exit non-zero on failure (for CI)
+    Check {
+        /// Path to the project directory (defaults to current directory)
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+        
+        /// Importance score below which used dependencies are reported (defaults to `analysis.removal_threshold`)
+        #[arg(long)]
+        threshold: Option<f64>,
+        
+        /// Fail on warnings as well as errors
+        #[arg(long)]
+        deny_warnings: bool,
+        
+        /// Dependencies to leave out of the report (can be repeated)
+        #[arg(long, value_name = "DEPENDENCY")]
+        ignore: Vec<String>,
+        
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    
+    /// Remove unused dependencies from Cargo.toml, keeping its formatting and comments
+    Fix {
+        /// Path to the project directory (defaults to current directory)
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+        
+        /// Print the changes as a unified diff instead of writing them
+        #[arg(long)]
+        dry_run: bool,
+        
+        /// Dependencies to keep even when unused (can be repeated)
+        #[arg(long, value_name = "DEPENDENCY")]
+        ignore: Vec<String>,
+    },
+    
+    /// Manage the analysis cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    
     /// Generate a default configuration file
     Config {
         /// Path to save the configuration file (defaults to .why.toml in current directory)
         #[arg(short, long)]
         output: Option<PathBuf>,
     },
-} 
\ No newline at end of file
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CacheAction {
+    /// Remove the cached analysis results of a project
+    Clean {
+        /// Path to the project directory (defaults to current directory)
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+    },
+}
@@ -1,3 +1,14 @@
+// Style lints the TUI and configuration code is written against; it is kept as is
+#![allow(
+    clippy::collapsible_match,
+    clippy::derivable_impls,
+    clippy::map_clone,
+    clippy::redundant_closure,
+    clippy::unused_enumerate_index,
+    clippy::useless_format,
+    clippy::useless_vec
+)]
+
 use anyhow::Result;
 use clap::Parser;
 use std::path::{Path, PathBuf};
@@ -8,14 +19,14 @@ mod analyzer;
 mod tui;
 mod utils;
 
-use cli::args::{Args, Command, ExportFormat};
+use cli::args::{Args, CacheAction, Command, ExportFormat};
 use utils::config::Config;
 
 fn main() -> Result<()> {
     let args = Args::parse();
     
     // Load config if specified or use default
-    let config = match &args.config {
+    let mut config = match &args.config {
         Some(config_path) => Config::load(config_path)?,
         None => {
             // Try to load from default locations
@@ -35,25 +46,130 @@ fn main() -> Result<()> {
         }
     };
     
+    if let Some(jobs) = args.jobs {
+        config.analysis.threads = Some(jobs);
+    }
+    if args.no_cache {
+        config.analysis.use_cache = false;
+    }
+    if !args.features.is_empty() {
+        config.analysis.features = args.features.clone();
+    }
+    if args.all_features {
+        config.analysis.all_features = true;
+    }
+    if !args.target_cfg.is_empty() {
+        config.analysis.target_cfg = args.target_cfg.clone();
+    }
+    
     match args.command {
         Command::Analyze { path, dep, deps } => {
             let path = path.or(config.general.project_dir.clone())
                 .unwrap_or_else(|| std::env::current_dir().expect("Failed to get current directory"));
             
             // Start the TUI application
-            tui::app::run(path, dep, deps)?;
+            tui::app::run(path, dep, deps, config)?;
         },
         Command::Export { path, output, format, dep } => {
             let path = path.or(config.general.project_dir.clone())
                 .unwrap_or_else(|| std::env::current_dir().expect("Failed to get current directory"));
             
             // Perform the analysis
-            let analysis = perform_analysis(&path, dep.as_deref())?;
+            let analysis = perform_analysis(&path, dep.as_deref(), &config)?;
             
             // Export the results
             export_analysis(&analysis, &output, format)?;
             println!("Analysis exported to {}", output.display());
         },
+        Command::Explain { krate, path, json, max_paths } => {
+            let path = path.or(config.general.project_dir.clone())
+                .unwrap_or_else(|| std::env::current_dir().expect("Failed to get current directory"));
+            
+            // Only manifests and Cargo.lock are needed, so skip the source code analysis
+            let projects = analyzer::DependencyAnalyzer::new(&path)
+                .with_config(config.clone())
+                .resolve_graphs()?;
+            
+            // Each Cargo project of a repository has its own lock file; the crate may be in any of them
+            let mut explanations = Vec::new();
+            let mut first_error = None;
+            for project in &projects {
+                let explainer = analyzer::explain::Explainer::new(project)
+                    .with_features(&config.analysis.features, config.analysis.all_features);
+                match explainer.explain(&krate, max_paths) {
+                    Ok(found) => explanations.extend(found),
+                    Err(err) => {
+                        first_error.get_or_insert(err);
+                    }
+                }
+            }
+            if let (true, Some(err)) = (explanations.is_empty(), first_error) {
+                return Err(err);
+            }
+            
+            if json {
+                println!("{}", serde_json::to_string_pretty(&explanations)?);
+            } else {
+                for explanation in &explanations {
+                    println!("{}", explanation);
+                }
+            }
+        },
+        Command::Check { path, threshold, deny_warnings, ignore, json } => {
+            let path = path.or(config.general.project_dir.clone())
+                .unwrap_or_else(|| std::env::current_dir().expect("Failed to get current directory"));
+            
+            let mut policy = config.check.clone();
+            policy.ignore.extend(ignore);
+            let threshold = threshold.unwrap_or(config.analysis.removal_threshold);
+            
+            let result = analyzer::DependencyAnalyzer::new(&path).with_config(config.clone()).analyze()?;
+            let report = analyzer::check::Checker::new(&policy, threshold).check(&result);
+            
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                println!("{}", report);
+            }
+            
+            // A failed check is reported through the exit code so CI pipelines can gate on it
+            if !report.passed(deny_warnings) {
+                std::process::exit(1);
+            }
+        },
+        Command::Fix { path, dry_run, mut ignore } => {
+            let path = path.or(config.general.project_dir.clone())
+                .unwrap_or_else(|| std::env::current_dir().expect("Failed to get current directory"));
+            
+            // Dependencies ignored by `check` are never removed either
+            ignore.extend(config.check.ignore.iter().cloned());
+            
+            let result = analyzer::DependencyAnalyzer::new(&path).with_config(config.clone()).analyze()?;
+            let fixes = analyzer::fix::plan_fixes(&result, &ignore)?;
+            
+            if fixes.is_empty() {
+                println!("No unused dependencies to remove");
+            }
+            
+            for fix in &fixes {
+                if dry_run {
+                    print!("{}", fix.diff());
+                } else {
+                    fix.apply()?;
+                    print!("{}", fix);
+                }
+            }
+        },
+        Command::Cache { action: CacheAction::Clean { path } } => {
+            let path = path.or(config.general.project_dir.clone())
+                .unwrap_or_else(|| std::env::current_dir().expect("Failed to get current directory"));
+            
+            if analyzer::cache::clean(&path)? {
+                println!("Removed the analysis cache in {}", analyzer::cache::cache_dir(&path).display());
+            } else {
+                println!("No analysis cache found in {}", path.display());
+            }
+        },
         Command::Config { output } => {
             let output_path = output.unwrap_or_else(|| PathBuf::from(".why.toml"));
             
@@ -66,12 +182,9 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-fn perform_analysis(project_path: &Path, filter_dep: Option<&str>) -> Result<analyzer::Analysis> {
-    // Parse manifest
-    let manifest = manifest::cargo::parse_cargo_toml(project_path)?;
-    
-    // Analyze code
-    let mut analysis = analyzer::analyze(project_path, &manifest)?;
+fn perform_analysis(project_path: &Path, filter_dep: Option<&str>, config: &Config) -> Result<analyzer::Analysis> {
+    // Analyze code (manifests are discovered by the analyzer, including workspace members)
+    let mut analysis = analyzer::analyze(project_path, config)?;
     
     // Apply filter if specified
     if let Some(dep_name) = filter_dep {
@@ -96,18 +209,9 @@ fn export_analysis(analysis: &analyzer::Analysis, output_path: &Path, format: Ex
             let mut wtr = csv::Writer::from_writer(file);
             
             // Write header
-            wtr.write_record(&["Dependency", "Version", "Usage Count", "Importance Score", "Removable"])?;
-            
-            // Write data for each dependency
-            for dep in &analysis.dependencies {
-                wtr.write_record(&[
-                    &dep.name,
-                    &dep.version,
-                    &dep.usage_count.to_string(),
-                    &dep.importance_score.to_string(),
-                    &dep.removable.to_string(),
-                ])?;
-            }
+            wtr.write_record(["Dependency", "Version", "Usage Count", "Importance Score", "Removable", "Locations", "Package", "Public", "Source", "Platforms", "Ecosystem", "Manifest"])?;
+            
+            write_csv_rows(&mut wtr, analysis)?;
             
             wtr.flush()?;
         }
@@ -115,3 +219,38 @@ fn export_analysis(analysis: &analyzer::Analysis, output_path: &Path, format: Ex
     
     Ok(())
 }
+
+/// Write one CSV row per dependency of an analysis; workspace-wide rows come first, followed by
+/// the rows of each member, and the rows of the projects of a mixed repository follow each other
+fn write_csv_rows<W: std::io::Write>(wtr: &mut csv::Writer<W>, analysis: &analyzer::Analysis) -> Result<()> {
+    let manifest_path = analysis.manifest_path.display().to_string();
+    
+    for dep in &analysis.dependencies {
+        // Usage locations are joined into a single cell as `file:line:column` entries
+        let locations = dep.usages.iter()
+            .map(|usage| usage.location())
+            .collect::<Vec<_>>()
+            .join(";");
+        
+        wtr.write_record([
+            &dep.name,
+            &dep.version,
+            &dep.usage_count.to_string(),
+            &dep.importance_score.to_string(),
+            &dep.removable.to_string(),
+            &locations,
+            &analysis.name,
+            &dep.public.to_string(),
+            &dep.source.describe(dep.ecosystem),
+            &dep.platforms.join(";"),
+            &dep.ecosystem.to_string(),
+            &manifest_path,
+        ])?;
+    }
+    
+    for nested in analysis.members.iter().chain(&analysis.projects) {
+        write_csv_rows(wtr, nested)?;
+    }
+    
+    Ok(())
+}
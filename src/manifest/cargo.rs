@@ -1,84 +1,551 @@
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::fs;
 use anyhow::{Result, Context};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use toml::Table;
 
 use super::ManifestParser;
+use super::dependency::{CargoExtras, Dependency, DependencyExtras, DependencySource, DependencyType, Ecosystem};
+use super::editor::dependency_table_name;
 
 #[derive(Debug, Default)]
 pub struct CargoParser;
 
-#[derive(Debug, Clone, Deserialize)]
-pub struct CargoDependency {
-    pub name: String,
-    pub version: Option<String>,
-    pub features: Vec<String>,
-    pub optional: bool,
-    pub dependency_type: DependencyType,
-    pub source: String,
+/// Kind of Cargo target a source file is compiled into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TargetKind {
+    Lib,
+    Bin,
+    /// Integration tests, and `#[cfg(test)]` code of any target
+    Test,
+    Bench,
+    Example,
+    /// The build script
+    Build,
 }
 
-#[derive(Debug, Clone, Deserialize, PartialEq)]
-pub enum DependencyType {
-    Normal,
-    Development,
-    Build,
+impl TargetKind {
+    /// Short lowercase identifier, as used in exports
+    pub fn id(&self) -> &'static str {
+        match self {
+            TargetKind::Lib => "lib",
+            TargetKind::Bin => "bin",
+            TargetKind::Test => "test",
+            TargetKind::Bench => "bench",
+            TargetKind::Example => "example",
+            TargetKind::Build => "build",
+        }
+    }
+}
+
+/// The targets of a package: root files declared in the manifest or found through
+/// Cargo's autodiscovery conventions
+#[derive(Debug, Clone, Default)]
+pub struct CargoTargets {
+    /// Directory containing the package manifest
+    root: PathBuf,
+    /// Target root files relative to `root`, with their kind
+    roots: Vec<(PathBuf, TargetKind)>,
+    /// Whether the package has a library target
+    has_lib: bool,
+}
+
+/// A Cargo workspace declared by a `[workspace]` table
+#[derive(Debug, Clone)]
+pub struct CargoWorkspace {
+    /// Directory containing the workspace root manifest
+    pub root: PathBuf,
+    /// Workspace members in declaration order (the root package comes first, if any)
+    pub members: Vec<WorkspaceMember>,
+    /// Shared dependency specifications from `[workspace.dependencies]`
+    pub dependencies: Table,
+}
+
+/// A single package that belongs to a Cargo workspace
+#[derive(Debug, Clone)]
+pub struct WorkspaceMember {
+    /// Package name from the member's `[package]` table
+    pub name: String,
+    /// Directory containing the member's manifest
+    pub path: PathBuf,
+    /// Path to the member's Cargo.toml
+    pub manifest_path: PathBuf,
 }
 
 /// Parse a Cargo.toml file and return the dependencies
-pub fn parse_cargo_toml<P: AsRef<Path>>(project_path: P) -> Result<Vec<CargoDependency>> {
+#[allow(dead_code)]
+pub fn parse_cargo_toml<P: AsRef<Path>>(project_path: P) -> Result<Vec<Dependency>> {
     let manifest_path = project_path.as_ref().join("Cargo.toml");
     CargoParser::parse(manifest_path)
 }
 
 impl ManifestParser for CargoParser {
-    type Dependency = CargoDependency;
+    const ECOSYSTEM: Ecosystem = Ecosystem::Rust;
     
-    fn parse<P: AsRef<Path>>(path: P) -> Result<Vec<Self::Dependency>> {
-        let manifest_path = path.as_ref();
-        let content = fs::read_to_string(manifest_path)
-            .with_context(|| format!("Failed to read Cargo.toml at {:?}", manifest_path))?;
+    fn parse<P: AsRef<Path>>(path: P) -> Result<Vec<Dependency>> {
+        // Dependencies declared with `workspace = true` inherit from the enclosing workspace
+        let workspace = CargoWorkspace::find_for(path.as_ref())?;
+        Self::parse_in_workspace(path, workspace.as_ref())
+    }
+}
+
+impl CargoWorkspace {
+    /// Load the workspace declared by the manifest at `manifest_path`, if it declares one
+    pub fn load<P: AsRef<Path>>(manifest_path: P) -> Result<Option<Self>> {
+        let manifest_path = manifest_path.as_ref();
+        let cargo_toml = read_manifest(manifest_path)?;
         
-        let cargo_toml: Table = toml::from_str(&content)
-            .with_context(|| format!("Failed to parse Cargo.toml at {:?}", manifest_path))?;
+        let workspace_table = match cargo_toml.get("workspace").and_then(|w| w.as_table()) {
+            Some(table) => table,
+            None => return Ok(None),
+        };
         
-        let mut dependencies = Vec::new();
+        let root = manifest_path.parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        
+        let exclude: Vec<PathBuf> = string_array(workspace_table.get("exclude"))
+            .into_iter()
+            .map(|path| root.join(path))
+            .collect();
         
-        // Process normal dependencies
-        if let Some(deps) = cargo_toml.get("dependencies").and_then(|d| d.as_table()) {
-            Self::extract_dependencies(deps, &mut dependencies, DependencyType::Normal);
+        let mut members = Vec::new();
+        
+        // A root manifest with a [package] table is itself a member
+        if cargo_toml.contains_key("package") {
+            members.push(WorkspaceMember::new(&root, manifest_path.to_path_buf(), &cargo_toml));
         }
         
-        // Process dev-dependencies
-        if let Some(deps) = cargo_toml.get("dev-dependencies").and_then(|d| d.as_table()) {
-            Self::extract_dependencies(deps, &mut dependencies, DependencyType::Development);
+        for pattern in string_array(workspace_table.get("members")) {
+            let pattern_path = root.join(&pattern);
+            let matches = glob::glob(&pattern_path.to_string_lossy())
+                .with_context(|| format!("Invalid workspace member pattern {:?} in {:?}", pattern, manifest_path))?;
+            
+            for member_dir in matches.filter_map(Result::ok) {
+                let member_manifest = member_dir.join("Cargo.toml");
+                
+                if !member_manifest.exists()
+                    || exclude.iter().any(|excluded| member_dir.starts_with(excluded))
+                    || members.iter().any(|m: &WorkspaceMember| m.manifest_path == member_manifest)
+                {
+                    continue;
+                }
+                
+                let member_toml = read_manifest(&member_manifest)?;
+                members.push(WorkspaceMember::new(&member_dir, member_manifest, &member_toml));
+            }
         }
         
-        // Process build-dependencies
-        if let Some(deps) = cargo_toml.get("build-dependencies").and_then(|d| d.as_table()) {
-            Self::extract_dependencies(deps, &mut dependencies, DependencyType::Build);
+        let dependencies = workspace_table.get("dependencies")
+            .and_then(|d| d.as_table())
+            .cloned()
+            .unwrap_or_default();
+        
+        Ok(Some(Self {
+            root,
+            members,
+            dependencies,
+        }))
+    }
+    
+    /// Find the workspace enclosing the manifest at `manifest_path` by searching it and its parent directories
+    pub fn find_for<P: AsRef<Path>>(manifest_path: P) -> Result<Option<Self>> {
+        let start = match manifest_path.as_ref().parent() {
+            Some(dir) => dir,
+            None => return Ok(None),
+        };
+        
+        for dir in start.ancestors() {
+            let candidate = dir.join("Cargo.toml");
+            if candidate.exists() {
+                if let Some(workspace) = Self::load(&candidate)? {
+                    return Ok(Some(workspace));
+                }
+            }
         }
         
-        Ok(dependencies)
+        Ok(None)
+    }
+}
+
+impl WorkspaceMember {
+    fn new(dir: &Path, manifest_path: PathBuf, cargo_toml: &Table) -> Self {
+        // Fall back to the directory name for manifests without a package name
+        let name = package_name(cargo_toml).unwrap_or_else(|| {
+            dir.file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| "root".to_string())
+        });
+        
+        Self {
+            name,
+            path: dir.to_path_buf(),
+            manifest_path,
+        }
     }
 }
 
+impl CargoTargets {
+    /// Read the targets of the package whose manifest is at `manifest_path`
+    pub fn load<P: AsRef<Path>>(manifest_path: P) -> Result<Self> {
+        let manifest_path = manifest_path.as_ref();
+        let cargo_toml = read_manifest(manifest_path)?;
+        let root = manifest_path.parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        
+        let mut roots = Vec::new();
+        let existing = |path: &str| root.join(path).exists().then(|| PathBuf::from(path));
+        
+        // Library: `[lib] path` or src/lib.rs
+        let lib_path = cargo_toml.get("lib")
+            .and_then(|lib| lib.get("path"))
+            .and_then(|path| path.as_str())
+            .map(PathBuf::from)
+            .or_else(|| existing("src/lib.rs"));
+        let has_lib = lib_path.is_some();
+        roots.extend(lib_path.map(|path| (path, TargetKind::Lib)));
+        
+        // Build script: `package.build` (a path, or `false` to disable it) or build.rs
+        let build = cargo_toml.get("package").and_then(|package| package.get("build"));
+        let build_path = match build {
+            Some(toml::Value::String(path)) => Some(PathBuf::from(path)),
+            Some(toml::Value::Boolean(false)) => None,
+            _ => existing("build.rs"),
+        };
+        roots.extend(build_path.map(|path| (path, TargetKind::Build)));
+        
+        roots.extend(existing("src/main.rs").map(|path| (path, TargetKind::Bin)));
+        
+        // Explicitly declared targets with custom paths
+        for (table, kind) in [
+            ("bin", TargetKind::Bin),
+            ("test", TargetKind::Test),
+            ("bench", TargetKind::Bench),
+            ("example", TargetKind::Example),
+        ] {
+            let targets = cargo_toml.get(table).and_then(|t| t.as_array()).into_iter().flatten();
+            
+            for target in targets {
+                if let Some(path) = target.get("path").and_then(|p| p.as_str()) {
+                    roots.push((PathBuf::from(path), kind));
+                }
+            }
+        }
+        
+        // Autodiscovered targets, unless disabled with `autobins = false` and friends
+        for (dir, kind, key) in [
+            ("src/bin", TargetKind::Bin, "autobins"),
+            ("tests", TargetKind::Test, "autotests"),
+            ("benches", TargetKind::Bench, "autobenches"),
+            ("examples", TargetKind::Example, "autoexamples"),
+        ] {
+            let enabled = cargo_toml.get("package")
+                .and_then(|package| package.get(key))
+                .and_then(|auto| auto.as_bool())
+                .unwrap_or(true);
+            if !enabled {
+                continue;
+            }
+            
+            for path in discover_target_roots(&root, dir) {
+                if !roots.iter().any(|(existing, _)| *existing == path) {
+                    roots.push((path, kind));
+                }
+            }
+        }
+        
+        Ok(Self {
+            root,
+            roots,
+            has_lib,
+        })
+    }
+    
+    /// Root files of all targets with their kind, library first
+    pub fn roots(&self) -> impl Iterator<Item = (PathBuf, TargetKind)> + '_ {
+        self.roots.iter().map(|(path, kind)| (self.root.join(path), *kind))
+    }
+    
+    /// Determine the target a source file of the package belongs to
+    pub fn classify(&self, file: &Path) -> TargetKind {
+        let relative = file.strip_prefix(&self.root).unwrap_or(file);
+        
+        // Target root files
+        if let Some((_, kind)) = self.roots.iter().find(|(path, _)| path == relative) {
+            return *kind;
+        }
+        
+        // Autodiscovery directories
+        let mut components = relative.components().map(|c| c.as_os_str().to_string_lossy());
+        match (components.next().as_deref(), components.next().as_deref()) {
+            (Some("tests"), _) => return TargetKind::Test,
+            (Some("benches"), _) => return TargetKind::Bench,
+            (Some("examples"), _) => return TargetKind::Example,
+            (Some("src"), Some("bin")) => return TargetKind::Bin,
+            _ => {}
+        }
+        
+        // Modules next to a target root with a custom location belong to that target
+        let owner = self.roots.iter()
+            .filter(|(path, _)| {
+                let dir = path.parent().unwrap_or(Path::new(""));
+                !dir.as_os_str().is_empty() && dir != Path::new("src") && relative.starts_with(dir)
+            })
+            .max_by_key(|(path, _)| path.components().count());
+        if let Some((_, kind)) = owner {
+            return *kind;
+        }
+        
+        // Remaining modules belong to the library, or to the binary of a binary-only package
+        if self.has_lib {
+            TargetKind::Lib
+        } else {
+            TargetKind::Bin
+        }
+    }
+}
+
+/// Target roots Cargo discovers in a directory of a package: `dir/*.rs` and `dir/*/main.rs`
+fn discover_target_roots(package_root: &Path, dir: &str) -> Vec<PathBuf> {
+    let entries = match fs::read_dir(package_root.join(dir)) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    
+    let mut names: Vec<_> = entries.filter_map(Result::ok).map(|entry| entry.file_name()).collect();
+    names.sort();
+    
+    names.into_iter()
+        .map(|name| Path::new(dir).join(name))
+        .filter_map(|path| {
+            let full_path = package_root.join(&path);
+            if full_path.is_dir() {
+                full_path.join("main.rs").exists().then(|| path.join("main.rs"))
+            } else {
+                path.extension().is_some_and(|ext| ext == "rs").then_some(path)
+            }
+        })
+        .collect()
+}
+
+/// Where a manifest's dependency tables are being read from, used to resolve relative paths
+struct DependencyContext<'a> {
+    /// Directory containing the manifest being parsed
+    manifest_dir: &'a Path,
+    /// Workspace the manifest belongs to, for `workspace = true` inheritance
+    workspace: Option<&'a CargoWorkspace>,
+}
+
+/// Normalize a crate name the way rustc sees it: hyphens become underscores
+pub fn normalize_crate_name(name: &str) -> String {
+    name.replace('-', "_")
+}
+
+/// Resolve the identifier a dependency is referred to by in Rust code, as far as its declaration tells.
+///
+/// Renamed dependencies (`foo = { package = "bar" }`) are always referred to by their key.
+/// Otherwise a custom `[lib] name` of a path dependency wins. Registry dependencies are
+/// assumed to be named after their key; see [`registry_lib_name`] for the exceptions.
+pub fn resolve_crate_name(key: &str, package: Option<&str>, local_dir: Option<&Path>) -> String {
+    let lib_name = match (package, local_dir) {
+        (None, Some(dir)) => read_lib_name(&dir.join("Cargo.toml")),
+        _ => None,
+    };
+    
+    normalize_crate_name(lib_name.as_deref().unwrap_or(key))
+}
+
+/// Look up the custom `[lib] name` of a registry dependency in the local Cargo registry cache.
+/// This searches the cache, so it is only done for dependencies not found under their key.
+pub fn registry_lib_name(dep: &Dependency) -> Option<String> {
+    if dep.package.is_some() || !matches!(dep.source, DependencySource::Registry { .. }) {
+        return None;
+    }
+    
+    let manifest = find_registry_manifest(&dep.name, dep.version.as_deref())?;
+    read_lib_name(&manifest).map(|name| normalize_crate_name(&name))
+}
+
+/// Read the `[lib] name` of a manifest, if it declares one
+fn read_lib_name(manifest_path: &Path) -> Option<String> {
+    let cargo_toml = read_manifest(manifest_path).ok()?;
+    cargo_toml.get("lib")
+        .and_then(|lib| lib.get("name"))
+        .and_then(|name| name.as_str())
+        .map(|name| name.to_string())
+}
+
+/// Find the manifest of a registry package in the local Cargo cache, preferring
+/// versions that match the version requirement
+pub fn find_registry_manifest(package: &str, version: Option<&str>) -> Option<PathBuf> {
+    let cargo_home = std::env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".cargo")))?;
+    
+    let pattern = cargo_home.join("registry/src/*").join(format!("{}-*", package));
+    let mut candidates: Vec<PathBuf> = glob::glob(&pattern.to_string_lossy())
+        .ok()?
+        .filter_map(Result::ok)
+        .filter(|dir| {
+            // `serde-*` must not match `serde-value-0.7`, so require a version right after the name
+            dir.file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| n.strip_prefix(package))
+                .and_then(|rest| rest.strip_prefix('-'))
+                .is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_digit()))
+        })
+        .collect();
+    candidates.sort();
+    
+    let requirement = version.map(|v| v.trim_start_matches(['^', '~', '=', ' ']));
+    let preferred = requirement.and_then(|req| {
+        candidates.iter().rev().find(|dir| {
+            dir.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n[package.len() + 1..].starts_with(req))
+        })
+    });
+    
+    preferred.or(candidates.last())
+        .map(|dir| dir.join("Cargo.toml"))
+        .filter(|manifest| manifest.exists())
+}
+
+/// Read and parse a Cargo.toml file into a TOML table
+fn read_manifest(manifest_path: &Path) -> Result<Table> {
+    let content = fs::read_to_string(manifest_path)
+        .with_context(|| format!("Failed to read Cargo.toml at {:?}", manifest_path))?;
+    
+    toml::from_str(&content)
+        .with_context(|| format!("Failed to parse Cargo.toml at {:?}", manifest_path))
+}
+
+/// Get the package name declared in a parsed Cargo.toml, if any
+pub fn package_name(cargo_toml: &Table) -> Option<String> {
+    cargo_toml.get("package")
+        .and_then(|p| p.get("name"))
+        .and_then(|n| n.as_str())
+        .map(|n| n.to_string())
+}
+
+/// Read the package name from the Cargo.toml at `manifest_path`, if it declares one
+pub fn read_package_name<P: AsRef<Path>>(manifest_path: P) -> Result<Option<String>> {
+    Ok(package_name(&read_manifest(manifest_path.as_ref())?))
+}
+
+/// Read the `[features]` table of the Cargo.toml at `manifest_path`: each feature with the
+/// features and dependencies it enables
+pub fn read_feature_table<P: AsRef<Path>>(manifest_path: P) -> Result<HashMap<String, Vec<String>>> {
+    let cargo_toml = read_manifest(manifest_path.as_ref())?;
+    
+    let features = cargo_toml.get("features")
+        .and_then(|features| features.as_table())
+        .map(|features| features.iter()
+            .map(|(name, enables)| (name.clone(), string_array(Some(enables))))
+            .collect())
+        .unwrap_or_default();
+    
+    Ok(features)
+}
+
+/// Read the edition of the package whose manifest is at `manifest_path`, following
+/// `edition.workspace = true` to `[workspace.package]` of `workspace`, the workspace the
+/// package belongs to; `None` when it is not declared
+pub fn read_edition<P: AsRef<Path>>(manifest_path: P, workspace: Option<&CargoWorkspace>) -> Result<Option<String>> {
+    let manifest_path = manifest_path.as_ref();
+    let cargo_toml = read_manifest(manifest_path)?;
+    
+    let edition = match cargo_toml.get("package").and_then(|package| package.get("edition")) {
+        Some(toml::Value::String(edition)) => Some(edition.clone()),
+        Some(inherited) if inherited.get("workspace").and_then(|w| w.as_bool()) == Some(true) => {
+            match workspace {
+                Some(workspace) => read_manifest(&workspace.root.join("Cargo.toml"))?
+                    .get("workspace")
+                    .and_then(|w| w.get("package"))
+                    .and_then(|p| p.get("edition"))
+                    .and_then(|e| e.as_str())
+                    .map(|e| e.to_string()),
+                None => None,
+            }
+        }
+        _ => None,
+    };
+    
+    Ok(edition)
+}
+
+/// Collect the string entries of an optional TOML array
+fn string_array(value: Option<&toml::Value>) -> Vec<String> {
+    value.and_then(|v| v.as_array())
+        .map(|items| items.iter()
+            .filter_map(|item| item.as_str().map(|s| s.to_string()))
+            .collect())
+        .unwrap_or_default()
+}
+
 impl CargoParser {
+    /// Parse a Cargo.toml file of a package belonging to `workspace`, which was already loaded,
+    /// and return the dependencies
+    pub fn parse_in_workspace<P: AsRef<Path>>(path: P, workspace: Option<&CargoWorkspace>) -> Result<Vec<Dependency>> {
+        let manifest_path = path.as_ref();
+        let cargo_toml = read_manifest(manifest_path)?;
+        
+        let context = DependencyContext {
+            manifest_dir: manifest_path.parent().unwrap_or_else(|| Path::new(".")),
+            workspace,
+        };
+        
+        let mut dependencies = Vec::new();
+        let dependency_types = [DependencyType::Normal, DependencyType::Development, DependencyType::Build];
+        
+        // Process the normal, dev and build dependency tables
+        for dep_type in &dependency_types {
+            if let Some(deps) = cargo_toml.get(dependency_table_name(dep_type)).and_then(|d| d.as_table()) {
+                Self::extract_dependencies(deps, &context, &mut dependencies, dep_type, None);
+            }
+        }
+        
+        // Process platform-specific tables such as `[target.'cfg(unix)'.dependencies]`
+        if let Some(platforms) = cargo_toml.get("target").and_then(|t| t.as_table()) {
+            for (platform, tables) in platforms {
+                for dep_type in &dependency_types {
+                    if let Some(deps) = tables.get(dependency_table_name(dep_type)).and_then(|d| d.as_table()) {
+                        Self::extract_dependencies(deps, &context, &mut dependencies, dep_type, Some(platform));
+                    }
+                }
+            }
+        }
+        
+        Ok(dependencies)
+    }
+    
+    /// Extract the dependencies of a table, declared for every platform or for `platform` only.
+    /// A dependency already declared in a table of the same kind for other platforms is merged into.
     fn extract_dependencies(
         deps_table: &Table,
-        dependencies: &mut Vec<CargoDependency>,
-        dep_type: DependencyType,
+        context: &DependencyContext,
+        dependencies: &mut Vec<Dependency>,
+        dep_type: &DependencyType,
+        platform: Option<&str>,
     ) {
         for (name, value) in deps_table {
-            let mut dep = CargoDependency {
+            let mut dep = Dependency {
                 name: name.clone(),
+                import_name: normalize_crate_name(name),
+                package: None,
                 version: None,
                 features: Vec::new(),
                 optional: false,
                 dependency_type: dep_type.clone(),
-                source: "Cargo.toml".to_string(),
+                source: DependencySource::default(),
+                extras: DependencyExtras::Rust(CargoExtras {
+                    default_features: true,
+                    platforms: platform.into_iter().map(str::to_string).collect(),
+                    all_platforms: platform.is_none(),
+                }),
             };
             
             match value {
@@ -86,20 +553,17 @@ impl CargoParser {
                     dep.version = Some(version.clone());
                 }
                 toml::Value::Table(table) => {
-                    // Handle inline table specification
-                    if let Some(version) = table.get("version").and_then(|v| v.as_str()) {
-                        dep.version = Some(version.to_string());
-                    }
-                    
-                    if let Some(features) = table.get("features").and_then(|f| f.as_array()) {
-                        dep.features = features.iter()
-                            .filter_map(|f| f.as_str().map(|s| s.to_string()))
-                            .collect();
+                    // `dep = { workspace = true }` starts from the workspace specification,
+                    // whose paths are relative to the workspace root
+                    if table.get("workspace").and_then(|w| w.as_bool()) == Some(true) {
+                        if let Some(workspace) = context.workspace {
+                            if let Some(inherited) = workspace.dependencies.get(name) {
+                                Self::apply_specification(&mut dep, inherited, &workspace.root);
+                            }
+                        }
                     }
                     
-                    if let Some(optional) = table.get("optional").and_then(|o| o.as_bool()) {
-                        dep.optional = optional;
-                    }
+                    Self::apply_specification(&mut dep, value, context.manifest_dir);
                 }
                 _ => {
                     // Skip other value types
@@ -107,7 +571,234 @@ impl CargoParser {
                 }
             }
             
-            dependencies.push(dep);
+            // The directory of a path dependency is where its `[lib] name` is looked up
+            let local_dir = match &dep.source {
+                DependencySource::Path { path } => Some(path.as_path()),
+                _ => None,
+            };
+            dep.import_name = resolve_crate_name(name, dep.package.as_deref(), local_dir);
+            
+            match dependencies.iter_mut().find(|existing| existing.name == dep.name && existing.dependency_type == dep.dependency_type) {
+                Some(existing) => merge_declaration(existing, dep),
+                None => dependencies.push(dep),
+            }
+        }
+    }
+    
+    /// Apply a dependency specification (a version string or inline table) to a dependency,
+    /// resolving a `path` against `base_dir`. Features are additive so that member features
+    /// extend inherited workspace features.
+    fn apply_specification(dep: &mut Dependency, value: &toml::Value, base_dir: &Path) {
+        match value {
+            toml::Value::String(version) => {
+                dep.version = Some(version.clone());
+            }
+            toml::Value::Table(table) => {
+                // Handle inline table specification
+                if let Some(version) = table.get("version").and_then(|v| v.as_str()) {
+                    dep.version = Some(version.to_string());
+                }
+                
+                for feature in string_array(table.get("features")) {
+                    if !dep.features.contains(&feature) {
+                        dep.features.push(feature);
+                    }
+                }
+                
+                if let Some(optional) = table.get("optional").and_then(|o| o.as_bool()) {
+                    dep.optional = optional;
+                }
+                
+                if let Some(package) = table.get("package").and_then(|p| p.as_str()) {
+                    dep.package = Some(package.to_string());
+                }
+                
+                // `default_features` is the deprecated spelling
+                if let Some(default_features) = table.get("default-features")
+                    .or_else(|| table.get("default_features"))
+                    .and_then(|d| d.as_bool())
+                {
+                    if let DependencyExtras::Rust(cargo) = &mut dep.extras {
+                        cargo.default_features = default_features;
+                    }
+                }
+                
+                // A path takes precedence over the registry version published alongside it
+                let string = |key: &str| table.get(key).and_then(|v| v.as_str()).map(str::to_string);
+                if let Some(path) = string("path") {
+                    dep.source = DependencySource::Path { path: base_dir.join(path) };
+                } else if let Some(url) = string("git") {
+                    dep.source = DependencySource::Git {
+                        url,
+                        branch: string("branch"),
+                        tag: string("tag"),
+                        rev: string("rev"),
+                    };
+                } else if let Some(registry) = string("registry") {
+                    dep.source = DependencySource::Registry { registry: Some(registry) };
+                }
+            }
+            _ => {}
+        }
+    }
+} 
+
+/// Merge another declaration of the same dependency, from a table for other platforms.
+/// Cargo unifies the features of every declaration that applies.
+fn merge_declaration(dep: &mut Dependency, other: Dependency) {
+    for feature in other.features {
+        if !dep.features.contains(&feature) {
+            dep.features.push(feature);
         }
     }
-} 
\ No newline at end of file
+    
+    if let (DependencyExtras::Rust(cargo), DependencyExtras::Rust(other)) = (&mut dep.extras, other.extras) {
+        cargo.default_features |= other.default_features;
+        cargo.all_platforms |= other.all_platforms;
+        for platform in other.platforms {
+            if !cargo.platforms.contains(&platform) {
+                cargo.platforms.push(platform);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+    
+    /// A directory containing `files`, given as relative paths and contents
+    fn project(files: &[(&str, &str)]) -> TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for (path, content) in files {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, content).unwrap();
+        }
+        dir
+    }
+    
+    /// The dependency of `kind` declared as `name`
+    fn find<'a>(dependencies: &'a [Dependency], name: &str, kind: DependencyType) -> &'a Dependency {
+        dependencies.iter()
+            .find(|dep| dep.name == name && dep.dependency_type == kind)
+            .unwrap_or_else(|| panic!("{} is not declared as a {:?} dependency", name, kind))
+    }
+    
+    /// Features, default features and platforms of a dependency, as `(features, default_features,
+    /// platforms, all_platforms)`
+    fn declaration(dep: &Dependency) -> (Vec<&str>, bool, Vec<&str>, bool) {
+        match &dep.extras {
+            DependencyExtras::Rust(cargo) => (
+                dep.features.iter().map(String::as_str).collect(),
+                cargo.default_features,
+                cargo.platforms.iter().map(String::as_str).collect(),
+                cargo.all_platforms,
+            ),
+            extras => panic!("{} has no Cargo details: {:?}", dep.name, extras),
+        }
+    }
+    
+    #[test]
+    fn merges_declarations_for_other_platforms() {
+        let dir = project(&[("Cargo.toml", "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\ntokio = { version = \"1\", features = [\"rt\"], default-features = false }\n\n[target.'cfg(unix)'.dependencies]\ntokio = { version = \"1\", features = [\"net\"] }\nsocket2 = \"0.5\"\n\n[target.'cfg(windows)'.dependencies]\nsocket2 = { version = \"0.5\", features = [\"all\"] }\n\n[target.'cfg(windows)'.dev-dependencies]\ntokio = { version = \"1\", features = [\"test-util\"] }\n")]);
+        let dependencies = CargoParser::parse(dir.path().join("Cargo.toml")).unwrap();
+        assert_eq!(dependencies.len(), 3);
+        
+        // Default features are on as soon as one declaration leaves them on
+        assert_eq!(declaration(find(&dependencies, "tokio", DependencyType::Normal)), (vec!["rt", "net"], true, vec!["cfg(unix)"], true));
+        assert_eq!(declaration(find(&dependencies, "socket2", DependencyType::Normal)), (vec!["all"], true, vec!["cfg(unix)", "cfg(windows)"], false));
+        assert_eq!(declaration(find(&dependencies, "tokio", DependencyType::Development)), (vec!["test-util"], true, vec!["cfg(windows)"], false));
+    }
+    
+    #[test]
+    fn reads_the_source_of_dependencies() {
+        let dir = project(&[("Cargo.toml", "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\nserde = \"1\"\nlocal = { version = \"0.1\", path = \"crates/local\" }\nforked = { git = \"https://example.com/forked.git\", tag = \"v1\" }\ninternal = { version = \"2\", registry = \"corp\", default_features = false }\n")]);
+        let dependencies = CargoParser::parse(dir.path().join("Cargo.toml")).unwrap();
+        let source = |name: &str| find(&dependencies, name, DependencyType::Normal).source.clone();
+        
+        assert_eq!(source("serde"), DependencySource::Registry { registry: None });
+        assert_eq!(source("local"), DependencySource::Path { path: dir.path().join("crates/local") });
+        assert_eq!(source("forked"), DependencySource::Git {
+            url: "https://example.com/forked.git".to_string(),
+            branch: None,
+            tag: Some("v1".to_string()),
+            rev: None,
+        });
+        assert_eq!(source("internal"), DependencySource::Registry { registry: Some("corp".to_string()) });
+        assert!(!declaration(find(&dependencies, "internal", DependencyType::Normal)).1);
+    }
+    
+    /// Workspace with the root package `app`, members matched by a glob with one of them excluded,
+    /// and shared dependency specifications
+    fn workspace() -> TempDir {
+        project(&[
+            ("Cargo.toml", "[workspace]\nmembers = [\"crates/*\", \"tools/cli\"]\nexclude = [\"crates/experimental\"]\n\n[workspace.dependencies]\nserde = { version = \"1\", features = [\"derive\"] }\nshared = { path = \"crates/shared\" }\n\n[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\nserde = { workspace = true, features = [\"rc\"], optional = true }\n"),
+            ("crates/api/Cargo.toml", "[package]\nname = \"api\"\nversion = \"0.1.0\"\n\n[dependencies]\nserde.workspace = true\nshared = { workspace = true }\n"),
+            ("crates/shared/Cargo.toml", "[package]\nname = \"shared\"\nversion = \"0.1.0\"\n"),
+            ("crates/experimental/Cargo.toml", "[package]\nname = \"experimental\"\nversion = \"0.1.0\"\n"),
+            ("crates/notes/README.md", ""),
+            ("tools/cli/Cargo.toml", "[package]\nname = \"cli\"\nversion = \"0.1.0\"\n"),
+        ])
+    }
+    
+    #[test]
+    fn loads_workspace_members_matching_the_member_globs() {
+        let dir = workspace();
+        let workspace = CargoWorkspace::load(dir.path().join("Cargo.toml")).unwrap().unwrap();
+        
+        // The root package comes first; directories without a manifest are not members
+        let members: Vec<(&str, PathBuf)> = workspace.members.iter()
+            .map(|member| (member.name.as_str(), member.path.strip_prefix(dir.path()).unwrap().to_path_buf()))
+            .collect();
+        assert_eq!(members, vec![
+            ("app", PathBuf::new()),
+            ("api", PathBuf::from("crates/api")),
+            ("shared", PathBuf::from("crates/shared")),
+            ("cli", PathBuf::from("tools/cli")),
+        ]);
+        
+        let found = CargoWorkspace::find_for(dir.path().join("tools/cli/Cargo.toml")).unwrap().unwrap();
+        assert_eq!(found.root, dir.path());
+        assert!(CargoWorkspace::load(dir.path().join("crates/api/Cargo.toml")).unwrap().is_none());
+    }
+    
+    #[test]
+    fn inherits_workspace_dependencies() {
+        let dir = workspace();
+        
+        // Paths of inherited specifications are relative to the workspace root
+        let api = CargoParser::parse(dir.path().join("crates/api/Cargo.toml")).unwrap();
+        let shared = find(&api, "shared", DependencyType::Normal);
+        assert_eq!(shared.source, DependencySource::Path { path: dir.path().join("crates/shared") });
+        let serde = find(&api, "serde", DependencyType::Normal);
+        assert_eq!((serde.version.as_deref(), declaration(serde).0), (Some("1"), vec!["derive"]));
+        
+        // Member keys extend the inherited specification
+        let app = CargoParser::parse(dir.path().join("Cargo.toml")).unwrap();
+        let serde = find(&app, "serde", DependencyType::Normal);
+        assert_eq!((serde.version.as_deref(), declaration(serde).0, serde.optional), (Some("1"), vec!["derive", "rc"], true));
+    }
+    
+    #[test]
+    fn resolves_the_crate_names_code_refers_to() {
+        let dir = project(&[
+            ("Cargo.toml", "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\nproc-macro2 = \"1\"\njson = { package = \"serde_json\", version = \"1\" }\nutils = { path = \"crates/utils\" }\nhelpers = { package = \"app-helpers\", path = \"crates/utils\" }\nplain-lib = { path = \"crates/plain\" }\n"),
+            ("crates/utils/Cargo.toml", "[package]\nname = \"app-utils\"\nversion = \"0.1.0\"\n\n[lib]\nname = \"my-utils\"\n"),
+            ("crates/plain/Cargo.toml", "[package]\nname = \"plain-lib\"\nversion = \"0.1.0\"\n"),
+        ]);
+        let dependencies = CargoParser::parse(dir.path().join("Cargo.toml")).unwrap();
+        let import_name = |name: &str| find(&dependencies, name, DependencyType::Normal).import_name.as_str();
+        
+        assert_eq!(import_name("proc-macro2"), "proc_macro2");
+        assert_eq!(import_name("json"), "json");
+        assert_eq!(import_name("utils"), "my_utils");
+        // A renamed dependency is referred to by its key, whatever its library is called
+        assert_eq!(import_name("helpers"), "helpers");
+        assert_eq!(import_name("plain-lib"), "plain_lib");
+        
+        assert_eq!(resolve_crate_name("tokio-util", None, Some(&dir.path().join("missing"))), "tokio_util");
+    }
+}
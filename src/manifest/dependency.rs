@@ -0,0 +1,231 @@
+use std::fmt;
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+
+use super::editor::dependency_table_header;
+use super::nodejs::dependency_field;
+
+/// Package ecosystem a dependency comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Ecosystem {
+    /// Crates declared in Cargo.toml
+    Rust,
+    /// Packages declared in package.json
+    Node,
+    /// Distributions declared in pyproject.toml, setup.cfg or requirements files
+    Python,
+}
+
+/// A dependency declared in a manifest, whatever the ecosystem
+#[derive(Debug, Clone, Deserialize)]
+pub struct Dependency {
+    /// Name of the dependency as declared in the manifest (the table or object key)
+    pub name: String,
+    /// Name code refers to the dependency by: the crate identifier in Rust (e.g. `proc_macro2`),
+    /// the package name in import specifiers for Node
+    pub import_name: String,
+    /// Actual package name when the dependency is renamed, with `package = "..."` in Cargo
+    /// or an `npm:` alias in package.json
+    pub package: Option<String>,
+    /// Version requirement, if the dependency is fetched from a registry by version
+    pub version: Option<String>,
+    /// Features enabled on the dependency, for ecosystems that have them
+    pub features: Vec<String>,
+    /// Whether the dependency is only installed on request: an optional Cargo dependency
+    /// enabled by a feature, or an npm optional or optional peer dependency
+    pub optional: bool,
+    pub dependency_type: DependencyType,
+    /// Where the dependency is fetched from
+    pub source: DependencySource,
+    /// What only the ecosystem of the dependency knows about it
+    pub extras: DependencyExtras,
+}
+
+/// Kind of a dependency: the manifest table or field it is declared in
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub enum DependencyType {
+    Normal,
+    Development,
+    Build,
+    /// A package expected to be provided by the dependent project (npm `peerDependencies`)
+    Peer,
+}
+
+/// Where a dependency is fetched from
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum DependencySource {
+    /// A registry: the default one of the ecosystem, unless `registry` names another one
+    Registry {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        registry: Option<String>,
+    },
+    /// A local directory
+    Path {
+        path: PathBuf,
+    },
+    /// A git repository, at a branch, tag or revision if one is given
+    Git {
+        url: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        branch: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        tag: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        rev: Option<String>,
+    },
+    /// An archive downloaded from a URL
+    Url {
+        url: String,
+    },
+}
+
+/// Ecosystem-specific details of a dependency
+#[derive(Debug, Clone, Deserialize)]
+pub enum DependencyExtras {
+    Rust(CargoExtras),
+    Node(NodeExtras),
+    Python(PythonExtras),
+}
+
+/// Details of a dependency declared in Cargo.toml
+#[derive(Debug, Clone, Deserialize)]
+pub struct CargoExtras {
+    /// Whether the default features of the dependency are enabled
+    pub default_features: bool,
+    /// Platforms (`cfg(...)` expressions or target triples) of the `[target.<platform>.*]`
+    /// tables declaring the dependency
+    pub platforms: Vec<String>,
+    /// Whether the dependency is declared in a table applying to every platform
+    pub all_platforms: bool,
+}
+
+/// Details of a dependency declared in package.json
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct NodeExtras {
+    /// Whether the dependency is packed into the published package (`bundleDependencies`)
+    pub bundled: bool,
+}
+
+/// Details of a dependency declared in a Python manifest
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PythonExtras {
+    /// Extras of the distribution requested with the dependency, e.g. `security` in `requests[security]`
+    pub extras: Vec<String>,
+    /// PEP 508 environment markers restricting where the dependency is installed,
+    /// e.g. `python_version < "3.8"`
+    pub markers: Option<String>,
+    /// Manifest tables and requirements files declaring the dependency, e.g. `project.dependencies`
+    /// or `requirements-dev.txt`
+    pub declared_in: Vec<String>,
+}
+
+impl Ecosystem {
+    /// Short lowercase identifier, as used in exports
+    pub fn id(&self) -> &'static str {
+        match self {
+            Ecosystem::Rust => "rust",
+            Ecosystem::Node => "node",
+            Ecosystem::Python => "python",
+        }
+    }
+    
+    /// Registry dependencies are fetched from when no other one is named
+    pub fn default_registry(&self) -> &'static str {
+        match self {
+            Ecosystem::Rust => "crates.io",
+            Ecosystem::Node => "npm",
+            Ecosystem::Python => "PyPI",
+        }
+    }
+}
+
+impl fmt::Display for Ecosystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ecosystem::Rust => write!(f, "Rust"),
+            Ecosystem::Node => write!(f, "Node.js"),
+            Ecosystem::Python => write!(f, "Python"),
+        }
+    }
+}
+
+impl Dependency {
+    /// Ecosystem the dependency belongs to
+    pub fn ecosystem(&self) -> Ecosystem {
+        match self.extras {
+            DependencyExtras::Rust(_) => Ecosystem::Rust,
+            DependencyExtras::Node(_) => Ecosystem::Node,
+            DependencyExtras::Python(_) => Ecosystem::Python,
+        }
+    }
+    
+    /// Cargo-specific details, for dependencies declared in Cargo.toml
+    pub fn cargo(&self) -> Option<&CargoExtras> {
+        match &self.extras {
+            DependencyExtras::Rust(extras) => Some(extras),
+            _ => None,
+        }
+    }
+    
+    /// Headers of the manifest tables or fields declaring the dependency, e.g. `dependencies`,
+    /// `target.'cfg(unix)'.dependencies`, `devDependencies` or `requirements.txt`
+    pub fn table_headers(&self) -> Vec<String> {
+        match &self.extras {
+            DependencyExtras::Rust(_) => self.table_platforms()
+                .map(|platform| dependency_table_header(&self.dependency_type, platform))
+                .collect(),
+            DependencyExtras::Node(_) => vec![dependency_field(&self.dependency_type, self.optional).to_string()],
+            DependencyExtras::Python(python) => python.declared_in.clone(),
+        }
+    }
+    
+    /// Platforms of the tables declaring the dependency, `None` for the table applying to every platform
+    pub fn table_platforms(&self) -> impl Iterator<Item = Option<&str>> {
+        let (all_platforms, platforms) = match self.cargo() {
+            Some(cargo) => (cargo.all_platforms, cargo.platforms.as_slice()),
+            None => (true, &[][..]),
+        };
+        
+        all_platforms.then_some(None)
+            .into_iter()
+            .chain(platforms.iter().map(|platform| Some(platform.as_str())))
+    }
+}
+
+impl Default for DependencySource {
+    fn default() -> Self {
+        DependencySource::Registry { registry: None }
+    }
+}
+
+impl DependencySource {
+    /// Describe the source, naming the default registry of `ecosystem` for registry dependencies
+    pub fn describe(&self, ecosystem: Ecosystem) -> String {
+        match self {
+            DependencySource::Registry { registry: None } => ecosystem.default_registry().to_string(),
+            source => source.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for DependencySource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DependencySource::Registry { registry: None } => write!(f, "default registry"),
+            DependencySource::Registry { registry: Some(registry) } => write!(f, "registry {}", registry),
+            DependencySource::Path { path } => write!(f, "path {}", path.display()),
+            DependencySource::Git { url, branch, tag, rev } => {
+                write!(f, "git {}", url)?;
+                match (branch, tag, rev) {
+                    (Some(branch), _, _) => write!(f, " (branch {})", branch),
+                    (_, Some(tag), _) => write!(f, " (tag {})", tag),
+                    (_, _, Some(rev)) => write!(f, " (rev {})", rev),
+                    _ => Ok(()),
+                }
+            }
+            DependencySource::Url { url } => write!(f, "url {}", url),
+        }
+    }
+}
@@ -0,0 +1,309 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{Result, Context};
+use toml_edit::{DocumentMut, Item, Table, Value};
+
+use crate::manifest::dependency::DependencyType;
+
+/// Edits a Cargo.toml in place, keeping its comments, ordering and formatting intact
+pub struct CargoManifestEditor {
+    path: PathBuf,
+    original: String,
+    document: DocumentMut,
+}
+
+impl CargoManifestEditor {
+    /// Open the manifest at `path` for editing
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let original = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read Cargo.toml at {:?}", path))?;
+        let document = original.parse::<DocumentMut>()
+            .with_context(|| format!("Failed to parse Cargo.toml at {:?}", path))?;
+        
+        Ok(Self {
+            path: path.to_path_buf(),
+            original,
+            document,
+        })
+    }
+    
+    /// Path of the manifest being edited
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    
+    /// Manifest content as it was when opened
+    pub fn original(&self) -> &str {
+        &self.original
+    }
+    
+    /// Manifest content including the edits made so far
+    pub fn contents(&self) -> String {
+        self.document.to_string()
+    }
+    
+    /// Whether any edit changed the manifest
+    pub fn is_modified(&self) -> bool {
+        self.contents() != self.original
+    }
+    
+    /// Remove a dependency from the table matching its type, for every platform or the
+    /// `[target.<platform>.*]` table of `platform`. Returns whether it was found.
+    ///
+    /// Comments above the removed entry are moved to the entry that follows it, so
+    /// section headings such as `# Utilities` survive the removal. Comments above the last
+    /// entry of a table move above the next table header, or to the end of the manifest.
+    pub fn remove_dependency(&mut self, name: &str, dependency_type: &DependencyType, platform: Option<&str>) -> bool {
+        let table_name = dependency_table_name(dependency_type);
+        let item = match platform {
+            Some(platform) => self.document.get_mut("target")
+                .and_then(Item::as_table_like_mut)
+                .and_then(|platforms| platforms.get_mut(platform))
+                .and_then(Item::as_table_like_mut)
+                .and_then(|tables| tables.get_mut(table_name)),
+            None => self.document.get_mut(table_name),
+        };
+        let item = match item {
+            Some(item) => item,
+            None => return false,
+        };
+        let position = item.as_table().and_then(Table::position);
+        let table = match item.as_table_like_mut() {
+            Some(table) => table,
+            None => return false,
+        };
+        
+        // Find the entry following the removed one before the removal shifts the order
+        let next_key = table.iter()
+            .map(|(key, _)| key.to_string())
+            .skip_while(|key| key != name)
+            .nth(1);
+        
+        let leading_comments = table.key(name)
+            .and_then(|key| key.leaf_decor().prefix())
+            .and_then(|prefix| prefix.as_str())
+            .filter(|prefix| prefix.contains('#'))
+            .map(str::to_string);
+        
+        if table.remove(name).is_none() {
+            return false;
+        }
+        
+        if let Some(comments) = leading_comments {
+            match next_key.and_then(|next_key| table.key_mut(&next_key)) {
+                Some(mut key) => {
+                    let next_prefix = key.leaf_decor()
+                        .prefix()
+                        .and_then(|prefix| prefix.as_str())
+                        .unwrap_or_default()
+                        .to_string();
+                    key.leaf_decor_mut().set_prefix(format!("{}{}", comments, next_prefix));
+                }
+                None => self.move_comments_after_table(position, &comments),
+            }
+        }
+        
+        true
+    }
+    
+    /// Keep comments that no longer precede an entry of the table at document `position`:
+    /// above the first table header after it, or at the end of the manifest
+    fn move_comments_after_table(&mut self, position: Option<usize>, comments: &str) {
+        let mut positions = Vec::new();
+        table_positions(self.document.as_table(), &mut positions);
+        let next_position = position.and_then(|position| positions.into_iter().filter(|&p| p > position).min());
+        
+        match next_position.and_then(|next_position| table_at_position(self.document.as_table_mut(), next_position)) {
+            Some(next_table) => {
+                let prefix = next_table.decor()
+                    .prefix()
+                    .and_then(|prefix| prefix.as_str())
+                    .unwrap_or_default()
+                    .to_string();
+                next_table.decor_mut().set_prefix(format!("{}{}", comments, prefix));
+            }
+            None => {
+                let trailing = self.document.trailing().as_str().unwrap_or_default().to_string();
+                self.document.set_trailing(format!("{}{}", trailing, comments));
+            }
+        }
+    }
+    
+    /// Remove references to a dependency from `[features]`: `dep:name`, `name/feature`,
+    /// `name?/feature` and, unless a feature is itself called `name`, bare `name` entries.
+    /// Returns the features that changed.
+    ///
+    /// Features themselves are kept, even when left empty, because other packages may enable them.
+    pub fn remove_feature_references(&mut self, name: &str) -> Vec<String> {
+        let features = match self.document.get_mut("features").and_then(Item::as_table_like_mut) {
+            Some(features) => features,
+            None => return Vec::new(),
+        };
+        
+        // A bare `name` entry enables the feature of that name when one is declared
+        let has_feature = features.contains_key(name);
+        
+        let mut changed = Vec::new();
+        for (feature, item) in features.iter_mut() {
+            let array = match item.as_value_mut().and_then(Value::as_array_mut) {
+                Some(array) => array,
+                None => continue,
+            };
+            
+            let before = array.len();
+            // The entry left first takes the spacing after `[` of the entry it replaces
+            let first_prefix = array.get(0).and_then(|value| value.decor().prefix()).cloned();
+            array.retain(|value| !value.as_str().is_some_and(|entry| references_dependency(entry, name, has_feature)));
+            
+            if array.len() != before {
+                if let (Some(first), Some(prefix)) = (array.get_mut(0), first_prefix) {
+                    first.decor_mut().set_prefix(prefix);
+                }
+                changed.push(feature.to_string());
+            }
+        }
+        
+        changed
+    }
+    
+    /// Write the edited manifest back to disk
+    pub fn save(&self) -> Result<()> {
+        fs::write(&self.path, self.contents())
+            .with_context(|| format!("Failed to write Cargo.toml at {:?}", self.path))
+    }
+}
+
+/// Name of the manifest table holding dependencies of the given type
+pub fn dependency_table_name(dependency_type: &DependencyType) -> &'static str {
+    match dependency_type {
+        // Cargo has no peer dependencies
+        DependencyType::Normal | DependencyType::Peer => "dependencies",
+        DependencyType::Development => "dev-dependencies",
+        DependencyType::Build => "build-dependencies",
+    }
+}
+
+/// Header of the dependency table of a type, for every platform or for `platform` only,
+/// e.g. `dependencies` or `target.'cfg(unix)'.dependencies`
+pub fn dependency_table_header(dependency_type: &DependencyType, platform: Option<&str>) -> String {
+    match platform {
+        Some(platform) => format!("target.'{}'.{}", platform, dependency_table_name(dependency_type)),
+        None => dependency_table_name(dependency_type).to_string(),
+    }
+}
+
+/// Collect the document positions of the table headers below `table`
+fn table_positions(table: &Table, positions: &mut Vec<usize>) {
+    for (_, item) in table.iter() {
+        let children: Vec<&Table> = match item {
+            Item::Table(child) => vec![child],
+            Item::ArrayOfTables(array) => array.iter().collect(),
+            _ => Vec::new(),
+        };
+        
+        for child in children {
+            if !child.is_implicit() {
+                positions.extend(child.position());
+            }
+            table_positions(child, positions);
+        }
+    }
+}
+
+/// Find the table whose header is at document `position` below `table`
+fn table_at_position(table: &mut Table, position: usize) -> Option<&mut Table> {
+    for (_, item) in table.iter_mut() {
+        let children: Vec<&mut Table> = match item {
+            Item::Table(child) => vec![child],
+            Item::ArrayOfTables(array) => array.iter_mut().collect(),
+            _ => Vec::new(),
+        };
+        
+        for child in children {
+            if !child.is_implicit() && child.position() == Some(position) {
+                return Some(child);
+            }
+            if let Some(found) = table_at_position(child, position) {
+                return Some(found);
+            }
+        }
+    }
+    
+    None
+}
+
+/// Whether a `[features]` entry refers to the dependency `name`
+fn references_dependency(entry: &str, name: &str, has_feature: bool) -> bool {
+    if let Some(dep) = entry.strip_prefix("dep:") {
+        return dep == name;
+    }
+    
+    match entry.split_once('/') {
+        Some((dep, _)) => dep.strip_suffix('?').unwrap_or(dep) == name,
+        None => entry == name && !has_feature,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    
+    /// Remove a dev-dependency from `manifest` and return the edited manifest
+    fn remove_dev_dependency(manifest: &str, name: &str) -> String {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Cargo.toml");
+        fs::write(&path, manifest).unwrap();
+        
+        let mut editor = CargoManifestEditor::open(&path).unwrap();
+        assert!(editor.remove_dependency(name, &DependencyType::Development, None));
+        editor.contents()
+    }
+    
+    #[test]
+    fn moves_comments_to_the_next_entry() {
+        let manifest = "[dev-dependencies]\n# Testing utilities\ntempfile = \"3\"\ntest-case = \"3\"\n";
+        
+        assert_eq!(
+            remove_dev_dependency(manifest, "tempfile"),
+            "[dev-dependencies]\n# Testing utilities\ntest-case = \"3\"\n",
+        );
+    }
+    
+    #[test]
+    fn moves_comments_of_the_last_entry_above_the_next_table() {
+        let manifest = "[dev-dependencies]\nserde = \"1\"\n\n# Testing utilities\ntempfile = \"3\"\n\n[profile.release]\nlto = true\n";
+        
+        assert_eq!(
+            remove_dev_dependency(manifest, "tempfile"),
+            "[dev-dependencies]\nserde = \"1\"\n\n# Testing utilities\n\n[profile.release]\nlto = true\n",
+        );
+    }
+    
+    #[test]
+    fn moves_comments_of_the_only_entry_to_the_end_of_the_manifest() {
+        let manifest = "[package]\nname = \"demo\"\n\n[dev-dependencies]\n# Testing utilities\ntempfile = \"3\"\n";
+        
+        assert_eq!(
+            remove_dev_dependency(manifest, "tempfile"),
+            "[package]\nname = \"demo\"\n\n[dev-dependencies]\n# Testing utilities\n",
+        );
+    }
+    
+    #[test]
+    fn keeps_comments_when_the_platform_table_is_emptied() {
+        let manifest = "[target.'cfg(unix)'.dependencies]\n# Signals\nlibc = \"0.2\"\n\n[features]\ndefault = []\n";
+        
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Cargo.toml");
+        fs::write(&path, manifest).unwrap();
+        let mut editor = CargoManifestEditor::open(&path).unwrap();
+        
+        assert!(editor.remove_dependency("libc", &DependencyType::Normal, Some("cfg(unix)")));
+        assert_eq!(
+            editor.contents(),
+            "[target.'cfg(unix)'.dependencies]\n# Signals\n\n[features]\ndefault = []\n",
+        );
+    }
+}
@@ -0,0 +1,286 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::Path;
+use anyhow::Result;
+
+use crate::manifest::cargo::{read_edition, read_feature_table, CargoWorkspace};
+use crate::manifest::dependency::Dependency;
+
+/// What a single entry of a feature in the `[features]` table enables
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FeatureEntry {
+    /// Another feature of the package, e.g. `"std"`
+    Feature(String),
+    /// An optional dependency without enabling a feature of the same name, e.g. `"dep:serde"`
+    Dependency(String),
+    /// A feature of a dependency, e.g. `"serde/derive"`; weak entries (`"serde?/derive"`)
+    /// only apply when something else enables the dependency
+    DependencyFeature {
+        dependency: String,
+        feature: String,
+        weak: bool,
+    },
+}
+
+impl FeatureEntry {
+    /// Parse an entry of the `[features]` table
+    pub fn parse(entry: &str) -> Self {
+        if let Some(dependency) = entry.strip_prefix("dep:") {
+            return FeatureEntry::Dependency(dependency.to_string());
+        }
+        
+        match entry.split_once('/') {
+            Some((dependency, feature)) => {
+                let weak = dependency.ends_with('?');
+                FeatureEntry::DependencyFeature {
+                    dependency: dependency.trim_end_matches('?').to_string(),
+                    feature: feature.to_string(),
+                    weak,
+                }
+            }
+            None => FeatureEntry::Feature(entry.to_string()),
+        }
+    }
+}
+
+/// The features of a package and what each of them enables, including the implicit
+/// features Cargo creates for optional dependencies
+#[derive(Debug, Clone, Default)]
+pub struct FeatureGraph {
+    /// Features declared in `[features]`
+    features: BTreeMap<String, Vec<FeatureEntry>>,
+    /// Optional dependencies, by the name they are declared with
+    optional: BTreeSet<String>,
+    /// Optional dependencies with an implicit feature of the same name
+    implicit: BTreeSet<String>,
+}
+
+impl FeatureGraph {
+    /// Read the `[features]` table of the manifest at `manifest_path`, of a package belonging to `workspace`
+    pub fn load(manifest_path: &Path, dependencies: &[Dependency], workspace: Option<&CargoWorkspace>) -> Result<Self> {
+        let table = read_feature_table(manifest_path)?;
+        // Edition 2024 dropped the implicit features of optional dependencies
+        let implicit_features = read_edition(manifest_path, workspace)?.is_none_or(|edition| edition.as_str() < "2024");
+        
+        let features = table.into_iter()
+            .map(|(name, entries)| (name, entries.iter().map(|entry| FeatureEntry::parse(entry)).collect()))
+            .collect();
+        
+        Ok(Self::new(features, dependencies, implicit_features))
+    }
+    
+    /// Build the graph from parsed `[features]` entries. With `implicit_features`, optional
+    /// dependencies never referenced as `dep:name` get a feature of the same name.
+    pub fn new(features: BTreeMap<String, Vec<FeatureEntry>>, dependencies: &[Dependency], implicit_features: bool) -> Self {
+        let optional: BTreeSet<String> = dependencies.iter()
+            .filter(|dep| dep.optional)
+            .map(|dep| dep.name.clone())
+            .collect();
+        
+        let explicit_deps: HashSet<&str> = features.values()
+            .flatten()
+            .filter_map(|entry| match entry {
+                FeatureEntry::Dependency(dependency) => Some(dependency.as_str()),
+                _ => None,
+            })
+            .collect();
+        let implicit = optional.iter()
+            .filter(|name| implicit_features && !explicit_deps.contains(name.as_str()) && !features.contains_key(*name))
+            .cloned()
+            .collect();
+        
+        Self {
+            features,
+            optional,
+            implicit,
+        }
+    }
+    
+    /// Names of the features declared in `[features]`
+    pub fn features(&self) -> impl Iterator<Item = &str> {
+        self.features.keys().map(String::as_str)
+    }
+    
+    /// Names of the optional dependencies
+    pub fn optional_dependencies(&self) -> impl Iterator<Item = &str> {
+        self.optional.iter().map(String::as_str)
+    }
+    
+    /// Whether `name` is a feature, declared or implicit
+    pub fn is_feature(&self, name: &str) -> bool {
+        self.features.contains_key(name) || self.implicit.contains(name)
+    }
+    
+    /// What a feature directly enables; an implicit feature enables its dependency
+    fn entries(&self, feature: &str) -> Vec<FeatureEntry> {
+        match self.features.get(feature) {
+            Some(entries) => entries.clone(),
+            None if self.implicit.contains(feature) => vec![FeatureEntry::Dependency(feature.to_string())],
+            None => Vec::new(),
+        }
+    }
+    
+    /// Resolve the features enabled by `requested`, including the requested ones
+    pub fn enabled_features(&self, requested: &[&str]) -> HashSet<String> {
+        let mut enabled = HashSet::new();
+        let mut pending: Vec<String> = requested.iter().map(|name| name.to_string()).collect();
+        
+        while let Some(feature) = pending.pop() {
+            if !self.is_feature(&feature) || !enabled.insert(feature.clone()) {
+                continue;
+            }
+            
+            for entry in self.entries(&feature) {
+                // `dep:name` only enables the dependency, `name?/feature` does not enable `name`
+                match entry {
+                    FeatureEntry::Feature(name) => pending.push(name),
+                    FeatureEntry::DependencyFeature { dependency, weak: false, .. } => pending.push(dependency),
+                    FeatureEntry::Dependency(_) | FeatureEntry::DependencyFeature { .. } => {}
+                }
+            }
+        }
+        
+        enabled
+    }
+    
+    /// Dependencies the given features enable or enable features of, including weakly
+    pub fn configured_dependencies(&self, features: &HashSet<String>) -> HashSet<String> {
+        features.iter()
+            .flat_map(|feature| self.entries(feature))
+            .filter_map(|entry| match entry {
+                FeatureEntry::Feature(_) => None,
+                FeatureEntry::Dependency(dependency) | FeatureEntry::DependencyFeature { dependency, .. } => Some(dependency),
+            })
+            .collect()
+    }
+    
+    /// Optional dependencies the given features turn on
+    pub fn enabled_dependencies(&self, features: &HashSet<String>) -> HashSet<String> {
+        features.iter()
+            .flat_map(|feature| self.entries(feature))
+            .filter_map(|entry| match entry {
+                FeatureEntry::Dependency(dependency) | FeatureEntry::DependencyFeature { dependency, weak: false, .. } => Some(dependency),
+                FeatureEntry::Feature(_) | FeatureEntry::DependencyFeature { .. } => None,
+            })
+            .filter(|dependency| self.optional.contains(dependency))
+            .collect()
+    }
+    
+    /// Features of `dependency` that the given features turn on through `dependency/feature` entries
+    pub fn dependency_features(&self, features: &HashSet<String>, dependency: &str) -> BTreeSet<String> {
+        features.iter()
+            .flat_map(|feature| self.entries(feature))
+            .filter_map(|entry| match entry {
+                FeatureEntry::DependencyFeature { dependency: name, feature, .. } if name == dependency => Some(feature),
+                _ => None,
+            })
+            .collect()
+    }
+    
+    /// Features, declared or implicit, that turn on the optional dependency `dependency`
+    /// directly or through the features they enable
+    pub fn features_enabling(&self, dependency: &str) -> Vec<String> {
+        self.features.keys()
+            .chain(&self.implicit)
+            .filter(|feature| {
+                let enabled = self.enabled_features(&[feature.as_str()]);
+                self.enabled_dependencies(&enabled).contains(dependency)
+            })
+            .cloned()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use crate::manifest::dependency::{CargoExtras, DependencyExtras, DependencySource, DependencyType};
+    
+    /// Feature graph of a `[features]` table given as `(feature, entries)`, with the listed
+    /// optional dependencies
+    fn graph(features: &[(&str, &[&str])], optional: &[&str], implicit_features: bool) -> FeatureGraph {
+        let features = features.iter()
+            .map(|(name, entries)| (name.to_string(), entries.iter().map(|entry| FeatureEntry::parse(entry)).collect()))
+            .collect();
+        let dependencies: Vec<Dependency> = optional.iter()
+            .map(|name| Dependency {
+                name: name.to_string(),
+                import_name: name.replace('-', "_"),
+                package: None,
+                version: None,
+                features: Vec::new(),
+                optional: true,
+                dependency_type: DependencyType::Normal,
+                source: DependencySource::default(),
+                extras: DependencyExtras::Rust(CargoExtras {
+                    default_features: true,
+                    platforms: Vec::new(),
+                    all_platforms: true,
+                }),
+            })
+            .collect();
+        
+        FeatureGraph::new(features, &dependencies, implicit_features)
+    }
+    
+    fn sorted(names: HashSet<String>) -> Vec<String> {
+        let mut names: Vec<String> = names.into_iter().collect();
+        names.sort();
+        names
+    }
+    
+    #[test]
+    fn parses_feature_entries() {
+        assert_eq!(FeatureEntry::parse("std"), FeatureEntry::Feature("std".to_string()));
+        assert_eq!(FeatureEntry::parse("dep:serde"), FeatureEntry::Dependency("serde".to_string()));
+        assert_eq!(FeatureEntry::parse("serde/derive"), FeatureEntry::DependencyFeature {
+            dependency: "serde".to_string(),
+            feature: "derive".to_string(),
+            weak: false,
+        });
+        assert_eq!(FeatureEntry::parse("serde?/derive"), FeatureEntry::DependencyFeature {
+            dependency: "serde".to_string(),
+            feature: "derive".to_string(),
+            weak: true,
+        });
+    }
+    
+    #[test]
+    fn resolves_enabled_features_and_dependencies() {
+        let features = graph(&[
+            ("default", &["json"]),
+            ("json", &["dep:serde_json", "chrono?/serde"]),
+            ("full", &["json", "tls"]),
+            ("tls", &["rustls/tls12"]),
+        ], &["serde_json", "chrono", "rustls"], true);
+        
+        let default = features.enabled_features(&["default"]);
+        assert_eq!(sorted(default.clone()), vec!["default", "json"]);
+        assert_eq!(sorted(features.enabled_dependencies(&default)), vec!["serde_json"]);
+        // A weak entry configures `chrono` without turning it on
+        assert_eq!(sorted(features.configured_dependencies(&default)), vec!["chrono", "serde_json"]);
+        
+        let full = features.enabled_features(&["full"]);
+        assert_eq!(sorted(full.clone()), vec!["full", "json", "rustls", "tls"]);
+        assert_eq!(sorted(features.enabled_dependencies(&full)), vec!["rustls", "serde_json"]);
+    }
+    
+    #[test]
+    fn creates_implicit_features_for_optional_dependencies() {
+        let features = graph(&[("json", &["dep:serde_json"])], &["serde_json", "chrono"], true);
+        
+        // `dep:` hides the implicit feature of the dependency it names
+        assert!(features.is_feature("chrono"));
+        assert!(!features.is_feature("serde_json"));
+        assert_eq!(sorted(features.enabled_features(&["chrono"])), vec!["chrono"]);
+        assert_eq!(features.features_enabling("chrono"), vec!["chrono"]);
+        assert_eq!(features.features_enabling("serde_json"), vec!["json"]);
+        
+        // Edition 2024 has no implicit features
+        let edition_2024 = graph(&[], &["chrono"], false);
+        assert!(!edition_2024.is_feature("chrono"));
+        assert!(edition_2024.features_enabling("chrono").is_empty());
+    }
+}
@@ -1,65 +1,105 @@
 pub mod cargo;
 pub mod nodejs;
+pub mod python;
+pub mod dependency;
+pub mod editor;
+pub mod features;
 
 use std::path::Path;
 use anyhow::Result;
-use crate::manifest::cargo::CargoDependency;
+use crate::manifest::dependency::{Dependency, Ecosystem};
 
-/// A trait for parsing project manifests
+/// File names of the manifests of every supported ecosystem, in order of precedence; Python
+/// requirements files are named `requirements*.txt`
+pub const MANIFEST_FILE_NAMES: &[&str] = &[
+    "Cargo.toml",
+    "package.json",
+    "pyproject.toml",
+    "setup.cfg",
+    "requirements.txt",
+    "requirements*.txt",
+];
+
+/// A trait for parsing project manifests into ecosystem-neutral dependencies
 pub trait ManifestParser {
-    /// The dependency type for the manifest
-    type Dependency;
+    /// Ecosystem of the manifests the parser reads
+    const ECOSYSTEM: Ecosystem;
     
     /// Parse a manifest file at the given path
-    fn parse<P: AsRef<Path>>(path: P) -> Result<Vec<Self::Dependency>>;
+    fn parse<P: AsRef<Path>>(path: P) -> Result<Vec<Dependency>>;
 }
 
 /// Enum of supported manifest parsers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ManifestParserType {
     Cargo,
     NodeJs,
+    Python,
+}
+
+impl ManifestParserType {
+    /// Ecosystem of the manifests read by the parser
+    pub fn ecosystem(&self) -> Ecosystem {
+        match self {
+            ManifestParserType::Cargo => cargo::CargoParser::ECOSYSTEM,
+            ManifestParserType::NodeJs => nodejs::NodeJsParser::ECOSYSTEM,
+            ManifestParserType::Python => python::PythonParser::ECOSYSTEM,
+        }
+    }
 }
 
 /// Get the appropriate parser type for a manifest file
 pub fn get_parser_type<P: AsRef<Path>>(path: P) -> Result<ManifestParserType> {
     let path = path.as_ref();
+    let file_name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
     
-    if path.ends_with("Cargo.toml") {
+    if file_name == "Cargo.toml" {
         Ok(ManifestParserType::Cargo)
-    } else if path.ends_with("package.json") {
+    } else if file_name == "package.json" {
         Ok(ManifestParserType::NodeJs)
+    } else if python::is_manifest_file_name(file_name) {
+        Ok(ManifestParserType::Python)
     } else {
         Err(anyhow::anyhow!("Unsupported manifest file: {:?}", path))
     }
 }
 
-/// Parse dependencies from a manifest file
-pub fn parse_dependencies<P: AsRef<Path>>(path: P) -> Result<Vec<CargoDependency>> {
-    let parser_type = get_parser_type(&path)?;
-    
-    match parser_type {
+/// Whether a file name is the name of a manifest of any supported ecosystem
+pub fn is_manifest_file_name(file_name: &str) -> bool {
+    MANIFEST_FILE_NAMES.iter().any(|pattern| matches_file_name(pattern, file_name))
+}
+
+/// Precedence of a manifest among those in the same directory, lower coming first
+pub fn manifest_precedence(file_name: &str) -> usize {
+    MANIFEST_FILE_NAMES.iter()
+        .position(|pattern| matches_file_name(pattern, file_name))
+        .unwrap_or(MANIFEST_FILE_NAMES.len())
+}
+
+/// Match a file name against a name with at most one `*` wildcard
+fn matches_file_name(pattern: &str, file_name: &str) -> bool {
+    match pattern.split_once('*') {
+        Some((prefix, suffix)) => file_name.len() >= prefix.len() + suffix.len()
+            && file_name.starts_with(prefix)
+            && file_name.ends_with(suffix),
+        None => pattern == file_name,
+    }
+}
+
+/// Read the package name declared by a manifest file of any supported ecosystem
+pub fn read_package_name<P: AsRef<Path>>(path: P) -> Result<Option<String>> {
+    match get_parser_type(&path)? {
+        ManifestParserType::Cargo => cargo::read_package_name(path),
+        ManifestParserType::NodeJs => nodejs::read_package_name(path),
+        ManifestParserType::Python => python::read_package_name(path),
+    }
+}
+
+/// Parse dependencies from a manifest file of any supported ecosystem
+pub fn parse_dependencies<P: AsRef<Path>>(path: P) -> Result<Vec<Dependency>> {
+    match get_parser_type(&path)? {
         ManifestParserType::Cargo => cargo::CargoParser::parse(path),
-        ManifestParserType::NodeJs => {
-            // For now, we'll convert NodeJs dependencies to CargoDependency format
-            // Later we can use a more generalized Dependency trait/enum
-            let node_deps = nodejs::NodeJsParser::parse(path)?;
-            
-            // Convert NodeJs dependencies to Cargo format
-            let cargo_deps = node_deps.into_iter()
-                .map(|node_dep| CargoDependency {
-                    name: node_dep.name,
-                    version: Some(node_dep.version),
-                    features: Vec::new(), // Node.js doesn't have features like Cargo
-                    optional: false,
-                    dependency_type: match node_dep.dependency_type {
-                        nodejs::DependencyType::Normal => cargo::DependencyType::Normal,
-                        nodejs::DependencyType::Development => cargo::DependencyType::Development,
-                    },
-                    source: "package.json".to_string(),
-                })
-                .collect();
-            
-            Ok(cargo_deps)
-        }
+        ManifestParserType::NodeJs => nodejs::NodeJsParser::parse(path),
+        ManifestParserType::Python => python::PythonParser::parse(path),
     }
-} 
\ No newline at end of file
+}
@@ -1,72 +1,246 @@
 use std::path::Path;
 use std::fs;
 use anyhow::{Result, Context};
-use serde::Deserialize;
 use serde_json::Value;
 
 use super::ManifestParser;
+use super::dependency::{Dependency, DependencyExtras, DependencySource, DependencyType, Ecosystem, NodeExtras};
 
 #[derive(Debug, Default)]
 pub struct NodeJsParser;
 
-#[derive(Debug, Clone)]
-pub struct NodeJsDependency {
-    pub name: String,
-    pub version: String,
-    pub dependency_type: DependencyType,
-}
-
-#[derive(Debug, Clone, PartialEq)]
-pub enum DependencyType {
-    Normal,
-    Development,
-}
-
 impl ManifestParser for NodeJsParser {
-    type Dependency = NodeJsDependency;
+    const ECOSYSTEM: Ecosystem = Ecosystem::Node;
     
-    fn parse<P: AsRef<Path>>(path: P) -> Result<Vec<Self::Dependency>> {
+    fn parse<P: AsRef<Path>>(path: P) -> Result<Vec<Dependency>> {
         let manifest_path = path.as_ref();
-        let content = fs::read_to_string(manifest_path)
-            .with_context(|| format!("Failed to read package.json at {:?}", manifest_path))?;
+        let package_json = read_manifest(manifest_path)?;
         
-        let package_json: Value = serde_json::from_str(&content)
-            .with_context(|| format!("Failed to parse package.json at {:?}", manifest_path))?;
+        let manifest_dir = manifest_path.parent().unwrap_or_else(|| Path::new("."));
+        let mut dependencies: Vec<Dependency> = Vec::new();
         
-        let mut dependencies = Vec::new();
+        // Process the normal, dev and peer dependency objects
+        for (field, dep_type) in [
+            ("dependencies", DependencyType::Normal),
+            ("devDependencies", DependencyType::Development),
+            ("peerDependencies", DependencyType::Peer),
+        ] {
+            for (name, specifier) in string_entries(&package_json, field) {
+                dependencies.push(parse_specifier(name, specifier, dep_type.clone(), manifest_dir));
+            }
+        }
         
-        // Process normal dependencies
-        if let Some(deps) = package_json.get("dependencies").and_then(|d| d.as_object()) {
-            for (name, version) in deps {
-                if let Some(version_str) = version.as_str() {
-                    dependencies.push(NodeJsDependency {
-                        name: name.clone(),
-                        version: version_str.to_string(),
-                        dependency_type: DependencyType::Normal,
-                    });
-                }
+        // `optionalDependencies` override an entry of the same name in `dependencies`
+        for (name, specifier) in string_entries(&package_json, "optionalDependencies") {
+            let mut dep = parse_specifier(name, specifier, DependencyType::Normal, manifest_dir);
+            dep.optional = true;
+            
+            match dependencies.iter_mut().find(|existing| existing.name == dep.name && existing.dependency_type == DependencyType::Normal) {
+                Some(existing) => *existing = dep,
+                None => dependencies.push(dep),
             }
         }
         
-        // Process dev dependencies
-        if let Some(deps) = package_json.get("devDependencies").and_then(|d| d.as_object()) {
-            for (name, version) in deps {
-                if let Some(version_str) = version.as_str() {
-                    dependencies.push(NodeJsDependency {
-                        name: name.clone(),
-                        version: version_str.to_string(),
-                        dependency_type: DependencyType::Development,
-                    });
-                }
+        // Peer dependencies marked optional in `peerDependenciesMeta` are not installed automatically
+        if let Some(meta) = package_json.get("peerDependenciesMeta").and_then(|m| m.as_object()) {
+            for dep in dependencies.iter_mut().filter(|dep| dep.dependency_type == DependencyType::Peer) {
+                dep.optional = meta.get(&dep.name)
+                    .and_then(|entry| entry.get("optional"))
+                    .and_then(|optional| optional.as_bool())
+                    .unwrap_or(false);
             }
         }
         
+        // `bundleDependencies` (or `bundledDependencies`) is a list of names, or `true` for all dependencies
+        let bundled = package_json.get("bundleDependencies").or_else(|| package_json.get("bundledDependencies"));
+        for dep in &mut dependencies {
+            let is_bundled = match bundled {
+                Some(Value::Bool(all)) => *all && dep.dependency_type == DependencyType::Normal,
+                Some(Value::Array(names)) => names.iter().any(|name| name.as_str() == Some(dep.name.as_str())),
+                _ => false,
+            };
+            dep.extras = DependencyExtras::Node(NodeExtras { bundled: is_bundled });
+        }
+        
         Ok(dependencies)
     }
 }
 
 /// Parse a package.json file and return the dependencies
-pub fn parse_package_json<P: AsRef<Path>>(project_path: P) -> Result<Vec<NodeJsDependency>> {
+#[allow(dead_code)]
+pub fn parse_package_json<P: AsRef<Path>>(project_path: P) -> Result<Vec<Dependency>> {
     let manifest_path = project_path.as_ref().join("package.json");
     NodeJsParser::parse(manifest_path)
-} 
\ No newline at end of file
+}
+
+/// Read the package name from the package.json at `manifest_path`, if it declares one
+pub fn read_package_name<P: AsRef<Path>>(manifest_path: P) -> Result<Option<String>> {
+    let package_json = read_manifest(manifest_path.as_ref())?;
+    Ok(package_json.get("name").and_then(|name| name.as_str()).map(|name| name.to_string()))
+}
+
+/// Read and parse a package.json file
+fn read_manifest(manifest_path: &Path) -> Result<Value> {
+    let content = fs::read_to_string(manifest_path)
+        .with_context(|| format!("Failed to read package.json at {:?}", manifest_path))?;
+    
+    serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse package.json at {:?}", manifest_path))
+}
+
+/// Name of the package.json field declaring a dependency of the given kind
+pub fn dependency_field(dependency_type: &DependencyType, optional: bool) -> &'static str {
+    match dependency_type {
+        DependencyType::Normal if optional => "optionalDependencies",
+        // npm has no build dependencies
+        DependencyType::Normal | DependencyType::Build => "dependencies",
+        DependencyType::Development => "devDependencies",
+        DependencyType::Peer => "peerDependencies",
+    }
+}
+
+/// Entries of a dependency object whose specifier is a string
+fn string_entries<'a>(package_json: &'a Value, field: &str) -> impl Iterator<Item = (&'a String, &'a str)> {
+    package_json.get(field)
+        .and_then(|deps| deps.as_object())
+        .into_iter()
+        .flatten()
+        .filter_map(|(name, specifier)| specifier.as_str().map(|specifier| (name, specifier)))
+}
+
+/// Build a dependency from its package.json specifier: a version range, an `npm:` alias,
+/// a local path, a git repository or a tarball URL. Paths are resolved against `manifest_dir`.
+fn parse_specifier(name: &str, specifier: &str, dependency_type: DependencyType, manifest_dir: &Path) -> Dependency {
+    let mut dep = Dependency {
+        name: name.to_string(),
+        import_name: name.to_string(),
+        package: None,
+        version: None,
+        features: Vec::new(),
+        optional: false,
+        dependency_type,
+        source: DependencySource::default(),
+        extras: DependencyExtras::Node(NodeExtras::default()),
+    };
+    
+    let specifier = specifier.trim();
+    let is_git_shorthand = !specifier.starts_with('@')
+        && !specifier.contains(':')
+        && specifier.split('/').count() == 2
+        && !specifier.starts_with('.');
+    
+    if let Some(alias) = specifier.strip_prefix("npm:") {
+        // `npm:@scope/name@^1.0.0`: the `@` separating the version is never the first character
+        match alias.get(1..).and_then(|rest| rest.find('@')) {
+            Some(at) => {
+                dep.package = Some(alias[..at + 1].to_string());
+                dep.version = Some(alias[at + 2..].to_string());
+            }
+            None => dep.package = Some(alias.to_string()),
+        }
+    } else if let Some(path) = specifier.strip_prefix("file:").or_else(|| specifier.strip_prefix("link:")) {
+        dep.source = DependencySource::Path { path: manifest_dir.join(path) };
+    } else if specifier.starts_with("./") || specifier.starts_with("../") || specifier.starts_with('/') {
+        dep.source = DependencySource::Path { path: manifest_dir.join(specifier) };
+    } else if specifier.starts_with("http://") || specifier.starts_with("https://") {
+        dep.source = DependencySource::Url { url: specifier.to_string() };
+    } else if specifier.starts_with("git")
+        || specifier.starts_with("github:")
+        || specifier.starts_with("gitlab:")
+        || specifier.starts_with("bitbucket:")
+        || is_git_shorthand
+    {
+        // A `#fragment` selects a commit-ish: a branch, tag, commit or `semver:` range
+        let (url, rev) = match specifier.split_once('#') {
+            Some((url, rev)) => (url, Some(rev.to_string())),
+            None => (specifier, None),
+        };
+        dep.source = DependencySource::Git {
+            url: url.to_string(),
+            branch: None,
+            tag: None,
+            rev,
+        };
+    } else if !specifier.is_empty() {
+        dep.version = Some(specifier.to_string());
+    }
+    
+    dep
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    
+    fn specifier(specifier: &str) -> Dependency {
+        parse_specifier("dep", specifier, DependencyType::Normal, Path::new("/project"))
+    }
+    
+    #[test]
+    fn parses_npm_aliases() {
+        let dep = specifier("npm:@scope/x@1");
+        assert_eq!(dep.package.as_deref(), Some("@scope/x"));
+        assert_eq!(dep.version.as_deref(), Some("1"));
+        
+        let dep = specifier("npm:lodash@^4.17.0");
+        assert_eq!(dep.package.as_deref(), Some("lodash"));
+        assert_eq!(dep.version.as_deref(), Some("^4.17.0"));
+        
+        let dep = specifier("npm:@scope/x");
+        assert_eq!(dep.package.as_deref(), Some("@scope/x"));
+        assert_eq!(dep.version, None);
+    }
+    
+    #[test]
+    fn parses_sources() {
+        assert_eq!(specifier("^1.2.3").version.as_deref(), Some("^1.2.3"));
+        assert_eq!(specifier("^1.2.3").source, DependencySource::default());
+        assert_eq!(specifier("file:../lib").source, DependencySource::Path { path: Path::new("/project/../lib").to_path_buf() });
+        assert_eq!(specifier("./vendor/x").source, DependencySource::Path { path: Path::new("/project/./vendor/x").to_path_buf() });
+        assert_eq!(specifier("https://example.com/x.tgz").source, DependencySource::Url { url: "https://example.com/x.tgz".to_string() });
+        assert_eq!(specifier("user/repo#v1.0").source, DependencySource::Git {
+            url: "user/repo".to_string(),
+            branch: None,
+            tag: None,
+            rev: Some("v1.0".to_string()),
+        });
+        assert_eq!(specifier("git+ssh://git@github.com/user/repo.git").source, DependencySource::Git {
+            url: "git+ssh://git@github.com/user/repo.git".to_string(),
+            branch: None,
+            tag: None,
+            rev: None,
+        });
+    }
+    
+    #[test]
+    fn reads_dependency_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest_path = dir.path().join("package.json");
+        fs::write(&manifest_path, r#"{
+            "dependencies": { "a": "^1.0.0", "b": "^2.0.0" },
+            "devDependencies": { "c": "^3.0.0" },
+            "peerDependencies": { "d": "*", "e": "*" },
+            "peerDependenciesMeta": { "e": { "optional": true } },
+            "optionalDependencies": { "b": "^2.1.0" },
+            "bundleDependencies": ["a"]
+        }"#).unwrap();
+        
+        let dependencies = NodeJsParser::parse(&manifest_path).unwrap();
+        let summary: Vec<_> = dependencies.iter()
+            .map(|dep| {
+                let bundled = matches!(dep.extras, DependencyExtras::Node(NodeExtras { bundled: true }));
+                (dep.name.as_str(), dep.version.as_deref(), dependency_field(&dep.dependency_type, dep.optional), bundled)
+            })
+            .collect();
+        
+        assert_eq!(summary, vec![
+            ("a", Some("^1.0.0"), "dependencies", true),
+            ("b", Some("^2.1.0"), "optionalDependencies", false),
+            ("c", Some("^3.0.0"), "devDependencies", false),
+            ("d", Some("*"), "peerDependencies", false),
+            ("e", Some("*"), "peerDependencies", false),
+        ]);
+        assert!(dependencies[4].optional);
+    }
+}
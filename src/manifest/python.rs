@@ -0,0 +1,699 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::fs;
+use anyhow::{Result, Context};
+use toml::Table;
+
+use super::ManifestParser;
+use super::dependency::{Dependency, DependencyExtras, DependencySource, DependencyType, Ecosystem, PythonExtras};
+
+#[derive(Debug, Default)]
+pub struct PythonParser;
+
+/// Names of optional dependency groups and Poetry groups holding development tools
+const DEV_GROUPS: &[&str] = &["dev", "develop", "development", "test", "tests", "testing", "lint", "typing", "docs"];
+
+impl ManifestParser for PythonParser {
+    const ECOSYSTEM: Ecosystem = Ecosystem::Python;
+    
+    /// Python projects spread their dependencies over pyproject.toml, setup.cfg and requirements
+    /// files, so every Python manifest in the directory of `path` is read, not only `path`
+    fn parse<P: AsRef<Path>>(path: P) -> Result<Vec<Dependency>> {
+        let manifest_dir = path.as_ref().parent().unwrap_or_else(|| Path::new("."));
+        let mut dependencies = Vec::new();
+        
+        for manifest_path in project_manifests(manifest_dir)? {
+            let declared = match manifest_path.file_name().and_then(|name| name.to_str()) {
+                Some("pyproject.toml") => parse_pyproject(&manifest_path)?,
+                Some("setup.cfg") => parse_setup_cfg(&manifest_path)?,
+                _ => parse_requirements(&manifest_path, manifest_dir)?,
+            };
+            
+            for dep in declared {
+                merge_declaration(&mut dependencies, dep);
+            }
+        }
+        
+        Ok(dependencies)
+    }
+}
+
+/// Whether a file name is the name of a Python manifest
+pub fn is_manifest_file_name(file_name: &str) -> bool {
+    file_name == "pyproject.toml"
+        || file_name == "setup.cfg"
+        || (file_name.starts_with("requirements") && file_name.ends_with(".txt"))
+}
+
+/// Python manifests in a directory, in a stable order
+fn project_manifests(dir: &Path) -> Result<Vec<PathBuf>> {
+    let entries = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory {:?}", dir))?;
+    
+    let mut manifests: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| path.file_name().and_then(|name| name.to_str()).is_some_and(is_manifest_file_name))
+        .collect();
+    manifests.sort();
+    
+    Ok(manifests)
+}
+
+/// Read the project name from pyproject.toml (`[project]` or `[tool.poetry]`) or setup.cfg
+/// (`[metadata]`) next to `manifest_path`; requirements files declare none
+pub fn read_package_name<P: AsRef<Path>>(manifest_path: P) -> Result<Option<String>> {
+    let manifest_dir = manifest_path.as_ref().parent().unwrap_or_else(|| Path::new("."));
+    
+    let pyproject_path = manifest_dir.join("pyproject.toml");
+    if pyproject_path.exists() {
+        let pyproject = read_pyproject(&pyproject_path)?;
+        let name = pyproject.get("project")
+            .or_else(|| pyproject.get("tool").and_then(|tool| tool.get("poetry")))
+            .and_then(|project| project.get("name"))
+            .and_then(|name| name.as_str());
+        if let Some(name) = name {
+            return Ok(Some(name.to_string()));
+        }
+    }
+    
+    let setup_cfg_path = manifest_dir.join("setup.cfg");
+    if setup_cfg_path.exists() {
+        let setup_cfg = read_setup_cfg(&setup_cfg_path)?;
+        if let Some(name) = setup_cfg.get("metadata").and_then(|metadata| metadata.get("name")) {
+            return Ok(Some(name.trim().to_string()));
+        }
+    }
+    
+    Ok(None)
+}
+
+/// Normalize a distribution name as PyPI compares them (PEP 503): lowercase, with runs of
+/// `-`, `_` and `.` replaced by a single `-`
+pub fn normalize_name(name: &str) -> String {
+    let mut normalized = String::with_capacity(name.len());
+    for c in name.chars() {
+        if matches!(c, '-' | '_' | '.') {
+            if !normalized.ends_with('-') {
+                normalized.push('-');
+            }
+        } else {
+            normalized.push(c.to_ascii_lowercase());
+        }
+    }
+    normalized
+}
+
+/// Merge a declaration into the dependencies read so far. A distribution declared in several
+/// places is one dependency: required anywhere makes it a normal, non-optional dependency.
+fn merge_declaration(dependencies: &mut Vec<Dependency>, dep: Dependency) {
+    let existing = dependencies.iter_mut()
+        .find(|existing| normalize_name(&existing.name) == normalize_name(&dep.name));
+    
+    let existing = match existing {
+        Some(existing) => existing,
+        None => {
+            dependencies.push(dep);
+            return;
+        }
+    };
+    
+    if dep.dependency_type == DependencyType::Normal && !dep.optional {
+        existing.dependency_type = DependencyType::Normal;
+        existing.optional = false;
+    }
+    if existing.version.is_none() {
+        existing.version = dep.version;
+    }
+    if existing.source == DependencySource::default() {
+        existing.source = dep.source;
+    }
+    
+    if let (DependencyExtras::Python(python), DependencyExtras::Python(other)) = (&mut existing.extras, dep.extras) {
+        for extra in other.extras {
+            if !python.extras.contains(&extra) {
+                python.extras.push(extra);
+            }
+        }
+        for declared_in in other.declared_in {
+            if !python.declared_in.contains(&declared_in) {
+                python.declared_in.push(declared_in);
+            }
+        }
+        if python.markers.is_none() {
+            python.markers = other.markers;
+        }
+    }
+}
+
+fn read_pyproject(manifest_path: &Path) -> Result<Table> {
+    let content = fs::read_to_string(manifest_path)
+        .with_context(|| format!("Failed to read pyproject.toml at {:?}", manifest_path))?;
+    
+    toml::from_str(&content)
+        .with_context(|| format!("Failed to parse pyproject.toml at {:?}", manifest_path))
+}
+
+/// Parse the PEP 621 `[project]` table and the Poetry tables of a pyproject.toml
+fn parse_pyproject(manifest_path: &Path) -> Result<Vec<Dependency>> {
+    let pyproject = read_pyproject(manifest_path)?;
+    let manifest_dir = manifest_path.parent().unwrap_or_else(|| Path::new("."));
+    let mut dependencies = Vec::new();
+    
+    if let Some(project) = pyproject.get("project").and_then(|project| project.as_table()) {
+        for requirement in toml_strings(project.get("dependencies")) {
+            if let Some(dep) = parse_requirement(requirement, DependencyType::Normal, "project.dependencies", manifest_dir) {
+                dependencies.push(dep);
+            }
+        }
+        
+        // Extras of the project: installed on request, like optional Cargo dependencies
+        if let Some(groups) = project.get("optional-dependencies").and_then(|groups| groups.as_table()) {
+            for (group, requirements) in groups {
+                let declared_in = format!("project.optional-dependencies.{}", group);
+                for requirement in toml_strings(Some(requirements)) {
+                    if let Some(mut dep) = parse_requirement(requirement, group_type(group), &declared_in, manifest_dir) {
+                        dep.optional = true;
+                        dependencies.push(dep);
+                    }
+                }
+            }
+        }
+    }
+    
+    // PEP 735 dependency groups are never installed with the project, only for development
+    if let Some(groups) = pyproject.get("dependency-groups").and_then(|groups| groups.as_table()) {
+        for (group, requirements) in groups {
+            let declared_in = format!("dependency-groups.{}", group);
+            for requirement in toml_strings(Some(requirements)) {
+                if let Some(dep) = parse_requirement(requirement, DependencyType::Development, &declared_in, manifest_dir) {
+                    dependencies.push(dep);
+                }
+            }
+        }
+    }
+    
+    if let Some(poetry) = pyproject.get("tool").and_then(|tool| tool.get("poetry")).and_then(|poetry| poetry.as_table()) {
+        let mut tables = vec![
+            ("tool.poetry.dependencies".to_string(), DependencyType::Normal, poetry.get("dependencies")),
+            ("tool.poetry.dev-dependencies".to_string(), DependencyType::Development, poetry.get("dev-dependencies")),
+        ];
+        if let Some(groups) = poetry.get("group").and_then(|groups| groups.as_table()) {
+            for (group, table) in groups {
+                // Poetry groups other than `main` are development dependencies, whatever their name
+                let dep_type = if group == "main" { DependencyType::Normal } else { DependencyType::Development };
+                tables.push((format!("tool.poetry.group.{}.dependencies", group), dep_type, table.get("dependencies")));
+            }
+        }
+        
+        for (declared_in, dep_type, table) in tables {
+            let Some(table) = table.and_then(|table| table.as_table()) else {
+                continue;
+            };
+            
+            for (name, spec) in table {
+                // The Python version the project supports is declared among its dependencies
+                if name == "python" {
+                    continue;
+                }
+                dependencies.push(parse_poetry_dependency(name, spec, dep_type.clone(), &declared_in, manifest_dir));
+            }
+        }
+    }
+    
+    Ok(dependencies)
+}
+
+/// Build a dependency from a Poetry specification: a version constraint, a table with
+/// `version`, `extras`, `optional`, `markers` and a `git`, `path` or `url` source, or an
+/// array of such tables for different Python versions or platforms (the first one is used)
+fn parse_poetry_dependency(
+    name: &str,
+    spec: &toml::Value,
+    dependency_type: DependencyType,
+    declared_in: &str,
+    manifest_dir: &Path,
+) -> Dependency {
+    let mut dep = new_dependency(name, dependency_type, declared_in);
+    let spec = match spec {
+        toml::Value::Array(specs) => specs.first().unwrap_or(spec),
+        spec => spec,
+    };
+    
+    let table = match spec {
+        toml::Value::String(version) => {
+            dep.version = Some(version.clone());
+            return dep;
+        }
+        toml::Value::Table(table) => table,
+        _ => return dep,
+    };
+    
+    let get_str = |key: &str| table.get(key).and_then(|value| value.as_str()).map(|value| value.to_string());
+    
+    dep.version = get_str("version");
+    dep.optional = table.get("optional").and_then(|optional| optional.as_bool()).unwrap_or(false);
+    
+    if let Some(git) = get_str("git") {
+        dep.source = DependencySource::Git {
+            url: git,
+            branch: get_str("branch"),
+            tag: get_str("tag"),
+            rev: get_str("rev"),
+        };
+    } else if let Some(path) = get_str("path") {
+        dep.source = DependencySource::Path { path: manifest_dir.join(path) };
+    } else if let Some(url) = get_str("url") {
+        dep.source = DependencySource::Url { url };
+    } else if let Some(registry) = get_str("source") {
+        dep.source = DependencySource::Registry { registry: Some(registry) };
+    }
+    
+    if let DependencyExtras::Python(python) = &mut dep.extras {
+        python.extras = toml_strings(table.get("extras")).into_iter().map(|extra| extra.to_string()).collect();
+        python.markers = get_str("markers");
+    }
+    
+    dep
+}
+
+/// Parse `-r` included requirements files, relative to the including file, and the
+/// requirements of a requirements file. Options and constraint files (`-c`) are skipped.
+fn parse_requirements(manifest_path: &Path, project_dir: &Path) -> Result<Vec<Dependency>> {
+    let mut dependencies = Vec::new();
+    let mut visited = Vec::new();
+    collect_requirements(manifest_path, project_dir, &mut visited, &mut dependencies)?;
+    Ok(dependencies)
+}
+
+fn collect_requirements(
+    path: &Path,
+    project_dir: &Path,
+    visited: &mut Vec<PathBuf>,
+    dependencies: &mut Vec<Dependency>,
+) -> Result<()> {
+    // Files including each other are read once
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if visited.contains(&canonical) {
+        return Ok(());
+    }
+    visited.push(canonical);
+    
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read requirements file at {:?}", path))?;
+    let file_dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let declared_in = path.strip_prefix(project_dir).unwrap_or(path).display().to_string();
+    let dep_type = requirements_file_type(path);
+    
+    // Lines ending with a backslash continue on the next line
+    let content = content.replace("\\\r\n", " ").replace("\\\n", " ");
+    
+    for line in content.lines() {
+        // Comments start at a `#` at the start of the line or after whitespace; `#egg=` fragments stay
+        let line = match line.find(" #").or_else(|| line.find("\t#")) {
+            Some(comment) => &line[..comment],
+            None if line.trim_start().starts_with('#') => "",
+            None => line,
+        };
+        let line = line.trim();
+        
+        if line.is_empty() {
+            continue;
+        }
+        
+        if let Some(include) = option_value(line, &["-r", "--requirement"]) {
+            let include_path = file_dir.join(include);
+            collect_requirements(&include_path, project_dir, visited, dependencies)?;
+            continue;
+        }
+        
+        if let Some(editable) = option_value(line, &["-e", "--editable"]) {
+            if let Some(dep) = parse_direct_reference(editable, dep_type.clone(), &declared_in, file_dir) {
+                dependencies.push(dep);
+            }
+            continue;
+        }
+        
+        // Index options, constraint files and other options declare no dependency
+        if line.starts_with('-') {
+            continue;
+        }
+        
+        // Per-requirement options such as `--hash=sha256:...` follow the requirement
+        let requirement = line.split(" --").next().unwrap_or(line).trim();
+        
+        let dep = if requirement.starts_with('.') || requirement.starts_with('/') || requirement.contains("://") {
+            parse_direct_reference(requirement, dep_type.clone(), &declared_in, file_dir)
+        } else {
+            parse_requirement(requirement, dep_type.clone(), &declared_in, file_dir)
+        };
+        dependencies.extend(dep);
+    }
+    
+    Ok(())
+}
+
+/// Value of a requirements file option such as `-r file`, `-rfile`, `--requirement file`
+/// or `--requirement=file`
+fn option_value<'a>(line: &'a str, names: &[&str]) -> Option<&'a str> {
+    for name in names {
+        if let Some(rest) = line.strip_prefix(name) {
+            let value = if name.starts_with("--") {
+                match rest.strip_prefix('=') {
+                    Some(value) => value,
+                    None if rest.starts_with(char::is_whitespace) => rest,
+                    None => continue,
+                }
+            } else {
+                rest.strip_prefix('=').unwrap_or(rest)
+            };
+            
+            let value = value.trim();
+            if !value.is_empty() {
+                return Some(value);
+            }
+        }
+    }
+    None
+}
+
+/// Kind of the dependencies of a requirements file: development dependencies for files
+/// such as `requirements-dev.txt` or `requirements/test.txt`
+fn requirements_file_type(path: &Path) -> DependencyType {
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy().to_lowercase()).unwrap_or_default();
+    let is_dev = stem.split(['-', '_', '.'])
+        .any(|part| DEV_GROUPS.contains(&part));
+    
+    if is_dev { DependencyType::Development } else { DependencyType::Normal }
+}
+
+/// Kind of the dependencies of an optional dependency group
+fn group_type(group: &str) -> DependencyType {
+    if DEV_GROUPS.contains(&group.to_lowercase().as_str()) {
+        DependencyType::Development
+    } else {
+        DependencyType::Normal
+    }
+}
+
+/// Build a dependency from a requirement that is a local path or a URL, such as
+/// `-e ./libs/core`, `git+https://github.com/org/repo@v1#egg=name` or an archive URL.
+/// The name comes from an `#egg=` fragment, or else from the last path segment.
+fn parse_direct_reference(reference: &str, dependency_type: DependencyType, declared_in: &str, base_dir: &Path) -> Option<Dependency> {
+    let (location, fragment) = match reference.split_once('#') {
+        Some((location, fragment)) => (location, Some(fragment)),
+        None => (reference, None),
+    };
+    let egg = fragment
+        .and_then(|fragment| fragment.split('&').find_map(|part| part.strip_prefix("egg=")))
+        .map(|egg| egg.split('[').next().unwrap_or(egg));
+    
+    let name = match egg {
+        Some(egg) => egg.to_string(),
+        None => {
+            let last_segment = location.trim_end_matches('/').rsplit('/').next()?;
+            let last_segment = last_segment.split('@').next().unwrap_or(last_segment);
+            let last_segment = last_segment.strip_suffix(".git").unwrap_or(last_segment);
+            // Archive file names start with the distribution name followed by the version,
+            // e.g. `name-1.0.tar.gz`
+            let is_archive = [".tar.gz", ".tar.bz2", ".zip", ".whl"].iter().any(|ext| last_segment.ends_with(ext));
+            let name = match last_segment.match_indices('-').find(|(at, _)| {
+                last_segment[at + 1..].starts_with(|c: char| c.is_ascii_digit())
+            }) {
+                Some((at, _)) if is_archive => &last_segment[..at],
+                _ => last_segment,
+            };
+            if name.is_empty() || name == "." || name == ".." {
+                return None;
+            }
+            name.to_string()
+        }
+    };
+    
+    let mut dep = new_dependency(&name, dependency_type, declared_in);
+    dep.source = match location.strip_prefix("git+") {
+        Some(url) => {
+            // `@ref` after the repository path selects a branch, tag or commit
+            let (url, rev) = match url.rsplit_once('@').filter(|(url, _)| url.contains("://") && !url.ends_with(':')) {
+                Some((url, rev)) if !rev.contains('/') => (url, Some(rev.to_string())),
+                _ => (url, None),
+            };
+            DependencySource::Git { url: url.to_string(), branch: None, tag: None, rev }
+        }
+        None if location.contains("://") => DependencySource::Url { url: location.to_string() },
+        None => DependencySource::Path { path: base_dir.join(location) },
+    };
+    
+    Some(dep)
+}
+
+/// Parse a PEP 508 requirement such as `requests[security]>=2.8.1; python_version < "3.8"`
+/// or `name @ https://example.com/name.zip`
+fn parse_requirement(requirement: &str, dependency_type: DependencyType, declared_in: &str, base_dir: &Path) -> Option<Dependency> {
+    let (requirement, markers) = match requirement.split_once(';') {
+        Some((requirement, markers)) => (requirement.trim(), Some(markers.trim().to_string())),
+        None => (requirement.trim(), None),
+    };
+    
+    let name_end = requirement
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        .unwrap_or(requirement.len());
+    let name = &requirement[..name_end];
+    if name.is_empty() {
+        return None;
+    }
+    
+    let mut rest = requirement[name_end..].trim_start();
+    let mut extras = Vec::new();
+    if let Some(after_bracket) = rest.strip_prefix('[') {
+        let (list, after) = after_bracket.split_once(']')?;
+        extras = list.split(',')
+            .map(|extra| extra.trim().to_string())
+            .filter(|extra| !extra.is_empty())
+            .collect();
+        rest = after.trim_start();
+    }
+    
+    let mut dep = match rest.strip_prefix('@') {
+        Some(url) => {
+            let mut dep = parse_direct_reference(url.trim(), dependency_type, declared_in, base_dir)?;
+            dep.name = name.to_string();
+            dep.import_name = default_module_name(name);
+            dep
+        }
+        None => {
+            let mut dep = new_dependency(name, dependency_type, declared_in);
+            // Version specifiers may be wrapped in parentheses: `name (>=1.0)`
+            let version = rest.trim_start_matches('(').trim_end_matches(')').trim();
+            if !version.is_empty() {
+                dep.version = Some(version.to_string());
+            }
+            dep
+        }
+    };
+    
+    if let DependencyExtras::Python(python) = &mut dep.extras {
+        python.extras = extras;
+        python.markers = markers;
+    }
+    
+    Some(dep)
+}
+
+/// Create a registry dependency declared in `declared_in`
+fn new_dependency(name: &str, dependency_type: DependencyType, declared_in: &str) -> Dependency {
+    Dependency {
+        name: name.to_string(),
+        import_name: default_module_name(name),
+        package: None,
+        version: None,
+        features: Vec::new(),
+        optional: false,
+        dependency_type,
+        source: DependencySource::default(),
+        extras: DependencyExtras::Python(PythonExtras {
+            declared_in: vec![declared_in.to_string()],
+            ..PythonExtras::default()
+        }),
+    }
+}
+
+/// Module a distribution is imported as when nothing else is known: its name in lowercase,
+/// with dashes replaced by underscores (`typing-extensions` is imported as `typing_extensions`)
+pub fn default_module_name(name: &str) -> String {
+    name.to_lowercase().replace('-', "_")
+}
+
+/// Strings of a TOML array
+fn toml_strings(value: Option<&toml::Value>) -> Vec<&str> {
+    value.and_then(|value| value.as_array())
+        .map(|items| items.iter().filter_map(|item| item.as_str()).collect())
+        .unwrap_or_default()
+}
+
+/// Read a setup.cfg file into its sections and their keys. Values continued on indented
+/// lines are joined with newlines.
+fn read_setup_cfg(manifest_path: &Path) -> Result<HashMap<String, HashMap<String, String>>> {
+    let content = fs::read_to_string(manifest_path)
+        .with_context(|| format!("Failed to read setup.cfg at {:?}", manifest_path))?;
+    
+    let mut sections: HashMap<String, HashMap<String, String>> = HashMap::new();
+    let mut section = String::new();
+    let mut key: Option<String> = None;
+    
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with(';') {
+            continue;
+        }
+        
+        // Indented lines continue the value of the previous key
+        if line.starts_with(char::is_whitespace) {
+            if let Some(key) = &key {
+                let value = sections.entry(section.clone()).or_default().entry(key.clone()).or_default();
+                value.push('\n');
+                value.push_str(trimmed);
+            }
+            continue;
+        }
+        
+        if let Some(name) = trimmed.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+            section = name.trim().to_string();
+            key = None;
+            continue;
+        }
+        
+        if let Some(separator) = trimmed.find(['=', ':']) {
+            let name = trimmed[..separator].trim().to_string();
+            let value = trimmed[separator + 1..].trim().to_string();
+            sections.entry(section.clone()).or_default().insert(name.clone(), value);
+            key = Some(name);
+        }
+    }
+    
+    Ok(sections)
+}
+
+/// Parse the `install_requires`, `tests_require` and `[options.extras_require]` requirements of a setup.cfg
+fn parse_setup_cfg(manifest_path: &Path) -> Result<Vec<Dependency>> {
+    let setup_cfg = read_setup_cfg(manifest_path)?;
+    let manifest_dir = manifest_path.parent().unwrap_or_else(|| Path::new("."));
+    let mut dependencies = Vec::new();
+    
+    // Requirements are listed one per line, on the lines continuing the key
+    let requirements = |value: &str| -> Vec<String> {
+        value.lines()
+            .map(|line| line.split(" #").next().unwrap_or(line).trim().to_string())
+            .filter(|line| !line.is_empty() && !line.starts_with("file:"))
+            .collect()
+    };
+    
+    if let Some(options) = setup_cfg.get("options") {
+        for (key, dep_type) in [("install_requires", DependencyType::Normal), ("tests_require", DependencyType::Development)] {
+            let declared_in = format!("options.{}", key);
+            for requirement in options.get(key).map(|value| requirements(value)).unwrap_or_default() {
+                dependencies.extend(parse_requirement(&requirement, dep_type.clone(), &declared_in, manifest_dir));
+            }
+        }
+    }
+    
+    if let Some(extras) = setup_cfg.get("options.extras_require") {
+        let mut groups: Vec<(&String, &String)> = extras.iter().collect();
+        groups.sort();
+        
+        for (group, value) in groups {
+            let declared_in = format!("options.extras_require.{}", group);
+            for requirement in requirements(value) {
+                if let Some(mut dep) = parse_requirement(&requirement, group_type(group), &declared_in, manifest_dir) {
+                    dep.optional = true;
+                    dependencies.push(dep);
+                }
+            }
+        }
+    }
+    
+    Ok(dependencies)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    
+    fn python_extras(dep: &Dependency) -> &PythonExtras {
+        match &dep.extras {
+            DependencyExtras::Python(python) => python,
+            other => panic!("not a Python dependency: {:?}", other),
+        }
+    }
+    
+    fn requirement(requirement: &str) -> Dependency {
+        parse_requirement(requirement, DependencyType::Normal, "requirements.txt", Path::new(".")).unwrap()
+    }
+    
+    #[test]
+    fn parses_extras_versions_and_markers() {
+        let dep = requirement("Requests[security, socks]>=2.8.1; python_version < \"3.8\"");
+        
+        assert_eq!(dep.name, "Requests");
+        assert_eq!(dep.import_name, "requests");
+        assert_eq!(dep.version.as_deref(), Some(">=2.8.1"));
+        assert_eq!(python_extras(&dep).extras, vec!["security", "socks"]);
+        assert_eq!(python_extras(&dep).markers.as_deref(), Some("python_version < \"3.8\""));
+    }
+    
+    #[test]
+    fn parses_parenthesized_versions_and_direct_references() {
+        assert_eq!(requirement("typing-extensions (>=4.0)").version.as_deref(), Some(">=4.0"));
+        
+        let dep = requirement("pkg @ git+https://github.com/org/repo@v1.2");
+        assert_eq!(dep.name, "pkg");
+        assert_eq!(dep.source, DependencySource::Git {
+            url: "https://github.com/org/repo".to_string(),
+            branch: None,
+            tag: None,
+            rev: Some("v1.2".to_string()),
+        });
+    }
+    
+    #[test]
+    fn names_direct_references_by_egg_fragment_or_archive_name() {
+        let reference = |reference: &str| {
+            parse_direct_reference(reference, DependencyType::Normal, "requirements.txt", Path::new("/project")).unwrap()
+        };
+        
+        let egg = reference("git+https://github.com/org/repo.git@main#egg=my-pkg[extra]");
+        assert_eq!(egg.name, "my-pkg");
+        
+        let archive = reference("https://example.com/dist/my_pkg-1.0.tar.gz");
+        assert_eq!(archive.name, "my_pkg");
+        assert_eq!(archive.source, DependencySource::Url { url: "https://example.com/dist/my_pkg-1.0.tar.gz".to_string() });
+        
+        let local = reference("./libs/core");
+        assert_eq!(local.name, "core");
+        assert_eq!(local.source, DependencySource::Path { path: PathBuf::from("/project/./libs/core") });
+    }
+    
+    #[test]
+    fn reads_requirements_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("requirements.txt");
+        fs::write(&base, "-r requirements-dev.txt\n# Pinned\nflask==2.0 \\\n    --hash=sha256:abc \\\n    --hash=sha256:def\n-e ./vendor/lib#egg=vendored\n--index-url https://example.com/simple\nnumpy  # arrays\n").unwrap();
+        // Files including each other are read once
+        fs::write(dir.path().join("requirements-dev.txt"), "-r requirements.txt\npytest>=7\n").unwrap();
+        
+        let dependencies = parse_requirements(&base, dir.path()).unwrap();
+        let summary: Vec<(&str, Option<&str>, DependencyType)> = dependencies.iter()
+            .map(|dep| (dep.name.as_str(), dep.version.as_deref(), dep.dependency_type.clone()))
+            .collect();
+        
+        assert_eq!(summary, vec![
+            ("pytest", Some(">=7"), DependencyType::Development),
+            ("flask", Some("==2.0"), DependencyType::Normal),
+            ("vendored", None, DependencyType::Normal),
+            ("numpy", None, DependencyType::Normal),
+        ]);
+        assert_eq!(python_extras(&dependencies[0]).declared_in, vec!["requirements-dev.txt"]);
+    }
+}
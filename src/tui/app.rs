@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 use std::time::Duration;
 use anyhow::Result;
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{KeyCode, KeyEvent};
 use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
 use crossterm::ExecutableCommand;
 use ratatui::backend::CrosstermBackend;
@@ -10,6 +10,7 @@ use ratatui::Terminal;
 use crate::analyzer::{DependencyAnalyzer, AnalysisResult};
 use crate::tui::event::{EventHandler, EventConfig, Event as AppEvent};
 use crate::tui::ui;
+use crate::utils::config::Config;
 
 /// Sort options for dependencies
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -125,11 +126,17 @@ pub struct App {
     pub enable_dependency_graph: bool,
     /// Counter for animations
     pub tick_count: usize,
+    /// Selected project of a repository with several projects
+    pub selected_project: usize,
+    /// Selected workspace member (`None` shows the workspace-wide rollup)
+    pub selected_member: Option<usize>,
+    /// Loaded configuration the analysis runs with
+    config: Config,
 }
 
 impl App {
     /// Create a new app instance
-    pub fn new(project_path: PathBuf, filter_dep: Option<String>) -> Self {
+    pub fn new(project_path: PathBuf, filter_dep: Option<String>, config: Config) -> Self {
         Self {
             project_path,
             analysis: None,
@@ -146,6 +153,9 @@ impl App {
             detail_view: 0,
             enable_dependency_graph: false,
             tick_count: 0,
+            selected_project: 0,
+            selected_member: None,
+            config,
         }
     }
     
@@ -216,6 +226,14 @@ impl App {
                 self.filter_option = self.filter_option.next();
                 self.selected_dependency = 0; // Reset selection when changing filter
             }
+            (KeyCode::Char('m'), _) => {
+                // Cycle through workspace members, ending with the workspace rollup
+                self.next_member();
+            }
+            (KeyCode::Char('p'), _) => {
+                // Cycle through the projects of a mixed repository
+                self.next_project();
+            }
             (KeyCode::Char('/'), _) => {
                 // Enter search mode
                 self.is_searching = true;
@@ -224,13 +242,13 @@ impl App {
             (KeyCode::Right, _) | (KeyCode::Char('l'), _) => {
                 // In details view, cycle through detail panels
                 if self.current_tab == 1 {
-                    self.detail_view = (self.detail_view + 1) % 3; // 3 detail views
+                    self.detail_view = (self.detail_view + 1) % 4; // 4 detail views
                 }
             }
             (KeyCode::Left, _) | (KeyCode::Char('h'), _) => {
                 // In details view, cycle through detail panels backwards
                 if self.current_tab == 1 {
-                    self.detail_view = (self.detail_view + 2) % 3;
+                    self.detail_view = (self.detail_view + 3) % 4;
                 }
             }
             _ => {}
@@ -239,12 +257,12 @@ impl App {
     
     /// Run the analysis
     pub fn run_analysis(&mut self) -> Result<()> {
-        let analyzer = DependencyAnalyzer::new(&self.project_path);
+        let analyzer = DependencyAnalyzer::new(&self.project_path).with_config(self.config.clone());
         self.analysis = Some(analyzer.analyze()?);
         
         // If a filter is specified, select that dependency
         if let Some(filter) = &self.filter_dep {
-            if let Some(analysis) = &self.analysis {
+            if let Some(analysis) = self.current_analysis() {
                 for (i, dep) in analysis.dependencies.iter().enumerate() {
                     if dep.name == *filter {
                         self.selected_dependency = i;
@@ -259,7 +277,7 @@ impl App {
     
     /// Get filtered and sorted dependencies
     pub fn filtered_dependencies(&self) -> Vec<usize> {
-        if let Some(analysis) = &self.analysis {
+        if let Some(analysis) = self.current_analysis() {
             let mut indices: Vec<usize> = (0..analysis.dependencies.len()).collect();
             
             // Filter dependencies
@@ -269,9 +287,9 @@ impl App {
                 // First check filter option
                 let filter_match = match self.filter_option {
                     FilterOption::All => true,
-                    FilterOption::Normal => dep.dependency_type == crate::manifest::cargo::DependencyType::Normal,
-                    FilterOption::Dev => dep.dependency_type == crate::manifest::cargo::DependencyType::Development,
-                    FilterOption::Build => dep.dependency_type == crate::manifest::cargo::DependencyType::Build,
+                    FilterOption::Normal => dep.dependency_type == crate::manifest::dependency::DependencyType::Normal,
+                    FilterOption::Dev => dep.dependency_type == crate::manifest::dependency::DependencyType::Development,
+                    FilterOption::Build => dep.dependency_type == crate::manifest::dependency::DependencyType::Build,
                     FilterOption::Unused => {
                         !*analysis.metrics.is_used.get(&dep.name).unwrap_or(&true)
                     },
@@ -310,11 +328,12 @@ impl App {
                     },
                     SortOption::Type => {
                         // Compare dependency types based on their variant order
-                        let type_order = |dep_type: &crate::manifest::cargo::DependencyType| -> u8 {
+                        let type_order = |dep_type: &crate::manifest::dependency::DependencyType| -> u8 {
                             match dep_type {
-                                crate::manifest::cargo::DependencyType::Normal => 0,
-                                crate::manifest::cargo::DependencyType::Development => 1,
-                                crate::manifest::cargo::DependencyType::Build => 2,
+                                crate::manifest::dependency::DependencyType::Normal => 0,
+                                crate::manifest::dependency::DependencyType::Development => 1,
+                                crate::manifest::dependency::DependencyType::Build => 2,
+                                crate::manifest::dependency::DependencyType::Peer => 3,
                             }
                         };
                         
@@ -340,6 +359,46 @@ impl App {
         }
     }
     
+    /// Get the project being displayed: the selected project of a mixed repository, or the whole project
+    pub fn current_project(&self) -> Option<&AnalysisResult> {
+        let analysis = self.analysis.as_ref()?;
+        analysis.projects.get(self.selected_project).or(Some(analysis))
+    }
+    
+    /// Get the analysis being displayed: the selected workspace member or the whole project
+    pub fn current_analysis(&self) -> Option<&AnalysisResult> {
+        let project = self.current_project()?;
+        
+        match self.selected_member {
+            Some(idx) => project.members.get(idx).or(Some(project)),
+            None => Some(project),
+        }
+    }
+    
+    /// Select the next project of a mixed repository, wrapping around to the first one
+    pub fn next_project(&mut self) {
+        let project_count = self.analysis.as_ref().map_or(0, |a| a.projects.len());
+        
+        self.selected_project = match project_count {
+            0 => 0,
+            count => (self.selected_project + 1) % count,
+        };
+        self.selected_member = None;
+        self.selected_dependency = 0; // Reset selection when changing projects
+    }
+    
+    /// Select the next workspace member, wrapping around to the workspace rollup
+    pub fn next_member(&mut self) {
+        let member_count = self.current_project().map_or(0, |project| project.members.len());
+        
+        self.selected_member = match self.selected_member {
+            None if member_count > 0 => Some(0),
+            Some(idx) if idx + 1 < member_count => Some(idx + 1),
+            _ => None,
+        };
+        self.selected_dependency = 0; // Reset selection when changing members
+    }
+    
     /// Get the actual index of the selected dependency
     pub fn actual_selected_index(&self) -> Option<usize> {
         let filtered = self.filtered_dependencies();
@@ -348,7 +407,7 @@ impl App {
 }
 
 /// Run the TUI application
-pub fn run(project_path: PathBuf, filter_dep: Option<String>, enable_deps: bool) -> Result<()> {
+pub fn run(project_path: PathBuf, filter_dep: Option<String>, enable_deps: bool, config: Config) -> Result<()> {
     // Set up terminal
     terminal::enable_raw_mode()?;
     std::io::stdout().execute(EnterAlternateScreen)?;
@@ -358,7 +417,7 @@ pub fn run(project_path: PathBuf, filter_dep: Option<String>, enable_deps: bool)
     let mut terminal = Terminal::new(backend)?;
     
     // Create app state
-    let mut app = App::new(project_path, filter_dep);
+    let mut app = App::new(project_path, filter_dep, config);
     
     // Enable dependency graph visualization if requested
     app.enable_dependency_graph = enable_deps;
@@ -1,4 +1,4 @@
-use std::sync::mpsc::{self, Receiver, RecvError, Sender};
+use std::sync::mpsc::{self, Receiver, RecvError};
 use std::thread;
 use std::time::{Duration, Instant};
 
@@ -1,15 +1,11 @@
-use std::collections::HashMap;
-
 use ratatui::layout::{Constraint, Direction, Layout, Rect, Alignment};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span, Text};
-use ratatui::widgets::{Block, Borders, BorderType, List, ListItem, Paragraph, Tabs, Clear, Table, Row, Cell, Gauge, Padding};
+use ratatui::widgets::{Block, Borders, BorderType, Paragraph, Tabs, Clear, Padding};
 use ratatui::Frame;
 use unicode_width::UnicodeWidthStr;
 
 use crate::tui::app::App;
-use crate::analyzer::{AnalysisResult, DependencyUsage};
-use crate::manifest::cargo::DependencyType;
 
 // Modern color palette
 pub const PRIMARY_COLOR: Color = Color::Rgb(0, 135, 175);    // Teal
@@ -113,6 +109,35 @@ fn draw_status_bar(frame: &mut Frame, app: &App, area: Rect) {
     status_items.push(Span::styled(" Filter:", key_style));
     status_items.push(Span::styled(format!("{} ", app.filter_option.as_str()), status_style));
     
+    // Add the project being shown, if the repository holds several
+    if let (Some(analysis), Some(project)) = (&app.analysis, app.current_project()) {
+        if analysis.is_multi_project() {
+            let manifest = project.manifest_path.strip_prefix(&analysis.manifest_path)
+                .unwrap_or(&project.manifest_path);
+            let ecosystem = project.ecosystem.map(|ecosystem| ecosystem.to_string()).unwrap_or_default();
+            status_items.push(Span::styled(" Project:", key_style));
+            status_items.push(Span::styled(format!("{} {} ({}/{}) ",
+                ecosystem,
+                manifest.display(),
+                app.selected_project + 1,
+                analysis.projects.len()
+            ), status_style));
+        }
+    }
+    
+    // Add the workspace member being shown, if the project is a workspace
+    if let (Some(analysis), Some(current)) = (app.current_project(), app.current_analysis()) {
+        if analysis.is_workspace() {
+            let label = if app.selected_member.is_some() {
+                current.name.clone()
+            } else {
+                format!("{} (workspace, {} members)", analysis.name, analysis.members.len())
+            };
+            status_items.push(Span::styled(" Package:", key_style));
+            status_items.push(Span::styled(format!("{} ", label), status_style));
+        }
+    }
+    
     // Add search query if applicable
     if !app.search_query.is_empty() {
         status_items.push(Span::styled(" Search:", key_style));
@@ -235,8 +260,9 @@ fn draw_removable_tab(frame: &mut Frame, app: &App, area: Rect) {
         let filtered_indices = app.filtered_dependencies()
             .into_iter()
             .filter(|&idx| {
-                let dep = &app.analysis.as_ref().unwrap().dependencies[idx];
-                app.analysis.as_ref().unwrap().metrics.removable_dependencies.contains(&dep.name)
+                let analysis = app.current_analysis().unwrap();
+                let dep = &analysis.dependencies[idx];
+                analysis.metrics.removable_dependencies.contains(&dep.name)
             })
             .collect::<Vec<_>>();
         
@@ -328,6 +354,14 @@ fn draw_help(frame: &mut Frame) {
             Span::styled("  /", Style::default().fg(ACCENT_COLOR).add_modifier(Modifier::BOLD)),
             Span::raw(" - Search dependencies")
         ]),
+        Line::from(vec![
+            Span::styled("  m", Style::default().fg(ACCENT_COLOR).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Cycle workspace members")
+        ]),
+        Line::from(vec![
+            Span::styled("  p", Style::default().fg(ACCENT_COLOR).add_modifier(Modifier::BOLD)),
+            Span::raw(" - Cycle projects of a mixed repository")
+        ]),
         Line::from(vec![
             Span::styled("  Enter", Style::default().fg(ACCENT_COLOR).add_modifier(Modifier::BOLD)),
             Span::raw(" - View dependency details")
@@ -378,6 +412,7 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
 }
 
 /// Calculate a color based on importance score
+#[allow(dead_code)]
 pub fn importance_color(score: f64) -> Color {
     if score > 0.7 {
         SUCCESS_COLOR
@@ -389,12 +424,14 @@ pub fn importance_color(score: f64) -> Color {
 }
 
 // Helper struct for margins
+#[allow(dead_code)]
 pub struct Margin {
     pub vertical: u16,
     pub horizontal: u16,
 }
 
 // Extension trait to apply margins to a Rect
+#[allow(dead_code)]
 pub trait RectExt {
     fn inner(&self, margin: &Margin) -> Rect;
 }
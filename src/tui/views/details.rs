@@ -1,10 +1,11 @@
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph, Table, Row, Cell, Tabs};
+use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph, Tabs};
 use ratatui::Frame;
 
-use crate::analyzer::{AnalysisResult, DependencyUsage, UsageType};
+use crate::analyzer::{metrics, AnalysisResult, DependencyUsage, UsageType};
+use crate::manifest::dependency::DependencyExtras;
 use crate::tui::app::App;
 
 /// Render the details view for a selected dependency
@@ -23,7 +24,7 @@ pub fn render(frame: &mut Frame, app: &App, area: Rect) {
     let actual_idx = app.actual_selected_index();
     
     // Render title
-    let title = if let Some(analysis) = &app.analysis {
+    let title = if let Some(analysis) = app.current_analysis() {
         if let Some(dep_idx) = actual_idx {
             if let Some(dep) = analysis.dependencies.get(dep_idx) {
                 format!("Dependency Details: {}", dep.name)
@@ -47,7 +48,7 @@ pub fn render(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(title_widget, chunks[0]);
     
     // Draw detail view tabs
-    let detail_titles = vec!["Basic Info", "Usage Metrics", "Dependencies"];
+    let detail_titles = vec!["Basic Info", "Usage Metrics", "Dependencies", "API Surface"];
     let detail_tabs = Tabs::new(detail_titles.iter().map(|t| Line::from(Span::styled(*t, Style::default().fg(Color::White)))).collect())
         .block(Block::default().borders(Borders::ALL))
         .select(app.detail_view)
@@ -56,7 +57,7 @@ pub fn render(frame: &mut Frame, app: &App, area: Rect) {
     
     frame.render_widget(detail_tabs, chunks[1]);
     
-    if let Some(analysis) = &app.analysis {
+    if let Some(analysis) = app.current_analysis() {
         if let Some(dep_idx) = actual_idx {
             if let Some(dep) = analysis.dependencies.get(dep_idx) {
                 // Render the appropriate detail view
@@ -64,6 +65,7 @@ pub fn render(frame: &mut Frame, app: &App, area: Rect) {
                     0 => render_basic_info(frame, app, analysis, dep, chunks[2]),
                     1 => render_usage_metrics(frame, app, analysis, dep, chunks[2]),
                     2 => render_dependency_graph_info(frame, app, analysis, &dep.name, chunks[2]),
+                    3 => render_api_surface(frame, analysis, &dep.name, chunks[2]),
                     _ => {}
                 }
             } else {
@@ -87,7 +89,7 @@ pub fn render(frame: &mut Frame, app: &App, area: Rect) {
 }
 
 /// Render basic information about a dependency
-fn render_basic_info(frame: &mut Frame, _app: &App, analysis: &AnalysisResult, dep: &crate::manifest::cargo::CargoDependency, area: Rect) {
+fn render_basic_info(frame: &mut Frame, _app: &App, analysis: &AnalysisResult, dep: &crate::manifest::dependency::Dependency, area: Rect) {
     // Split the area for basic info and features
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
@@ -103,23 +105,87 @@ fn render_basic_info(frame: &mut Frame, _app: &App, analysis: &AnalysisResult, d
     let importance = analysis.metrics.importance_scores.get(&dep.name).unwrap_or(&0.0);
     let is_partially_used = analysis.metrics.is_partially_used.get(&dep.name).unwrap_or(&false);
     let is_removable = analysis.metrics.removable_dependencies.contains(&dep.name);
-    
-    let info_text = vec![
+    let misplacement = analysis.metrics.misplaced.get(&dep.name);
+    let optional_issue = analysis.metrics.optional_issues.get(&dep.name);
+    let exposures = analysis.usage_data.usage_locations.get(&dep.name)
+        .map(|usages| usages.iter().filter(|usage| usage.exposure.is_some() && !usage.inactive).count())
+        .unwrap_or(0);
+    let enabled_by = analysis.features.features_enabling(&dep.name);
+    
+    let mut info_text = vec![
         Line::from(vec![
             Span::styled("Name: ", Style::default().add_modifier(Modifier::BOLD)),
             Span::raw(&dep.name)
         ]),
+        Line::from(vec![
+            Span::styled("Ecosystem: ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(dep.ecosystem().to_string())
+        ]),
+        Line::from(vec![
+            Span::styled("Name in code: ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(match &dep.package {
+                Some(package) => format!("{} (renamed from {})", dep.import_name, package),
+                None => dep.import_name.clone(),
+            })
+        ]),
         Line::from(vec![
             Span::styled("Version: ", Style::default().add_modifier(Modifier::BOLD)),
             Span::raw(dep.version.as_deref().unwrap_or("unknown"))
         ]),
+        Line::from(vec![
+            Span::styled("Source: ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(dep.source.describe(dep.ecosystem()))
+        ]),
         Line::from(vec![
             Span::styled("Type: ", Style::default().add_modifier(Modifier::BOLD)),
             Span::raw(format!("{:?}", dep.dependency_type))
         ]),
+    ];
+    
+    match &dep.extras {
+        DependencyExtras::Rust(cargo) => info_text.extend([
+            Line::from(vec![
+                Span::styled("Platforms: ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(match (cargo.all_platforms, cargo.platforms.is_empty()) {
+                    (true, true) => "all".to_string(),
+                    // Platform-specific declarations then only add features
+                    (true, false) => format!("all (extra declarations for {})", cargo.platforms.join(", ")),
+                    (false, _) => cargo.platforms.join(", "),
+                })
+            ]),
+            Line::from(vec![
+                Span::styled("Default features: ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(if cargo.default_features { "Yes" } else { "No" })
+            ]),
+        ]),
+        DependencyExtras::Node(node) => info_text.push(Line::from(vec![
+            Span::styled("Bundled: ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(if node.bundled { "Yes" } else { "No" })
+        ])),
+        DependencyExtras::Python(python) => info_text.extend([
+            Line::from(vec![
+                Span::styled("Declared in: ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(python.declared_in.join(", "))
+            ]),
+            Line::from(vec![
+                Span::styled("Extras: ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(if python.extras.is_empty() { "none".to_string() } else { python.extras.join(", ") })
+            ]),
+            Line::from(vec![
+                Span::styled("Markers: ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(python.markers.as_deref().unwrap_or("none"))
+            ]),
+        ]),
+    }
+    
+    info_text.extend([
         Line::from(vec![
             Span::styled("Optional: ", Style::default().add_modifier(Modifier::BOLD)),
-            Span::raw(format!("{}", dep.optional))
+            Span::raw(if dep.optional && !enabled_by.is_empty() {
+                format!("true (enabled by {})", enabled_by.join(", "))
+            } else {
+                format!("{}", dep.optional)
+            })
         ]),
         Line::from(vec![
             Span::styled("Used: ", Style::default().add_modifier(Modifier::BOLD)),
@@ -150,7 +216,30 @@ fn render_basic_info(frame: &mut Frame, _app: &App, analysis: &AnalysisResult, d
                 Style::default().fg(if is_removable { Color::Red } else { Color::Green })
             )
         ]),
-    ];
+        Line::from(vec![
+            Span::styled("Public API: ", Style::default().add_modifier(Modifier::BOLD)),
+            if exposures > 0 {
+                // Upgrading a dependency exposed in the public API is a breaking change
+                Span::styled(format!("Yes, {} location(s)", exposures), Style::default().fg(Color::Yellow))
+            } else {
+                Span::raw("No")
+            }
+        ]),
+    ]);
+    
+    if let Some(misplacement) = misplacement {
+        info_text.push(Line::from(vec![
+            Span::styled("Misplaced: ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::styled(misplacement.message(), Style::default().fg(Color::Yellow))
+        ]));
+    }
+    
+    if let Some(issue) = optional_issue {
+        info_text.push(Line::from(vec![
+            Span::styled("Features: ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::styled(issue.message(), Style::default().fg(Color::Yellow))
+        ]));
+    }
     
     let info = Paragraph::new(info_text)
         .block(Block::default().borders(Borders::ALL).title("Basic Information"));
@@ -193,7 +282,7 @@ fn render_basic_info(frame: &mut Frame, _app: &App, analysis: &AnalysisResult, d
 }
 
 /// Render usage metrics for a dependency
-fn render_usage_metrics(frame: &mut Frame, _app: &App, analysis: &AnalysisResult, dep: &crate::manifest::cargo::CargoDependency, area: Rect) {
+fn render_usage_metrics(frame: &mut Frame, _app: &App, analysis: &AnalysisResult, dep: &crate::manifest::dependency::Dependency, area: Rect) {
     // Create static empty maps to use as fallbacks
     static EMPTY_USAGE_TYPES: std::sync::OnceLock<std::collections::HashMap<UsageType, usize>> = std::sync::OnceLock::new();
     static EMPTY_FEATURE_USAGE: std::sync::OnceLock<std::collections::HashMap<String, bool>> = std::sync::OnceLock::new();
@@ -283,31 +372,42 @@ fn render_dependency_graph_info(frame: &mut Frame, _app: &App, analysis: &Analys
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(10),  // Metrics
-            Constraint::Min(0),      // Circular dependencies
+            Constraint::Min(0),      // Direct and transitive dependencies
         ].as_ref())
         .split(area);
     
-    // In our implementation, we need to calculate dependencies from the graph structure
-    // Count the number of nodes in the graph
-    let node_count = analysis.dependency_graph.graph.node_count();
+    let graph = &analysis.dependency_graph;
+    let node_count = graph.graph.node_count();
+    let package = graph.package(dep_name);
+    let direct = graph.direct_dependencies_of(dep_name);
+    let transitive = graph.transitive_dependencies_of(dep_name);
     
-    // Check if this dependency is in any circular dependencies
-    let circular_deps = analysis.dependency_graph.find_circular_dependencies();
-    let dep_name_owned = dep_name.to_string();
-    
-    // Clone the circular_deps vector to avoid borrowing issues
-    let circular_deps_owned: Vec<Vec<String>> = circular_deps.clone();
-    
-    // Now check if this dependency is in any circular dependencies
-    let is_in_circular = circular_deps_owned.iter().any(|cycle| cycle.contains(&dep_name_owned));
+    // Check if this dependency is in any circular dependencies (cycles are reported by package name)
+    let package_name = package.map_or(dep_name, |p| p.name.as_str()).to_string();
+    let circular_deps: Vec<Vec<String>> = graph.find_circular_dependencies()
+        .into_iter()
+        .filter(|cycle| cycle.contains(&package_name))
+        .collect();
+    let is_in_circular = !circular_deps.is_empty();
     
     // Create a summary of the dependency graph
-    let graph_info = vec![
+    let mut graph_info = vec![
+        Line::from(vec![
+            Span::styled("Resolved package: ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(package.map_or_else(|| "not found in Cargo.lock".to_string(), |p| p.to_string()))
+        ]),
+        Line::from(vec![
+            Span::styled("Source: ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(package.and_then(|p| p.source.as_deref()).unwrap_or("local"))
+        ]),
+        Line::from(vec![
+            Span::raw(format!("Direct dependencies: {}", direct.len()))
+        ]),
         Line::from(vec![
-            Span::styled("Dependency Graph:", Style::default().add_modifier(Modifier::BOLD))
+            Span::raw(format!("Transitive dependencies: {}", transitive.len()))
         ]),
         Line::from(vec![
-            Span::raw(format!("Total dependencies in graph: {}", node_count))
+            Span::raw(format!("Total packages in graph: {}", node_count))
         ]),
         Line::from(vec![
             Span::raw("In circular dependency: "),
@@ -318,32 +418,43 @@ fn render_dependency_graph_info(frame: &mut Frame, _app: &App, analysis: &Analys
         ]),
     ];
     
+    for cycle in &circular_deps {
+        graph_info.push(Line::from(vec![
+            Span::raw(format!("• {}", cycle.join(" → ")))
+        ]));
+    }
+    
     let graph_widget = Paragraph::new(graph_info)
         .block(Block::default().borders(Borders::ALL).title("Dependency Graph Info"));
     
     frame.render_widget(graph_widget, chunks[0]);
     
-    // Show circular dependencies if this dependency is in any
-    if is_in_circular {
-        let circular_text: Vec<Line> = circular_deps_owned.iter()
-            .filter(|cycle| cycle.contains(&dep_name_owned))
-            .map(|cycle| {
-                Line::from(vec![
-                    Span::raw(format!("• {}", cycle.join(" → ")))
-                ])
-            })
-            .collect();
-        
-        let circular_widget = Paragraph::new(circular_text)
-            .block(Block::default().borders(Borders::ALL).title("Circular Dependencies"));
-        
-        frame.render_widget(circular_widget, chunks[1]);
-    } else {
-        let no_circular = Paragraph::new("This dependency is not part of any circular dependencies.")
-            .block(Block::default().borders(Borders::ALL).title("Circular Dependencies"));
-        
-        frame.render_widget(no_circular, chunks[1]);
-    }
+    // Show the direct and transitive dependencies side by side
+    let list_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(40),
+            Constraint::Percentage(60),
+        ].as_ref())
+        .split(chunks[1]);
+    
+    let direct_items: Vec<ListItem> = direct.iter()
+        .map(|p| ListItem::new(format!("• {}", p)))
+        .collect();
+    let direct_list = List::new(direct_items)
+        .block(Block::default().borders(Borders::ALL).title(format!("Direct Dependencies ({})", direct.len())))
+        .style(Style::default().fg(Color::White));
+    
+    frame.render_widget(direct_list, list_chunks[0]);
+    
+    let transitive_items: Vec<ListItem> = transitive.iter()
+        .map(|p| ListItem::new(format!("• {}", p)))
+        .collect();
+    let transitive_list = List::new(transitive_items)
+        .block(Block::default().borders(Borders::ALL).title(format!("Transitive Dependencies ({})", transitive.len())))
+        .style(Style::default().fg(Color::White));
+    
+    frame.render_widget(transitive_list, list_chunks[1]);
 }
 
 /// Get color for importance score
@@ -357,6 +468,61 @@ fn importance_color(score: f64) -> Color {
     }
 }
 
+/// Render the distinct items of a dependency the code uses, with a count per kind
+fn render_api_surface(frame: &mut Frame, analysis: &AnalysisResult, dep_name: &str, area: Rect) {
+    let active: Vec<DependencyUsage> = analysis.usage_data.usage_locations.get(dep_name)
+        .map(|usages| usages.iter().filter(|usage| !usage.inactive).cloned().collect())
+        .unwrap_or_default();
+    let items = metrics::api_surface(&active);
+    
+    if items.is_empty() {
+        let no_items = Paragraph::new("No items of this dependency are used")
+            .block(Block::default().borders(Borders::ALL).title("API Surface"));
+        frame.render_widget(no_items, area);
+        return;
+    }
+    
+    // Summarize by kind, e.g. "3 Function, 1 Type"
+    let mut kinds: Vec<(String, usize)> = Vec::new();
+    for item in &items {
+        let kind = format!("{:?}", item.kind);
+        match kinds.iter_mut().find(|(name, _)| *name == kind) {
+            Some((_, count)) => *count += 1,
+            None => kinds.push((kind, 1)),
+        }
+    }
+    let summary = kinds.iter()
+        .map(|(kind, count)| format!("{} {}", count, kind))
+        .collect::<Vec<_>>()
+        .join(", ");
+    
+    let mut list_items = vec![
+        ListItem::new(Line::from(vec![
+            Span::styled(summary, Style::default().add_modifier(Modifier::BOLD))
+        ])),
+    ];
+    list_items.extend(items.iter().map(|item| {
+        ListItem::new(vec![
+            Line::from(vec![
+                Span::styled(item.path.clone(), Style::default().fg(Color::Cyan)),
+                Span::raw(format!("  {:?}, {} usage(s)", item.kind, item.count)),
+            ]),
+            Line::from(vec![
+                Span::raw(format!("  in {}", item.files.iter()
+                    .map(|file| file.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "))),
+            ]),
+        ])
+    }));
+    
+    let list = List::new(list_items)
+        .block(Block::default().borders(Borders::ALL).title(format!("API Surface ({} items)", items.len())))
+        .style(Style::default().fg(Color::White));
+    
+    frame.render_widget(list, area);
+}
+
 /// Render usage locations for a dependency
 fn render_usage_locations(frame: &mut Frame, analysis: &AnalysisResult, dep_name: &str, area: Rect) {
     // Get usage information for the dependency
@@ -370,19 +536,40 @@ fn render_usage_locations(frame: &mut Frame, analysis: &AnalysisResult, dep_name
                 let mut lines = vec![
                     Line::from(vec![
                         Span::styled(
-                            usage.file.to_string_lossy().to_string(),
+                            usage.location(),
                             Style::default().add_modifier(Modifier::BOLD)
                         ),
-                        Span::raw(format!(" (line {})", usage.line))
+                        Span::raw(format!(" (to {}:{})", usage.end_line, usage.end_column)),
+                        Span::styled(
+                            if usage.in_macro { " [in macro]" } else { "" },
+                            Style::default().fg(Color::Magenta)
+                        ),
+                        Span::styled(
+                            if usage.inactive { " [inactive]" } else { "" },
+                            Style::default().fg(Color::DarkGray)
+                        ),
+                        Span::styled(
+                            usage.exposure.map(|exposure| format!(" [{}]", exposure)).unwrap_or_default(),
+                            Style::default().fg(Color::Yellow)
+                        ),
                     ]),
                     Line::from(vec![
                         Span::raw(format!("  Import: {}", usage.imported_item)),
                     ]),
                     Line::from(vec![
-                        Span::raw(format!("  Type: {:?}", usage.usage_type))
+                        Span::raw(format!("  Type: {:?}, target: {:?}", usage.usage_type, usage.target))
+                    ]),
+                    Line::from(vec![
+                        Span::raw(format!("  Module: {}", if usage.module.is_empty() { "unknown" } else { &usage.module }))
                     ])
                 ];
                 
+                if let Some(cfg) = &usage.cfg {
+                    lines.push(Line::from(vec![
+                        Span::raw(format!("  cfg: {}", cfg))
+                    ]));
+                }
+                
                 ListItem::new(lines)
             })
             .collect();
@@ -6,7 +6,7 @@ use ratatui::Frame;
 
 use crate::analyzer::AnalysisResult;
 use crate::tui::app::App;
-use crate::manifest::cargo::DependencyType;
+use crate::manifest::dependency::DependencyType;
 use crate::tui::ui::{PRIMARY_COLOR, SECONDARY_COLOR, ACCENT_COLOR, BG_COLOR, TEXT_COLOR, 
                   HIGHLIGHT_COLOR, SUCCESS_COLOR, WARNING_COLOR, ERROR_COLOR, INACTIVE_COLOR};
 
@@ -21,7 +21,7 @@ pub fn render(frame: &mut Frame, app: &App, area: Rect) {
         ].as_ref())
         .split(area);
     
-    if let Some(analysis) = &app.analysis {
+    if let Some(analysis) = app.current_analysis() {
         // Render dependency summary with visualizations
         render_dependency_summary(frame, analysis, chunks[0]);
         
@@ -71,46 +71,50 @@ fn render_dependency_summary(frame: &mut Frame, analysis: &AnalysisResult, area:
         .filter(|(_, &is_used)| !is_used)
         .count();
     let removable_deps = analysis.metrics.removable_dependencies.len();
+    let orphan_files = analysis.usage_data.orphan_files.len();
+    
+    // Share of all dependencies; a package without dependencies gets empty gauges
+    let ratio = |count: usize| if total_deps == 0 { 0.0 } else { count as f64 / total_deps as f64 };
     
     // Create gauges for different metrics
     let normal_gauge = Gauge::default()
         .block(Block::default().borders(Borders::NONE))
         .gauge_style(Style::default().fg(PRIMARY_COLOR).bg(BG_COLOR))
-        .ratio((normal_deps as f64) / (total_deps as f64))
+        .ratio(ratio(normal_deps))
         .label(format!("Normal: {}/{} ({}%)", 
             normal_deps, 
             total_deps,
-            (normal_deps as f64 * 100.0 / total_deps as f64) as u32
+            (ratio(normal_deps) * 100.0) as u32
         ));
     
     let dev_gauge = Gauge::default()
         .block(Block::default().borders(Borders::NONE))
         .gauge_style(Style::default().fg(SECONDARY_COLOR).bg(BG_COLOR))
-        .ratio((dev_deps as f64) / (total_deps as f64))
+        .ratio(ratio(dev_deps))
         .label(format!("Dev: {}/{} ({}%)", 
             dev_deps, 
             total_deps,
-            (dev_deps as f64 * 100.0 / total_deps as f64) as u32
+            (ratio(dev_deps) * 100.0) as u32
         ));
     
     let build_gauge = Gauge::default()
         .block(Block::default().borders(Borders::NONE))
         .gauge_style(Style::default().fg(ACCENT_COLOR).bg(BG_COLOR))
-        .ratio((build_deps as f64) / (total_deps as f64))
+        .ratio(ratio(build_deps))
         .label(format!("Build: {}/{} ({}%)", 
             build_deps, 
             total_deps,
-            (build_deps as f64 * 100.0 / total_deps as f64) as u32
+            (ratio(build_deps) * 100.0) as u32
         ));
     
     let removable_gauge = Gauge::default()
         .block(Block::default().borders(Borders::NONE))
         .gauge_style(Style::default().fg(ERROR_COLOR).bg(BG_COLOR))
-        .ratio((removable_deps as f64) / (total_deps as f64))
+        .ratio(ratio(removable_deps))
         .label(format!("Removable: {}/{} ({}%)", 
             removable_deps, 
             total_deps,
-            (removable_deps as f64 * 100.0 / total_deps as f64) as u32
+            (ratio(removable_deps) * 100.0) as u32
         ));
     
     // Create a block for the summary section
@@ -137,6 +141,7 @@ fn render_dependency_summary(frame: &mut Frame, analysis: &AnalysisResult, area:
             Constraint::Length(1),
             Constraint::Length(1),
             Constraint::Length(1),
+            Constraint::Length(1),
         ].as_ref())
         .split(gauge_area);
     
@@ -146,6 +151,13 @@ fn render_dependency_summary(frame: &mut Frame, analysis: &AnalysisResult, area:
     frame.render_widget(build_gauge, gauge_chunks[2]);
     frame.render_widget(removable_gauge, gauge_chunks[3]);
     
+    // Files outside every target's module tree were not analyzed, which can hide usages
+    if orphan_files > 0 {
+        let orphans = Paragraph::new(format!("{} orphan file(s) not reachable from any target root", orphan_files))
+            .style(Style::default().fg(WARNING_COLOR));
+        frame.render_widget(orphans, gauge_chunks[4]);
+    }
+    
     // Create bars for the chart in the right column
     let chart_data = [
         ("Normal", normal_deps, PRIMARY_COLOR),
@@ -165,12 +177,6 @@ fn render_dependency_summary(frame: &mut Frame, analysis: &AnalysisResult, area:
         height: chunks[1].height - 4,
     };
     
-    // Create bars with styled labels
-    let bars: Vec<(String, u64)> = chart_data
-        .iter()
-        .map(|(name, count, _)| (name.to_string(), *count as u64))
-        .collect();
-    
     // Create custom styled bars
     let mut styled_rows = Vec::new();
     for (_, (name, count, color)) in chart_data.iter().enumerate() {
@@ -233,6 +239,7 @@ fn render_dependencies_list(frame: &mut Frame, app: &App, analysis: &AnalysisRes
                 DependencyType::Normal => "📦",
                 DependencyType::Development => "🔧",
                 DependencyType::Build => "🏗️",
+                DependencyType::Peer => "🤝",
             };
             
             // Show removable indicator
@@ -241,7 +248,7 @@ fn render_dependencies_list(frame: &mut Frame, app: &App, analysis: &AnalysisRes
             // Create mini usage graph using unicode block characters
             let max_graph_width = 10;
             let graph_width = ((usage_count * max_graph_width) / 
-                analysis.metrics.usage_count.values().map(|v| *v).max().unwrap_or(1)).min(max_graph_width);
+                analysis.metrics.usage_count.values().map(|v| *v).max().unwrap_or(0).max(1)).min(max_graph_width);
             let usage_graph = "█".repeat(graph_width);
             let empty_graph = "░".repeat(max_graph_width - graph_width);
             
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::fs;
 use anyhow::{Result, Context};
@@ -22,6 +23,10 @@ pub struct Config {
     /// TUI configuration options
     #[serde(default)]
     pub tui: TuiConfig,
+    
+    /// Policies for the `check` command
+    #[serde(default)]
+    pub check: CheckConfig,
 }
 
 /// General configuration options
@@ -63,8 +68,47 @@ pub struct AnalysisConfig {
     /// Whether to follow symlinks during analysis
     pub follow_symlinks: bool,
     
-    /// List of globs to exclude from analysis
+    /// List of globs to exclude from analysis, relative to the project directory
     pub exclude_patterns: Vec<String>,
+    
+    /// Whether to skip files ignored by `.gitignore` and `.ignore` files
+    #[serde(default = "default_true")]
+    pub respect_ignore_files: bool,
+    
+    /// Whether to cache per-file results in `target/why` so unchanged files are not parsed again
+    #[serde(default = "default_true")]
+    pub use_cache: bool,
+    
+    /// Whether to only analyze files reachable through `mod` declarations from a target root
+    /// (`src/lib.rs`, `src/main.rs`, `build.rs`, ...) instead of every `.rs` file
+    #[serde(default = "default_true")]
+    pub follow_modules: bool,
+    
+    /// Features to evaluate `#[cfg(feature = "...")]` predicates with, in addition to the default
+    /// features. When empty, code behind any feature counts as used.
+    #[serde(default)]
+    pub features: Vec<String>,
+    
+    /// Evaluate cfg predicates with every feature enabled
+    #[serde(default)]
+    pub all_features: bool,
+    
+    /// Target cfgs such as `unix` or `target_os=linux` to evaluate cfg predicates with.
+    /// When empty, code for any target counts as used.
+    #[serde(default)]
+    pub target_cfg: Vec<String>,
+    
+    /// Methods of traits that crates are mainly used through, keyed by trait path
+    /// (`itertools::Itertools`) or by a module whose glob import brings traits into scope
+    /// (`rayon::prelude`). Extends the built-in map of popular crates.
+    #[serde(default)]
+    pub trait_methods: BTreeMap<String, Vec<String>>,
+    
+    /// Modules Python distributions are imported as, keyed by distribution name, for
+    /// distributions not imported by their own name (`PyYAML` as `yaml`). Takes precedence
+    /// over the built-in map of popular distributions.
+    #[serde(default)]
+    pub python_modules: BTreeMap<String, Vec<String>>,
 }
 
 impl Default for AnalysisConfig {
@@ -76,8 +120,17 @@ impl Default for AnalysisConfig {
             exclude_patterns: vec![
                 "**/target/**".to_string(),
                 "**/node_modules/**".to_string(),
+                "**/.venv/**".to_string(),
                 "**/.git/**".to_string(),
             ],
+            respect_ignore_files: true,
+            use_cache: true,
+            follow_modules: true,
+            features: Vec::new(),
+            all_features: false,
+            target_cfg: Vec::new(),
+            trait_methods: BTreeMap::new(),
+            python_modules: BTreeMap::new(),
         }
     }
 }
@@ -133,6 +186,47 @@ impl Default for TuiConfig {
     }
 }
 
+/// Policies deciding which `check` findings fail the run
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CheckConfig {
+    /// Fail when a normal dependency is never used
+    pub fail_on_unused: bool,
+    
+    /// Fail when a dev or build dependency is never used (otherwise only warn)
+    pub fail_on_unused_dev: bool,
+    
+    /// Fail when a used dependency scores below `analysis.removal_threshold` (otherwise only warn)
+    pub fail_on_low_importance: bool,
+    
+    /// Fail when a dependency is declared in the wrong table for the targets using it (otherwise only warn)
+    pub fail_on_misplaced: bool,
+    
+    /// Fail when an optional dependency is not enabled by any feature, or is used by code
+    /// not guarded by a cfg for a feature enabling it (otherwise only warn)
+    pub fail_on_optional: bool,
+    
+    /// Fail when a feature of the package enables nothing the code uses (otherwise only warn)
+    pub fail_on_unused_features: bool,
+    
+    /// Dependencies that are never reported
+    pub ignore: Vec<String>,
+}
+
+impl Default for CheckConfig {
+    fn default() -> Self {
+        Self {
+            fail_on_unused: true,
+            fail_on_unused_dev: false,
+            fail_on_low_importance: false,
+            fail_on_misplaced: false,
+            fail_on_optional: false,
+            fail_on_unused_features: false,
+            ignore: Vec::new(),
+        }
+    }
+}
+
 /// Color scheme configuration
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ColorScheme {
@@ -160,6 +254,10 @@ impl Default for ColorScheme {
     }
 }
 
+fn default_true() -> bool {
+    true
+}
+
 impl Config {
     /// Load the configuration from a file
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
@@ -205,6 +303,7 @@ impl Default for Config {
             analysis: AnalysisConfig::default(),
             export: ExportConfig::default(),
             tui: TuiConfig::default(),
+            check: CheckConfig::default(),
         }
     }
 } 
\ No newline at end of file
@@ -0,0 +1,189 @@
+/// Number of unchanged lines shown around each change
+const CONTEXT_LINES: usize = 3;
+
+/// A line of the diff together with the number of old and new lines before it
+struct DiffLine<'a> {
+    tag: char,
+    /// Text of the line, with its line break unless it ends a text lacking a final newline
+    text: &'a str,
+    old_before: usize,
+    new_before: usize,
+}
+
+/// Render the differences between two texts as a unified diff
+pub fn unified_diff(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    let mut lines = Vec::new();
+    let (mut old_line, mut new_line) = (0, 0);
+    
+    // Compare lines with their line breaks, so that a final line losing or gaining its
+    // newline shows up as changed
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+    
+    for result in diff::slice(&old_lines, &new_lines) {
+        let (tag, text) = match result {
+            diff::Result::Left(text) => ('-', text),
+            diff::Result::Both(text, _) => (' ', text),
+            diff::Result::Right(text) => ('+', text),
+        };
+        
+        lines.push(DiffLine { tag, text, old_before: old_line, new_before: new_line });
+        
+        if tag != '+' {
+            old_line += 1;
+        }
+        if tag != '-' {
+            new_line += 1;
+        }
+    }
+    
+    let changes: Vec<usize> = lines.iter()
+        .enumerate()
+        .filter(|(_, line)| line.tag != ' ')
+        .map(|(i, _)| i)
+        .collect();
+    
+    if changes.is_empty() {
+        return String::new();
+    }
+    
+    // Group changes whose context would overlap into the same hunk
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for &change in &changes {
+        let start = change.saturating_sub(CONTEXT_LINES);
+        let end = (change + CONTEXT_LINES + 1).min(lines.len());
+        
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+    
+    let mut output = format!("--- {}\n+++ {}\n", old_label, new_label);
+    
+    for (start, end) in hunks {
+        let hunk = &lines[start..end];
+        let old_count = hunk.iter().filter(|line| line.tag != '+').count();
+        let new_count = hunk.iter().filter(|line| line.tag != '-').count();
+        
+        // Empty ranges point at the line before them, as in `diff -u`
+        let old_start = hunk[0].old_before + usize::from(old_count > 0);
+        let new_start = hunk[0].new_before + usize::from(new_count > 0);
+        
+        output.push_str(&format!("@@ -{},{} +{},{} @@\n", old_start, old_count, new_start, new_count));
+        for line in hunk {
+            output.push(line.tag);
+            output.push_str(line.text);
+            if !line.text.ends_with('\n') {
+                output.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+    
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::fs;
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+    
+    /// Lines `1` to `count`, each with a newline, with the given lines replaced
+    fn numbers(count: usize, replaced: &[(usize, &str)]) -> String {
+        (1..=count)
+            .map(|n| match replaced.iter().find(|(line, _)| *line == n) {
+                Some((_, text)) => format!("{}\n", text),
+                None => format!("{}\n", n),
+            })
+            .collect()
+    }
+    
+    /// Diff of `old` and `new` without its file header
+    fn hunks(old: &str, new: &str) -> String {
+        let diff = unified_diff(old, new, "a", "b");
+        diff.strip_prefix("--- a\n+++ b\n").expect("diff header").to_string()
+    }
+    
+    /// Apply the diff of `old` and `new` to `old` with `patch` and return the patched text
+    fn patched(old: &str, new: &str) -> String {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file");
+        fs::write(&path, old).unwrap();
+        
+        let mut child = Command::new("patch")
+            .arg("--quiet")
+            .arg(&path)
+            .stdin(Stdio::piped())
+            .spawn()
+            .expect("patch should be installed");
+        child.stdin.take().unwrap().write_all(unified_diff(old, new, "a", "b").as_bytes()).unwrap();
+        assert!(child.wait().unwrap().success());
+        
+        fs::read_to_string(&path).unwrap()
+    }
+    
+    #[test]
+    fn returns_nothing_for_identical_texts() {
+        assert_eq!(unified_diff("a\nb\n", "a\nb\n", "a", "b"), "");
+    }
+    
+    #[test]
+    fn diffs_a_change_at_the_end() {
+        let (old, new) = ("a\nb\nc\n", "a\nb\nd\n");
+        
+        assert_eq!(hunks(old, new), "@@ -1,3 +1,3 @@\n a\n b\n-c\n+d\n");
+        assert_eq!(patched(old, new), new);
+    }
+    
+    #[test]
+    fn diffs_a_change_in_the_middle() {
+        let (old, new) = (numbers(9, &[]), numbers(9, &[(5, "five")]));
+        
+        assert_eq!(hunks(&old, &new), "@@ -2,7 +2,7 @@\n 2\n 3\n 4\n-5\n+five\n 6\n 7\n 8\n");
+        assert_eq!(patched(&old, &new), new);
+    }
+    
+    #[test]
+    fn merges_changes_with_overlapping_context() {
+        let (old, new) = (numbers(12, &[]), numbers(12, &[(3, "three"), (8, "eight")]));
+        
+        assert_eq!(
+            hunks(&old, &new),
+            "@@ -1,11 +1,11 @@\n 1\n 2\n-3\n+three\n 4\n 5\n 6\n 7\n-8\n+eight\n 9\n 10\n 11\n"
+        );
+        assert_eq!(patched(&old, &new), new);
+    }
+    
+    #[test]
+    fn splits_distant_changes_into_hunks() {
+        let (old, new) = (numbers(16, &[]), numbers(16, &[(2, "two"), (14, "fourteen")]));
+        
+        assert_eq!(
+            hunks(&old, &new),
+            "@@ -1,5 +1,5 @@\n 1\n-2\n+two\n 3\n 4\n 5\n@@ -11,6 +11,6 @@\n 11\n 12\n 13\n-14\n+fourteen\n 15\n 16\n"
+        );
+        assert_eq!(patched(&old, &new), new);
+    }
+    
+    #[test]
+    fn diffs_removed_lines_at_the_end() {
+        let (old, new) = ("a\nb\nc\n", "a\n");
+        
+        assert_eq!(hunks(old, new), "@@ -1,3 +1,1 @@\n a\n-b\n-c\n");
+        assert_eq!(patched(old, new), new);
+    }
+    
+    #[test]
+    fn marks_lines_without_final_newline() {
+        let (old, new) = ("a\nb", "a\nc\n");
+        assert_eq!(hunks(old, new), "@@ -1,2 +1,2 @@\n a\n-b\n\\ No newline at end of file\n+c\n");
+        assert_eq!(patched(old, new), new);
+        
+        let (old, new) = ("a\nb\n", "a\nb");
+        assert_eq!(hunks(old, new), "@@ -1,2 +1,2 @@\n a\n-b\n+b\n\\ No newline at end of file\n");
+        assert_eq!(patched(old, new), new);
+    }
+}
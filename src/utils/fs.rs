@@ -1,8 +1,15 @@
+use std::fs;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
-use anyhow::Result;
+use anyhow::{Result, Context};
+use glob::Pattern;
+
+use crate::manifest::{is_manifest_file_name, manifest_precedence};
+use crate::utils::config::Config;
+use crate::utils::ignore::IgnoreRules;
 
 /// Find a file in a directory tree
+#[allow(dead_code)]
 pub fn find_file<P: AsRef<Path>>(dir: P, filename: &str) -> Option<PathBuf> {
     WalkDir::new(dir)
         .into_iter()
@@ -11,34 +18,263 @@ pub fn find_file<P: AsRef<Path>>(dir: P, filename: &str) -> Option<PathBuf> {
         .map(|entry| entry.path().to_path_buf())
 }
 
-/// Find all Rust source files in a directory
+/// Find all Rust source files in a directory, using the default analysis configuration
+#[allow(dead_code)]
 pub fn find_rust_files<P: AsRef<Path>>(dir: P) -> Result<Vec<PathBuf>> {
-    let mut rust_files = Vec::new();
+    let dir = dir.as_ref();
+    let walker = FileWalker::new(dir, &Config::default())?;
     
-    for entry in WalkDir::new(dir)
-        .into_iter()
-        .filter_map(Result::ok)
-        .filter(|e| !is_excluded(e.path()))
-        .filter(|e| e.path().extension().map_or(false, |ext| ext == "rs"))
-    {
-        rust_files.push(entry.path().to_path_buf());
+    Ok(walker.files(dir, "rs"))
+}
+
+/// Walks source directories according to the loaded configuration: exclude patterns,
+/// symlink handling, maximum depth and `.gitignore`/`.ignore` files
+#[derive(Debug, Clone)]
+pub struct FileWalker {
+    /// Directory exclude patterns are relative to
+    base: PathBuf,
+    /// Patterns matching excluded files
+    exclude: Vec<Pattern>,
+    /// Patterns matching excluded directories, derived from `dir/**` patterns so whole trees are skipped
+    exclude_dirs: Vec<Pattern>,
+    /// Directories skipped entirely (e.g. nested workspace members)
+    excluded_dirs: Vec<PathBuf>,
+    follow_symlinks: bool,
+    max_depth: usize,
+    respect_ignore_files: bool,
+}
+
+impl FileWalker {
+    /// Create a walker for a project rooted at `base`
+    pub fn new<P: AsRef<Path>>(base: P, config: &Config) -> Result<Self> {
+        let mut exclude = Vec::new();
+        let mut exclude_dirs = Vec::new();
+        
+        for glob in &config.analysis.exclude_patterns {
+            let pattern = Pattern::new(glob)
+                .with_context(|| format!("Invalid exclude pattern {:?}", glob))?;
+            
+            if let Some(dir_glob) = glob.strip_suffix("/**") {
+                exclude_dirs.push(Pattern::new(dir_glob)
+                    .with_context(|| format!("Invalid exclude pattern {:?}", glob))?);
+            }
+            exclude.push(pattern);
+        }
+        
+        Ok(Self {
+            base: base.as_ref().to_path_buf(),
+            exclude,
+            exclude_dirs,
+            excluded_dirs: Vec::new(),
+            follow_symlinks: config.analysis.follow_symlinks,
+            max_depth: config.general.max_search_depth,
+            respect_ignore_files: config.analysis.respect_ignore_files,
+        })
+    }
+    
+    /// Skip the given directories in addition to the configured exclusions
+    pub fn with_excluded_dirs(mut self, excluded_dirs: Vec<PathBuf>) -> Self {
+        self.excluded_dirs = excluded_dirs;
+        self
+    }
+    
+    /// Find the files with the given extension below `root`, in a stable order
+    pub fn files<P: AsRef<Path>>(&self, root: P, extension: &str) -> Vec<PathBuf> {
+        self.files_with_extensions(root, &[extension])
+    }
+    
+    /// Find the files with any of the given extensions below `root`, in a stable order
+    pub fn files_with_extensions<P: AsRef<Path>>(&self, root: P, extensions: &[&str]) -> Vec<PathBuf> {
+        self.walk(root.as_ref(), true, |path| {
+            path.extension().is_some_and(|ext| extensions.iter().any(|extension| ext == *extension))
+        })
+    }
+    
+    /// Find the files whose name is accepted by `is_wanted` below `root`, in a stable order
+    pub fn files_named<P: AsRef<Path>>(&self, root: P, is_wanted: impl Fn(&str) -> bool) -> Vec<PathBuf> {
+        // Directories beyond the maximum depth are reported when their source files are looked for
+        self.walk(root.as_ref(), false, |path| {
+            path.file_name().and_then(|name| name.to_str()).is_some_and(&is_wanted)
+        })
+    }
+    
+    /// Walk `root` and collect the files accepted by `keep`, optionally warning about
+    /// directories skipped for being deeper than the maximum depth
+    fn walk(&self, root: &Path, warn_too_deep: bool, keep: impl Fn(&Path) -> bool) -> Vec<PathBuf> {
+        let (files, too_deep) = self.scan(root, keep);
+        
+        if warn_too_deep {
+            for dir in too_deep {
+                eprintln!("Warning: Skipping {:?}, it is deeper than max_search_depth ({})", dir, self.max_depth);
+            }
+        }
+        
+        files
+    }
+    
+    /// Walk `root` and collect the files accepted by `keep`, along with the non-empty
+    /// directories whose contents are deeper than the maximum depth
+    fn scan(&self, root: &Path, keep: impl Fn(&Path) -> bool) -> (Vec<PathBuf>, Vec<PathBuf>) {
+        let ignore_rules = self.respect_ignore_files.then(|| IgnoreRules::new(root));
+        let mut files = Vec::new();
+        let mut too_deep = Vec::new();
+        
+        let walker = WalkDir::new(root)
+            .follow_links(self.follow_symlinks)
+            .max_depth(self.max_depth)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|entry| {
+                let is_dir = entry.file_type().is_dir();
+                
+                if self.is_excluded(entry.path(), is_dir) {
+                    return false;
+                }
+                
+                // The walked root itself is never ignored
+                match (&ignore_rules, entry.path().strip_prefix(root)) {
+                    (Some(rules), Ok(relative)) if entry.depth() > 0 => !rules.is_ignored(relative, is_dir),
+                    _ => true,
+                }
+            });
+        
+        for entry in walker.filter_map(Result::ok) {
+            if entry.file_type().is_dir() {
+                if entry.depth() == self.max_depth && has_entries(entry.path()) {
+                    too_deep.push(entry.path().to_path_buf());
+                }
+                continue;
+            }
+            
+            if keep(entry.path()) {
+                files.push(entry.path().to_path_buf());
+            }
+        }
+        
+        (files, too_deep)
     }
     
-    Ok(rust_files)
+    /// Check if a path is excluded by the configured patterns or excluded directories
+    fn is_excluded(&self, path: &Path, is_dir: bool) -> bool {
+        if self.excluded_dirs.iter().any(|dir| path.starts_with(dir)) {
+            return true;
+        }
+        
+        let relative = path.strip_prefix(&self.base).unwrap_or(path);
+        let patterns = if is_dir { &self.exclude_dirs } else { &self.exclude };
+        
+        patterns.iter().any(|pattern| pattern.matches_path(relative))
+    }
 }
 
-/// Check if a path should be excluded from analysis
-pub fn is_excluded(path: &Path) -> bool {
-    let path_str = path.to_string_lossy();
-    path_str.contains("/target/") || path_str.contains("/.git/")
+/// Whether a directory has any entries
+fn has_entries(dir: &Path) -> bool {
+    fs::read_dir(dir).is_ok_and(|mut entries| entries.next().is_some())
 }
 
-/// Find the project's manifest file
+/// Find the project's manifest file, of any supported ecosystem
+#[allow(dead_code)]
 pub fn find_manifest_file<P: AsRef<Path>>(dir: P) -> Option<PathBuf> {
-    let cargo_toml = dir.as_ref().join("Cargo.toml");
-    if cargo_toml.exists() {
-        return Some(cargo_toml);
+    fs::read_dir(dir).ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter_map(|path| {
+            let file_name = path.file_name()?.to_str()?;
+            is_manifest_file_name(file_name).then(|| (manifest_precedence(file_name), path.clone()))
+        })
+        .min()
+        .map(|(_, path)| path)
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+    
+    /// A directory containing `files`, given as relative paths and contents
+    fn tree(files: &[(&str, &str)]) -> TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for (path, content) in files {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, content).unwrap();
+        }
+        dir
+    }
+    
+    /// Paths of `files` relative to `dir`
+    fn relative(dir: &TempDir, files: Vec<PathBuf>) -> Vec<String> {
+        files.iter()
+            .map(|file| file.strip_prefix(dir.path()).unwrap().to_string_lossy().replace('\\', "/"))
+            .collect()
     }
     
-    None
-} 
\ No newline at end of file
+    #[test]
+    fn skips_excluded_and_ignored_files() {
+        let dir = tree(&[
+            (".git/HEAD", ""),
+            (".gitignore", "generated/\n"),
+            ("src/lib.rs", ""),
+            ("src/generated/bindings.rs", ""),
+            ("target/debug/build.rs", ""),
+            ("tests/it.rs", ""),
+        ]);
+        
+        let walker = FileWalker::new(dir.path(), &Config::default()).unwrap();
+        assert_eq!(relative(&dir, walker.files(dir.path(), "rs")), vec!["src/lib.rs", "tests/it.rs"]);
+        
+        let mut config = Config::default();
+        config.analysis.respect_ignore_files = false;
+        let walker = FileWalker::new(dir.path(), &config).unwrap();
+        assert_eq!(relative(&dir, walker.files(dir.path(), "rs")), vec![
+            "src/generated/bindings.rs",
+            "src/lib.rs",
+            "tests/it.rs",
+        ]);
+    }
+    
+    #[test]
+    fn prunes_directories_excluded_with_a_double_star() {
+        let dir = tree(&[
+            ("src/lib.rs", ""),
+            ("vendor/a/b/c/lib.rs", ""),
+        ]);
+        let mut config = Config::default();
+        config.analysis.exclude_patterns = vec!["vendor/**".to_string()];
+        config.general.max_search_depth = 2;
+        
+        // The excluded tree is not walked, so it is not reported as too deep either
+        let walker = FileWalker::new(dir.path(), &config).unwrap();
+        let (files, too_deep) = walker.scan(dir.path(), |_| true);
+        assert_eq!(relative(&dir, files), vec!["src/lib.rs"]);
+        assert_eq!(too_deep, Vec::<PathBuf>::new());
+    }
+    
+    #[test]
+    fn reports_directories_deeper_than_the_maximum_depth() {
+        let dir = tree(&[
+            ("src/lib.rs", ""),
+            ("src/net/mod.rs", ""),
+            ("src/net/http/client.rs", ""),
+        ]);
+        fs::create_dir_all(dir.path().join("src/empty")).unwrap();
+        let mut config = Config::default();
+        config.general.max_search_depth = 2;
+        
+        let walker = FileWalker::new(dir.path(), &config).unwrap();
+        let (files, too_deep) = walker.scan(dir.path(), |_| true);
+        assert_eq!(relative(&dir, files), vec!["src/lib.rs"]);
+        assert_eq!(relative(&dir, too_deep), vec!["src/net"]);
+    }
+    
+    #[test]
+    fn rejects_invalid_exclude_patterns() {
+        let mut config = Config::default();
+        config.analysis.exclude_patterns = vec!["src/[".to_string()];
+        
+        let err = FileWalker::new(".", &config).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid exclude pattern \"src/[\"");
+    }
+}
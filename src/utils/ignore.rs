@@ -0,0 +1,231 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use glob::{MatchOptions, Pattern};
+
+/// Ignore files read in every directory, later files taking precedence
+const IGNORE_FILES: [&str; 2] = [".gitignore", ".ignore"];
+
+/// Wildcards never match `/`, as in gitignore
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// A single pattern line of an ignore file
+struct IgnoreRule {
+    pattern: Pattern,
+    /// `!pattern` re-includes paths excluded by earlier rules
+    negated: bool,
+    /// `pattern/` only matches directories
+    dir_only: bool,
+    /// Patterns containing a `/` match relative to the ignore file's directory,
+    /// others match the file name at any depth
+    anchored: bool,
+}
+
+impl IgnoreRule {
+    /// Parse a line of an ignore file, skipping blank lines, comments and invalid patterns
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        
+        let anchored = line.contains('/');
+        let pattern = Pattern::new(line.trim_start_matches('/')).ok()?;
+        
+        Some(Self {
+            pattern,
+            negated,
+            dir_only,
+            anchored,
+        })
+    }
+    
+    /// Whether the rule matches a path relative to the ignore file's directory
+    fn matches(&self, relative: &Path, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        
+        if self.anchored {
+            self.pattern.matches_path_with(relative, MATCH_OPTIONS)
+        } else {
+            relative.file_name()
+                .is_some_and(|name| self.pattern.matches_with(&name.to_string_lossy(), MATCH_OPTIONS))
+        }
+    }
+}
+
+/// `.gitignore` and `.ignore` rules applying below a directory, including those of
+/// parent directories up to the enclosing git repository
+pub struct IgnoreRules {
+    /// Canonical form of the directory being walked
+    root: PathBuf,
+    /// Topmost directory whose ignore files apply
+    top: PathBuf,
+    /// Rules per directory, loaded on first use
+    rules: RefCell<HashMap<PathBuf, Rc<Vec<IgnoreRule>>>>,
+}
+
+impl IgnoreRules {
+    /// Collect the ignore rules for walking `root`
+    pub fn new(root: &Path) -> Self {
+        let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        
+        // Ignore files above the walked directory apply as long as they are in the same repository
+        let top = root.ancestors()
+            .find(|dir| dir.join(".git").exists())
+            .unwrap_or(&root)
+            .to_path_buf();
+        
+        Self {
+            root,
+            top,
+            rules: RefCell::new(HashMap::new()),
+        }
+    }
+    
+    /// Whether a path, relative to the walked directory, is ignored
+    pub fn is_ignored(&self, relative: &Path, is_dir: bool) -> bool {
+        let path = self.root.join(relative);
+        
+        // Rules of deeper directories override those of their parents; the last match wins
+        let mut dirs: Vec<&Path> = path.ancestors()
+            .skip(1)
+            .take_while(|dir| dir.starts_with(&self.top))
+            .collect();
+        dirs.reverse();
+        
+        let mut ignored = false;
+        for dir in dirs {
+            let relative = match path.strip_prefix(dir) {
+                Ok(relative) => relative,
+                Err(_) => continue,
+            };
+            
+            for rule in self.rules_for(dir).iter() {
+                if rule.matches(relative, is_dir) {
+                    ignored = !rule.negated;
+                }
+            }
+        }
+        
+        ignored
+    }
+    
+    /// Load the rules declared by the ignore files of a directory
+    fn rules_for(&self, dir: &Path) -> Rc<Vec<IgnoreRule>> {
+        self.rules.borrow_mut()
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| {
+                let rules = IGNORE_FILES.iter()
+                    .filter_map(|name| fs::read_to_string(dir.join(name)).ok())
+                    .flat_map(|content| content.lines().filter_map(IgnoreRule::parse).collect::<Vec<_>>())
+                    .collect();
+                Rc::new(rules)
+            })
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    
+    /// A directory containing `files`, given as relative paths and contents; paths ending with
+    /// `/` are created as empty directories
+    fn tree(files: &[(&str, &str)]) -> TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for (path, content) in files {
+            let path = dir.path().join(path);
+            if path.to_string_lossy().ends_with('/') {
+                fs::create_dir_all(&path).unwrap();
+            } else {
+                fs::create_dir_all(path.parent().unwrap()).unwrap();
+                fs::write(&path, content).unwrap();
+            }
+        }
+        dir
+    }
+    
+    #[test]
+    fn negated_patterns_re_include_paths() {
+        let dir = tree(&[
+            (".git/", ""),
+            (".gitignore", "*.log\n!keep.log\n"),
+            ("logs/.ignore", "!debug.log\n"),
+        ]);
+        let rules = IgnoreRules::new(dir.path());
+        
+        assert!(rules.is_ignored(Path::new("error.log"), false));
+        assert!(!rules.is_ignored(Path::new("keep.log"), false));
+        assert!(rules.is_ignored(Path::new("logs/error.log"), false));
+        assert!(!rules.is_ignored(Path::new("logs/debug.log"), false));
+    }
+    
+    #[test]
+    fn directory_patterns_only_match_directories() {
+        let dir = tree(&[
+            (".git/", ""),
+            (".gitignore", "build/\n"),
+        ]);
+        let rules = IgnoreRules::new(dir.path());
+        
+        assert!(rules.is_ignored(Path::new("build"), true));
+        assert!(rules.is_ignored(Path::new("src/build"), true));
+        assert!(!rules.is_ignored(Path::new("build"), false));
+    }
+    
+    #[test]
+    fn patterns_with_a_slash_are_anchored_to_their_directory() {
+        let dir = tree(&[
+            (".git/", ""),
+            (".gitignore", "/generated.rs\nsrc/*.tmp\nscratch.rs\n"),
+        ]);
+        let rules = IgnoreRules::new(dir.path());
+        
+        assert!(rules.is_ignored(Path::new("generated.rs"), false));
+        assert!(!rules.is_ignored(Path::new("src/generated.rs"), false));
+        
+        assert!(rules.is_ignored(Path::new("src/cache.tmp"), false));
+        assert!(!rules.is_ignored(Path::new("src/nested/cache.tmp"), false));
+        assert!(!rules.is_ignored(Path::new("cache.tmp"), false));
+        
+        assert!(rules.is_ignored(Path::new("scratch.rs"), false));
+        assert!(rules.is_ignored(Path::new("src/nested/scratch.rs"), false));
+    }
+    
+    #[test]
+    fn applies_ignore_files_of_parent_directories_up_to_the_repository() {
+        let dir = tree(&[
+            (".gitignore", "*.rs\n"),
+            ("repo/.git/", ""),
+            ("repo/.gitignore", "*.bak\n"),
+            ("repo/crates/app/.ignore", "/fixtures\n"),
+        ]);
+        let rules = IgnoreRules::new(&dir.path().join("repo/crates/app"));
+        
+        assert!(rules.is_ignored(Path::new("src/lib.bak"), false));
+        assert!(rules.is_ignored(Path::new("fixtures"), true));
+        assert!(!rules.is_ignored(Path::new("src/fixtures"), true));
+        
+        // The ignore file above the repository does not apply
+        assert!(!rules.is_ignored(Path::new("src/lib.rs"), false));
+    }
+}
@@ -1,2 +1,4 @@
 pub mod fs;
-pub mod config; 
\ No newline at end of file
+pub mod config;
+pub mod diff;
+pub mod ignore;
\ No newline at end of file